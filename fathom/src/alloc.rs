@@ -92,3 +92,10 @@ pub unsafe fn slice_assume_init_ref<'a, T>(slice: &'a [MaybeUninit<T>]) -> &'a [
     // reference and thus guaranteed to be valid for reads.
     &*(slice as *const [MaybeUninit<T>] as *const [T])
 }
+
+/// Copy a string into the scope.
+pub fn str_to_scope<'a>(scope: &'a scoped_arena::Scope<'a>, string: &str) -> &'a str {
+    let bytes = scope.to_scope_from_iter(string.bytes());
+    // SAFETY: The bytes were copied from a valid UTF-8 string.
+    unsafe { std::str::from_utf8_unchecked(bytes) }
+}
@@ -1,6 +1,9 @@
 //! Core language.
 
-use crate::env::{GlobalVar, LocalVar};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use crate::env::{self, EnvLen, GlobalVar, LocalVar};
 use crate::StringId;
 
 pub mod binary;
@@ -19,6 +22,20 @@ pub enum EntryInfo {
     Parameter,
 }
 
+/// Universe levels.
+///
+/// Universes are arranged in a cumulative hierarchy, where each universe is
+/// an element of the universe one level above it, and every type in a universe
+/// is also a type in the universes above it:
+///
+/// ```text
+/// Type : Type1 : Type2 : ...
+/// ```
+///
+/// This prevents the paradoxes that arise from `Type : Type`, for example
+/// [Hurkens' paradox](https://doi.org/10.1007/BFb0014058).
+pub type UniverseLevel = u32;
+
 /// Core language terms.
 #[derive(Debug, Clone)]
 pub enum Term<'arena> {
@@ -112,8 +129,8 @@ pub enum Term<'arena> {
         &'arena Term<'arena>,
     ),
 
-    /// The type of types.
-    Universe,
+    /// The type of types, indexed by a [universe level][UniverseLevel].
+    Universe(UniverseLevel),
 
     /// Dependent function types.
     ///
@@ -146,17 +163,187 @@ pub enum Term<'arena> {
     Prim(Prim),
 
     /// Constant literals.
-    ConstLit(Const),
+    ConstLit(Const<'arena>),
     /// Match on a constant.
     ///
     /// (head_expr, pattern_branches, default_expr)
     ConstMatch(
         &'arena Term<'arena>,
-        &'arena [(Const, Term<'arena>)],
+        &'arena [(Const<'arena>, Term<'arena>)],
         Option<&'arena Term<'arena>>,
     ),
 }
 
+impl<'arena> Term<'arena> {
+    /// Returns `true` if the rigid variable `var` is referenced from within
+    /// this term, either directly or as an argument to a [flexible insertion].
+    ///
+    /// [flexible insertion]: Term::FlexibleInsertion
+    pub fn references_local(&self, var: LocalVar) -> bool {
+        match self {
+            Term::RigidVar(v) => *v == var,
+            Term::FlexibleInsertion(_, entry_infos) => {
+                Iterator::zip(env::local_vars(), entry_infos.iter().rev())
+                    .any(|(v, info)| v == var && matches!(info, EntryInfo::Parameter))
+            }
+            Term::FlexibleVar(_) | Term::Universe(_) | Term::Prim(_) | Term::ConstLit(_) => false,
+            Term::Ann(expr, r#type) => expr.references_local(var) || r#type.references_local(var),
+            Term::Let(_, def_type, def_expr, output_expr) => {
+                def_type.references_local(var)
+                    || def_expr.references_local(var)
+                    || output_expr.references_local(var.prev())
+            }
+            Term::FunType(_, input_type, output_type) => {
+                input_type.references_local(var) || output_type.references_local(var.prev())
+            }
+            Term::FunLit(_, output_expr) => output_expr.references_local(var.prev()),
+            Term::FunApp(head_expr, input_expr) => {
+                head_expr.references_local(var) || input_expr.references_local(var)
+            }
+            Term::RecordType(_, terms)
+            | Term::FormatRecord(_, terms)
+            | Term::FormatOverlap(_, terms) => {
+                // Each entry in a telescope is bound in the entries that follow it
+                let mut var = var;
+                terms.iter().any(|term| {
+                    let references_local = term.references_local(var);
+                    var = var.prev();
+                    references_local
+                })
+            }
+            Term::RecordLit(_, terms) | Term::ArrayLit(terms) => {
+                terms.iter().any(|term| term.references_local(var))
+            }
+            Term::RecordProj(head_expr, _) => head_expr.references_local(var),
+            Term::ConstMatch(head_expr, branches, default_expr) => {
+                head_expr.references_local(var)
+                    || branches.iter().any(|(_, expr)| expr.references_local(var))
+                    || default_expr.map_or(false, |expr| expr.references_local(var.prev()))
+            }
+        }
+    }
+
+    /// Collect the rigid variables, primitives, and labels referenced from
+    /// within this term in a single pass. `len` is the length of the rigid
+    /// environment the term is in scope of.
+    pub fn free_vars(&self, len: EnvLen) -> FreeVars {
+        let mut free_vars = FreeVars::default();
+        self.collect_free_vars(len, len, &mut Vec::new(), &mut free_vars);
+        free_vars
+    }
+
+    fn collect_free_vars(
+        &self,
+        outer_len: EnvLen,
+        len: EnvLen,
+        labels: &mut Vec<StringId>,
+        free_vars: &mut FreeVars,
+    ) {
+        match self {
+            Term::RigidVar(var) => {
+                let var = len.local_to_global(*var).unwrap();
+                free_vars.push_rigid_var(outer_len, labels, var);
+            }
+            Term::FlexibleInsertion(_, entry_infos) => {
+                for (var, info) in Iterator::zip(env::global_vars(), entry_infos.iter()) {
+                    if let EntryInfo::Parameter = info {
+                        free_vars.push_rigid_var(outer_len, labels, var);
+                    }
+                }
+            }
+            Term::Prim(prim) => {
+                if !free_vars.prims.contains(prim) {
+                    free_vars.prims.push(*prim);
+                }
+            }
+            Term::FlexibleVar(_) | Term::Universe(_) | Term::ConstLit(_) => {}
+            Term::Ann(expr, r#type) => {
+                expr.collect_free_vars(outer_len, len, labels, free_vars);
+                r#type.collect_free_vars(outer_len, len, labels, free_vars);
+            }
+            Term::Let(_, def_type, def_expr, output_expr) => {
+                def_type.collect_free_vars(outer_len, len, labels, free_vars);
+                def_expr.collect_free_vars(outer_len, len, labels, free_vars);
+                output_expr.collect_free_vars(outer_len, push_len(len), labels, free_vars);
+            }
+            Term::FunType(_, input_type, output_type) => {
+                input_type.collect_free_vars(outer_len, len, labels, free_vars);
+                output_type.collect_free_vars(outer_len, push_len(len), labels, free_vars);
+            }
+            Term::FunLit(_, output_expr) => {
+                output_expr.collect_free_vars(outer_len, push_len(len), labels, free_vars);
+            }
+            Term::FunApp(head_expr, input_expr) => {
+                head_expr.collect_free_vars(outer_len, len, labels, free_vars);
+                input_expr.collect_free_vars(outer_len, len, labels, free_vars);
+            }
+            Term::RecordType(entry_labels, terms)
+            | Term::FormatRecord(entry_labels, terms)
+            | Term::FormatOverlap(entry_labels, terms) => {
+                // Each entry in a telescope is bound in the entries that follow it
+                let initial_labels = labels.len();
+                let mut len = len;
+                for (label, term) in Iterator::zip(entry_labels.iter(), terms.iter()) {
+                    term.collect_free_vars(outer_len, len, labels, free_vars);
+                    labels.push(*label);
+                    len = push_len(len);
+                }
+                labels.truncate(initial_labels);
+            }
+            Term::RecordLit(_, terms) | Term::ArrayLit(terms) => {
+                for term in terms.iter() {
+                    term.collect_free_vars(outer_len, len, labels, free_vars);
+                }
+            }
+            Term::RecordProj(head_expr, _) => {
+                head_expr.collect_free_vars(outer_len, len, labels, free_vars);
+            }
+            Term::ConstMatch(head_expr, branches, default_expr) => {
+                head_expr.collect_free_vars(outer_len, len, labels, free_vars);
+                for (_, expr) in branches.iter() {
+                    expr.collect_free_vars(outer_len, len, labels, free_vars);
+                }
+                if let Some(default_expr) = default_expr {
+                    default_expr.collect_free_vars(outer_len, push_len(len), labels, free_vars);
+                }
+            }
+        }
+    }
+}
+
+fn push_len(mut len: EnvLen) -> EnvLen {
+    len.push();
+    len
+}
+
+/// The rigid variables, primitives, and labels referenced from within a term,
+/// as collected by [`Term::free_vars`].
+#[derive(Debug, Default)]
+pub struct FreeVars {
+    /// Rigid variables bound outside of the term that are referenced from
+    /// within it, either directly or as arguments to [flexible insertions].
+    ///
+    /// [flexible insertions]: Term::FlexibleInsertion
+    pub rigid_vars: BTreeSet<GlobalVar>,
+    /// Rigid variables that are referenced from within the scope of a field,
+    /// paired with the label of that field. This is used to avoid binding
+    /// names that would be captured by the labels of record types, record
+    /// formats, or overlap formats.
+    pub labelled_vars: BTreeSet<(StringId, GlobalVar)>,
+    /// Primitives that are referenced from within the term.
+    pub prims: Vec<Prim>,
+}
+
+impl FreeVars {
+    fn push_rigid_var(&mut self, outer_len: EnvLen, labels: &[StringId], var: GlobalVar) {
+        // Ignore variables that were bound inside of the term
+        if outer_len.global_to_local(var).is_some() {
+            self.rigid_vars.insert(var);
+            (self.labelled_vars).extend(labels.iter().map(|label| (*label, var)));
+        }
+    }
+}
+
 macro_rules! def_prims {
     ($($(#[$prim_attr:meta])* $PrimName:ident => $prim_name:literal),* $(,)?) => {
         /// Primitives.
@@ -166,6 +353,9 @@ macro_rules! def_prims {
         }
 
         impl Prim {
+            /// Every primitive, in the order they were defined.
+            pub const ALL: &'static [Prim] = &[$(Prim::$PrimName),*];
+
             pub const fn name(&self) -> &'static str {
                 match self {
                     $(Prim::$PrimName => $prim_name),*
@@ -201,6 +391,8 @@ def_prims! {
     F32Type => "F32",
     /// Type of 64-bit, IEEE-754 floating point numbers.
     F64Type => "F64",
+    /// Type of unicode strings.
+    StringType => "String",
     /// Type of optional data.
     OptionType => "Option",
     /// Type of dynamically sized arrays.
@@ -256,6 +448,39 @@ def_prims! {
     FormatF64Be => "f64be",
     /// 64-bit, IEEE-754 floating point formats (little-endian).
     FormatF64Le => "f64le",
+    /// NUL-terminated, UTF-8 encoded string formats.
+    FormatCString => "cstring",
+    /// UTF-8 encoded string formats, with an unsigned 8-bit length in bytes.
+    FormatUtf8Len8 => "utf8_8",
+    /// UTF-8 encoded string formats, with an unsigned 16-bit length in bytes.
+    FormatUtf8Len16 => "utf8_16",
+    /// UTF-8 encoded string formats, with an unsigned 32-bit length in bytes.
+    FormatUtf8Len32 => "utf8_32",
+    /// UTF-8 encoded string formats, with an unsigned 64-bit length in bytes.
+    FormatUtf8Len64 => "utf8_64",
+    /// UTF-16 encoded string formats (big-endian), with an unsigned 8-bit length in bytes.
+    FormatUtf16BeLen8 => "utf16be_8",
+    /// UTF-16 encoded string formats (big-endian), with an unsigned 16-bit length in bytes.
+    FormatUtf16BeLen16 => "utf16be_16",
+    /// UTF-16 encoded string formats (big-endian), with an unsigned 32-bit length in bytes.
+    FormatUtf16BeLen32 => "utf16be_32",
+    /// UTF-16 encoded string formats (big-endian), with an unsigned 64-bit length in bytes.
+    FormatUtf16BeLen64 => "utf16be_64",
+    /// ASCII encoded string formats, with an unsigned 8-bit length in bytes.
+    FormatAsciiLen8 => "ascii_8",
+    /// ASCII encoded string formats, with an unsigned 16-bit length in bytes.
+    FormatAsciiLen16 => "ascii_16",
+    /// ASCII encoded string formats, with an unsigned 32-bit length in bytes.
+    FormatAsciiLen32 => "ascii_32",
+    /// ASCII encoded string formats, with an unsigned 64-bit length in bytes.
+    FormatAsciiLen64 => "ascii_64",
+    /// Unsigned, variable-length LEB128 integer formats.
+    FormatULeb128 => "uleb128",
+    /// Signed, variable-length LEB128 integer formats.
+    FormatSLeb128 => "sleb128",
+    /// Unsigned, variable-length base-128 integer formats (big-endian), as
+    /// used in ASN.1 object identifiers.
+    FormatBase128Be => "base128be",
     /// Array formats, with unsigned 8-bit indices.
     FormatArray8 => "array8",
     /// Array formats, with unsigned 16-bit indices.
@@ -268,9 +493,16 @@ def_prims! {
     FormatRepeatUntilEnd => "repeat_until_end",
     /// A format which returns the current position in the input stream.
     FormatStreamPos => "stream_pos",
+    /// A format that succeeds only at the end of the input stream.
+    FormatEndOfInput => "end_of_input",
+    /// A format which returns the number of bytes remaining in the input stream.
+    FormatRemainingBytes => "remaining_bytes",
     /// A format that links to another location in the binary data stream,
     /// relative to a base position.
     FormatLink => "link",
+    /// A format that applies a function to the data that was parsed with
+    /// another format.
+    FormatMap => "map",
     /// A format that forces a reference to be read eagerly.
     FormatDeref => "deref",
     /// A format that always succeeds with some data.
@@ -416,14 +648,138 @@ def_prims! {
     S64Abs => "s64_abs",
     S64UAbs => "s64_unsigned_abs",
 
+    F32Eq  => "f32_eq",
+    F32Neq => "f32_neq",
+    F32Gt  => "f32_gt",
+    F32Lt  => "f32_lt",
+    F32Gte => "f32_gte",
+    F32Lte => "f32_lte",
+    F32Neg => "f32_neg",
+    F32Add => "f32_add",
+    F32Sub => "f32_sub",
+    F32Mul => "f32_mul",
+    F32Div => "f32_div",
+    F32Abs => "f32_abs",
+    F32IsNan => "f32_is_nan",
+
+    F64Eq  => "f64_eq",
+    F64Neq => "f64_neq",
+    F64Gt  => "f64_gt",
+    F64Lt  => "f64_lt",
+    F64Gte => "f64_gte",
+    F64Lte => "f64_lte",
+    F64Neg => "f64_neg",
+    F64Add => "f64_add",
+    F64Sub => "f64_sub",
+    F64Mul => "f64_mul",
+    F64Div => "f64_div",
+    F64Abs => "f64_abs",
+    F64IsNan => "f64_is_nan",
+
+    U8ToU16 => "u8_to_u16",
+    U8ToU32 => "u8_to_u32",
+    U8ToU64 => "u8_to_u64",
+    U16ToU32 => "u16_to_u32",
+    U16ToU64 => "u16_to_u64",
+    U32ToU64 => "u32_to_u64",
+
+    S8ToS16 => "s8_to_s16",
+    S8ToS32 => "s8_to_s32",
+    S8ToS64 => "s8_to_s64",
+    S16ToS32 => "s16_to_s32",
+    S16ToS64 => "s16_to_s64",
+    S32ToS64 => "s32_to_s64",
+
+    U8ToS16 => "u8_to_s16",
+    U8ToS32 => "u8_to_s32",
+    U8ToS64 => "u8_to_s64",
+    U16ToS32 => "u16_to_s32",
+    U16ToS64 => "u16_to_s64",
+    U32ToS64 => "u32_to_s64",
+
+    U16ToU8 => "u16_to_u8",
+    U32ToU8 => "u32_to_u8",
+    U32ToU16 => "u32_to_u16",
+    U64ToU8 => "u64_to_u8",
+    U64ToU16 => "u64_to_u16",
+    U64ToU32 => "u64_to_u32",
+
+    S16ToS8 => "s16_to_s8",
+    S32ToS8 => "s32_to_s8",
+    S32ToS16 => "s32_to_s16",
+    S64ToS8 => "s64_to_s8",
+    S64ToS16 => "s64_to_s16",
+    S64ToS32 => "s64_to_s32",
+
+    U8ToS8 => "u8_to_s8",
+    U16ToS16 => "u16_to_s16",
+    U32ToS32 => "u32_to_s32",
+    U64ToS64 => "u64_to_s64",
+    S8ToU8 => "s8_to_u8",
+    S16ToU16 => "s16_to_u16",
+    S32ToU32 => "s32_to_u32",
+    S64ToU64 => "s64_to_u64",
+
+    U8ToF32 => "u8_to_f32",
+    U16ToF32 => "u16_to_f32",
+    S8ToF32 => "s8_to_f32",
+    S16ToF32 => "s16_to_f32",
+    U8ToF64 => "u8_to_f64",
+    U16ToF64 => "u16_to_f64",
+    U32ToF64 => "u32_to_f64",
+    S8ToF64 => "s8_to_f64",
+    S16ToF64 => "s16_to_f64",
+    S32ToF64 => "s32_to_f64",
+    F32ToF64 => "f32_to_f64",
+
     OptionSome => "some",
     OptionNone => "none",
     OptionFold => "option_fold",
 
+    Array8Len => "array8_len",
+    Array8Index => "array8_index",
+    Array8Fold => "array8_fold",
+    Array8Map => "array8_map",
+    Array8All => "array8_all",
+    Array8Any => "array8_any",
     Array8Find => "array8_find",
+    Array8BinarySearch => "array8_binary_search",
+
+    Array16Len => "array16_len",
+    Array16Index => "array16_index",
+    Array16Fold => "array16_fold",
+    Array16Map => "array16_map",
+    Array16All => "array16_all",
+    Array16Any => "array16_any",
     Array16Find => "array16_find",
+    Array16BinarySearch => "array16_binary_search",
+
+    Array32Len => "array32_len",
+    Array32Index => "array32_index",
+    Array32Fold => "array32_fold",
+    Array32Map => "array32_map",
+    Array32All => "array32_all",
+    Array32Any => "array32_any",
     Array32Find => "array32_find",
+    Array32BinarySearch => "array32_binary_search",
+
+    Array64Len => "array64_len",
+    Array64Index => "array64_index",
+    Array64Fold => "array64_fold",
+    Array64Map => "array64_map",
+    Array64All => "array64_all",
+    Array64Any => "array64_any",
     Array64Find => "array64_find",
+    Array64BinarySearch => "array64_binary_search",
+
+    ArrayLen => "array_len",
+    ArrayIndex => "array_index",
+    ArrayFold => "array_fold",
+    ArrayMap => "array_map",
+    ArrayAll => "array_all",
+    ArrayAny => "array_any",
+    ArrayFind => "array_find",
+    ArrayBinarySearch => "array_binary_search",
 
     PosAddU8  => "pos_add_u8",
     PosAddU16 => "pos_add_u16",
@@ -439,11 +795,19 @@ pub enum UIntStyle {
     Hexadecimal,
     /// A [four-character code](https://en.wikipedia.org/wiki/FourCC) (big-endian)
     Ascii,
+    /// A [four-character code](https://en.wikipedia.org/wiki/FourCC) (little-endian)
+    AsciiLe,
 }
 
 /// Constants
-#[derive(Debug, Copy, Clone, PartialOrd)]
-pub enum Const {
+///
+/// Constants are compared by value, ignoring the [style][UIntStyle] of
+/// unsigned integers. Floating point numbers are compared using the IEEE 754
+/// `totalOrder` predicate, so unlike the IEEE 754 comparisons
+/// used by primitives like `f64_eq`, `NaN` is equal to itself and `-0.0` is
+/// distinct from `0.0`. This ensures that conversion checking is reflexive.
+#[derive(Debug, Copy, Clone)]
+pub enum Const<'arena> {
     Bool(bool),
     U8(u8, UIntStyle),
     U16(u16, UIntStyle),
@@ -453,34 +817,96 @@ pub enum Const {
     S16(i16),
     S32(i32),
     S64(i64),
-    // TODO: use logical equality for floating point numbers
     F32(f32),
     F64(f64),
     Pos(u64),
     Ref(u64),
+    /// Strings, allocated in the same arena as the terms or values that refer
+    /// to them. These are not interned, as they may be read from binary data.
+    String(&'arena str),
+}
+
+impl<'arena> Const<'arena> {
+    /// An index used for ordering constants of different types.
+    fn tag(&self) -> u8 {
+        match self {
+            Const::Bool(_) => 0,
+            Const::U8(_, _) => 1,
+            Const::U16(_, _) => 2,
+            Const::U32(_, _) => 3,
+            Const::U64(_, _) => 4,
+            Const::S8(_) => 5,
+            Const::S16(_) => 6,
+            Const::S32(_) => 7,
+            Const::S64(_) => 8,
+            Const::F32(_) => 9,
+            Const::F64(_) => 10,
+            Const::Pos(_) => 11,
+            Const::Ref(_) => 12,
+            Const::String(_) => 13,
+        }
+    }
 }
 
-impl PartialEq for Const {
+impl<'arena> PartialEq for Const<'arena> {
     fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'arena> Eq for Const<'arena> {}
+
+impl<'arena> PartialOrd for Const<'arena> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'arena> Ord for Const<'arena> {
+    fn cmp(&self, other: &Self) -> Ordering {
         match (*self, *other) {
-            (Const::Bool(a), Const::Bool(b)) => a == b,
-            (Const::U8(a, _), Const::U8(b, _)) => a == b,
-            (Const::U16(a, _), Const::U16(b, _)) => a == b,
-            (Const::U32(a, _), Const::U32(b, _)) => a == b,
-            (Const::U64(a, _), Const::U64(b, _)) => a == b,
-            (Const::S8(a), Const::S8(b)) => a == b,
-            (Const::S16(a), Const::S16(b)) => a == b,
-            (Const::S32(a), Const::S32(b)) => a == b,
-            (Const::S64(a), Const::S64(b)) => a == b,
-            (Const::F32(a), Const::F32(b)) => a == b,
-            (Const::F64(a), Const::F64(b)) => a == b,
-            (Const::Pos(a), Const::Pos(b)) => a == b,
-            (Const::Ref(a), Const::Ref(b)) => a == b,
-            _ => false,
+            (Const::Bool(a), Const::Bool(b)) => a.cmp(&b),
+            (Const::U8(a, _), Const::U8(b, _)) => a.cmp(&b),
+            (Const::U16(a, _), Const::U16(b, _)) => a.cmp(&b),
+            (Const::U32(a, _), Const::U32(b, _)) => a.cmp(&b),
+            (Const::U64(a, _), Const::U64(b, _)) => a.cmp(&b),
+            (Const::S8(a), Const::S8(b)) => a.cmp(&b),
+            (Const::S16(a), Const::S16(b)) => a.cmp(&b),
+            (Const::S32(a), Const::S32(b)) => a.cmp(&b),
+            (Const::S64(a), Const::S64(b)) => a.cmp(&b),
+            (Const::F32(a), Const::F32(b)) => total_cmp_f32(a, b),
+            (Const::F64(a), Const::F64(b)) => total_cmp_f64(a, b),
+            (Const::Pos(a), Const::Pos(b)) => a.cmp(&b),
+            (Const::Ref(a), Const::Ref(b)) => a.cmp(&b),
+            (Const::String(a), Const::String(b)) => a.cmp(b),
+            (a, b) => a.tag().cmp(&b.tag()),
         }
     }
 }
 
+// TODO: Use `f32::total_cmp` and `f64::total_cmp` once the minimum supported
+//       Rust version is at least 1.62
+
+/// Compare two floats using the IEEE 754 `totalOrder` predicate. The bits of
+/// negative numbers are flipped so that they are ordered by their magnitude.
+fn total_cmp_f32(a: f32, b: f32) -> Ordering {
+    let key = |x: f32| {
+        let bits = x.to_bits() as i32;
+        bits ^ (((bits >> 31) as u32) >> 1) as i32
+    };
+    key(a).cmp(&key(b))
+}
+
+/// Compare two floats using the IEEE 754 `totalOrder` predicate. The bits of
+/// negative numbers are flipped so that they are ordered by their magnitude.
+fn total_cmp_f64(a: f64, b: f64) -> Ordering {
+    let key = |x: f64| {
+        let bits = x.to_bits() as i64;
+        bits ^ (((bits >> 63) as u64) >> 1) as i64
+    };
+    key(a).cmp(&key(b))
+}
+
 pub trait ToBeBytes<const N: usize> {
     fn to_be_bytes(self) -> [u8; N];
 }
@@ -512,14 +938,11 @@ impl UIntStyle {
             UIntStyle::Binary => format!("0b{:b}", number),
             UIntStyle::Decimal => number.to_string(),
             UIntStyle::Hexadecimal => format!("0x{:x}", number),
-            UIntStyle::Ascii => {
-                let bytes = number.to_be_bytes();
-                if bytes.iter().all(|c| c.is_ascii() && !c.is_ascii_control()) {
-                    let s = std::str::from_utf8(&bytes).unwrap(); // unwrap safe due to above check
-                    format!("\"{}\"", s)
-                } else {
-                    format!("0x{:x}", number)
-                }
+            UIntStyle::Ascii => format!("\"{}\"", escape_ascii(number.to_be_bytes())),
+            UIntStyle::AsciiLe => {
+                let mut bytes = number.to_be_bytes();
+                bytes.reverse();
+                format!("\"{}\"le", escape_ascii(bytes))
             }
         }
     }
@@ -534,12 +957,27 @@ impl UIntStyle {
             (Binary, Binary) => Binary,
             (Hexadecimal, Hexadecimal) => Hexadecimal,
             (Ascii, Ascii) => Ascii,
+            (AsciiLe, AsciiLe) => AsciiLe,
             // Otherwise use the default style
             (_, _) => Decimal,
         }
     }
 }
 
+/// Escape bytes so that they can be printed in a string literal.
+fn escape_ascii<const N: usize>(bytes: [u8; N]) -> String {
+    let mut string = String::with_capacity(N);
+    for byte in bytes {
+        match byte {
+            b'"' => string.push_str("\\\""),
+            b'\\' => string.push_str("\\\\"),
+            _ if byte.is_ascii() && !byte.is_ascii_control() => string.push(char::from(byte)),
+            _ => string.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    string
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -549,4 +987,37 @@ mod tests {
         assert!(!std::mem::needs_drop::<Term<'_>>());
         assert!(!std::mem::needs_drop::<Term<'_>>());
     }
+
+    #[test]
+    fn total_cmp_floats() {
+        let f64s = [
+            f64::NEG_INFINITY,
+            -1.5,
+            -0.0,
+            0.0,
+            1.5,
+            f64::INFINITY,
+            f64::NAN,
+        ];
+        for (a, b) in Iterator::zip(f64s.iter(), f64s.iter().skip(1)) {
+            assert_eq!(total_cmp_f64(*a, *b), Ordering::Less, "{} < {}", a, b);
+            assert_eq!(total_cmp_f64(*b, *a), Ordering::Greater, "{} > {}", b, a);
+        }
+        assert_eq!(total_cmp_f64(f64::NAN, f64::NAN), Ordering::Equal);
+
+        let f32s = [
+            f32::NEG_INFINITY,
+            -1.5,
+            -0.0,
+            0.0,
+            1.5,
+            f32::INFINITY,
+            f32::NAN,
+        ];
+        for (a, b) in Iterator::zip(f32s.iter(), f32s.iter().skip(1)) {
+            assert_eq!(total_cmp_f32(*a, *b), Ordering::Less, "{} < {}", a, b);
+            assert_eq!(total_cmp_f32(*b, *a), Ordering::Greater, "{} > {}", b, a);
+        }
+        assert_eq!(total_cmp_f32(f32::NAN, f32::NAN), Ordering::Equal);
+    }
 }
@@ -1,17 +1,122 @@
 //! Binary semantics of the data description language
 
+use itertools::Itertools;
+use scoped_arena::Scope;
 use std::collections::HashMap;
+use std::fmt;
 use std::io::{self, Read, Seek, SeekFrom};
 use std::sync::Arc;
 
-use crate::core::semantics::{self, ArcValue, Elim, Head, Value};
+use crate::alloc;
+use crate::core::semantics::{self, ArcValue, Elim, Frame, Head, Telescope, Value};
 use crate::core::{Const, Prim, UIntStyle};
 use crate::env::{EnvLen, SliceEnv};
+use crate::StringId;
 
 pub struct Context<'arena, 'env> {
+    /// Scope used for allocating strings read from the binary data
+    scope: &'arena Scope<'arena>,
     flexible_exprs: &'env SliceEnv<Option<ArcValue<'arena>>>,
-    pending_formats: Vec<(u64, ArcValue<'arena>)>,
+    /// Links that are yet to be read, along with the index of the reference
+    /// in `ref_chains` that they were found in
+    pending_formats: Vec<(u64, ArcValue<'arena>, Option<usize>)>,
+    /// References that have been read, along with the index of the reference
+    /// that linked to them, used to detect cycles
+    ref_chains: Vec<(u64, ArcValue<'arena>, Option<usize>)>,
+    /// The index of the reference in `ref_chains` that is currently being read
+    current_ref: Option<usize>,
     cached_refs: HashMap<u64, Vec<ParsedRef<'arena>>>,
+    limits: Limits,
+    /// The number of values that have been read so far
+    values_read: u64,
+    /// The number of links that have been encountered so far
+    links_read: u64,
+    /// The number of bytes that have been read so far
+    bytes_read: u64,
+    /// The number of formats that are currently being read
+    depth: u64,
+    /// The ranges of bytes that have been read so far, in the order that they
+    /// were read. Adjacent ranges are merged as they are recorded.
+    read_ranges: Vec<(u64, u64)>,
+    /// The fields and references that are currently being read, included in
+    /// the traces of evaluation errors
+    frames: Vec<Frame>,
+}
+
+/// Bounds on the resources used when reading binary data.
+///
+/// These allow formats to be run over untrusted data without exhausting memory
+/// or time. Limits that are set to `None` are not enforced. The
+/// [default limits][Limits::default] are finite, and must be explicitly
+/// removed, for example by using [`Limits::UNLIMITED`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum number of elements in a single array
+    pub max_array_len: Option<u64>,
+    /// The maximum number of values read in total
+    pub max_values: Option<u64>,
+    /// The maximum number of links read in total
+    pub max_links: Option<u64>,
+    /// The maximum depth that formats can be nested while reading
+    pub max_depth: Option<u64>,
+    /// The maximum number of bytes read in total, including those that are
+    /// read more than once
+    pub max_bytes_read: Option<u64>,
+}
+
+impl Limits {
+    /// Limits that are never exceeded. These should only be used when reading
+    /// trusted data.
+    pub const UNLIMITED: Limits = Limits {
+        max_array_len: None,
+        max_values: None,
+        max_links: None,
+        max_depth: None,
+        max_bytes_read: None,
+    };
+}
+
+impl Default for Limits {
+    /// Conservative limits that are suitable for reading untrusted data.
+    fn default() -> Limits {
+        Limits {
+            max_array_len: Some(1 << 22),
+            max_values: Some(1 << 22),
+            max_links: Some(1 << 16),
+            max_depth: Some(256),
+            max_bytes_read: Some(1 << 30),
+        }
+    }
+}
+
+/// A resource that is bounded by [`Limits`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Limit {
+    ArrayLen,
+    Values,
+    Links,
+    Depth,
+    BytesRead,
+}
+
+impl Limit {
+    pub fn description(&self) -> &str {
+        match self {
+            Limit::ArrayLen => "maximum array length",
+            Limit::Values => "maximum number of values",
+            Limit::Links => "maximum number of links",
+            Limit::Depth => "maximum nesting depth",
+            Limit::BytesRead => "maximum number of bytes read",
+        }
+    }
+}
+
+/// Returns an error if `value` exceeds the `max` allowed for a limit.
+fn check_limit(limit: Limit, max: Option<u64>, value: u64) -> Result<(), ReadError> {
+    match max {
+        Some(max) if value > max => Err(ReadError::LimitExceeded(limit, max)),
+        _ => Ok(()),
+    }
 }
 
 pub struct ParsedRef<'arena> {
@@ -21,58 +126,236 @@ pub struct ParsedRef<'arena> {
     /// The expression that was parsed for this reference
     // Invariant: `expr : Repr format`
     pub expr: ArcValue<'arena>,
+    /// Where each part of the expression was parsed from
+    pub provenance: Provenance<'arena>,
+}
+
+/// The region of the binary data that a parsed value was read from.
+///
+/// This mirrors the structure of the parsed value, allowing it to be mapped
+/// back to the bytes that it was parsed from.
+#[derive(Debug, Clone)]
+pub struct Provenance<'arena> {
+    /// The format that the value was parsed with
+    pub format: ArcValue<'arena>,
+    /// The offset of the first byte that was read
+    pub start: u64,
+    /// The offset after the last byte that was read
+    pub end: u64,
+    /// The provenance of each field (for records and overlaps) or element
+    /// (for arrays) of the parsed value.
+    ///
+    /// Values parsed with `deref` are not included here, and should instead
+    /// be looked up in the reference cache.
+    pub children: Vec<Provenance<'arena>>,
+}
+
+/// Errors encountered while reading binary data.
+#[derive(Debug)]
+pub enum ReadError {
+    InvalidFormat,
+    InvalidArrayLength,
+    InvalidLinkPos,
+    InvalidRef,
+    InvalidOption,
+    ReadFailFormat,
+    UnwrappedNone,
+    ExpectedEndOfInput,
+    /// A variable-length integer was too large for its representation.
+    VarIntOverflow,
+    InvalidStringLength,
+    InvalidUtf8,
+    InvalidUtf16,
+    InvalidAscii,
+    /// A reference was encountered while it was already being read. The
+    /// positions of the references that were being read at the time are
+    /// recorded, starting from the first occurrence of the cyclic reference.
+    CyclicRef(Vec<u64>),
+    /// One of the [`Limits`] was exceeded, with the maximum that was allowed.
+    LimitExceeded(Limit, u64),
+    Io(io::Error),
+}
+
+impl ReadError {
+    pub fn description(&self) -> &str {
+        match self {
+            ReadError::InvalidFormat => "invalid format",
+            ReadError::InvalidArrayLength => "invalid array length",
+            ReadError::InvalidLinkPos => "invalid link pos",
+            ReadError::InvalidRef => "invalid format reference",
+            ReadError::InvalidOption => "invalid option",
+            ReadError::ReadFailFormat => "parse failure",
+            ReadError::UnwrappedNone => "unwrapped none",
+            ReadError::ExpectedEndOfInput => "expected end of input",
+            ReadError::VarIntOverflow => "variable-length integer overflow",
+            ReadError::InvalidStringLength => "invalid string length",
+            ReadError::InvalidUtf8 => "invalid UTF-8 string",
+            ReadError::InvalidUtf16 => "invalid UTF-16 string",
+            ReadError::InvalidAscii => "invalid ASCII string",
+            ReadError::CyclicRef(_) => "cyclic reference",
+            ReadError::LimitExceeded(limit, _) => limit.description(),
+            ReadError::Io(_) => "io error",
+        }
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::CyclicRef(positions) => {
+                write!(
+                    f,
+                    "{}: {}",
+                    self.description(),
+                    positions.iter().format(" -> ")
+                )
+            }
+            ReadError::LimitExceeded(limit, max) => {
+                write!(f, "exceeded the {} of {}", limit.description(), max)
+            }
+            ReadError::Io(error) => error.fmt(f),
+            _ => f.write_str(self.description()),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> ReadError {
+        ReadError::Io(error)
+    }
 }
 
 impl<'arena, 'env> Context<'arena, 'env> {
-    pub fn new(flexible_exprs: &'env SliceEnv<Option<ArcValue<'arena>>>) -> Context<'arena, 'env> {
+    pub fn new(
+        scope: &'arena Scope<'arena>,
+        flexible_exprs: &'env SliceEnv<Option<ArcValue<'arena>>>,
+    ) -> Context<'arena, 'env> {
         Context {
+            scope,
             flexible_exprs,
             pending_formats: Vec::new(),
+            ref_chains: Vec::new(),
+            current_ref: None,
             cached_refs: HashMap::new(),
+            limits: Limits::default(),
+            values_read: 0,
+            links_read: 0,
+            bytes_read: 0,
+            depth: 0,
+            read_ranges: Vec::new(),
+            frames: Vec::new(),
         }
     }
 
-    fn elim_context(&self) -> semantics::ElimContext<'arena, 'env> {
-        semantics::ElimContext::new(self.flexible_exprs)
+    /// Set the limits to enforce when reading binary data
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    fn elim_context(&self) -> semantics::ElimContext<'arena, '_> {
+        semantics::ElimContext::new(self.flexible_exprs).with_frames(&self.frames)
     }
 
     fn conversion_context(&self) -> semantics::ConversionContext<'arena, 'env> {
         semantics::ConversionContext::new(EnvLen::new(), self.flexible_exprs)
     }
 
+    /// Parse the binary data using the given entrypoint format, returning the
+    /// references that were parsed, keyed by their position in the data.
+    ///
+    /// Each [parsed reference][ParsedRef] includes its [provenance], which
+    /// records the range of the data that each part of it was parsed from.
     // TODO: allow refs to be streamed
     pub fn read_entrypoint(
-        mut self,
+        &mut self,
         reader: &mut dyn SeekRead,
         format: ArcValue<'arena>,
-    ) -> io::Result<HashMap<u64, Vec<ParsedRef<'arena>>>> {
+    ) -> Result<HashMap<u64, Vec<ParsedRef<'arena>>>, ReadError> {
         // Parse the entrypoint from the beginning start of the binary data
-        self.pending_formats.push((0, format));
+        self.pending_formats.push((0, format, None));
 
-        while let Some((pos, format)) = self.pending_formats.pop() {
+        while let Some((pos, format, linked_from)) = self.pending_formats.pop() {
+            self.current_ref = linked_from;
             self.read_cached_ref(reader, pos, &format)?;
         }
 
-        Ok(self.cached_refs)
+        Ok(std::mem::take(&mut self.cached_refs))
+    }
+
+    /// The number of times each byte of binary data of length `len` was read
+    /// by this context.
+    pub fn coverage(&self, len: u64) -> Coverage {
+        Coverage::new(len, &self.read_ranges)
+    }
+
+    /// Count bytes towards the limit on the number of bytes read, returning an
+    /// error if the limit is exceeded.
+    fn count_bytes_read(&mut self, len: u64) -> Result<(), ReadError> {
+        self.bytes_read += len;
+        check_limit(
+            Limit::BytesRead,
+            self.limits.max_bytes_read,
+            self.bytes_read,
+        )
+    }
+
+    /// Record that the bytes from `start` up to `end` were read.
+    fn record_read(&mut self, start: u64, end: u64) {
+        match self.read_ranges.last_mut() {
+            Some((_, last_end)) if *last_end == start => *last_end = end,
+            _ if start < end => self.read_ranges.push((start, end)),
+            _ => {}
+        }
     }
 
     fn read_format(
         &mut self,
         reader: &mut dyn SeekRead,
         format: &ArcValue<'arena>,
-    ) -> io::Result<ArcValue<'arena>> {
+    ) -> Result<(ArcValue<'arena>, Provenance<'arena>), ReadError> {
+        self.values_read += 1;
+        check_limit(Limit::Values, self.limits.max_values, self.values_read)?;
+        check_limit(Limit::Depth, self.limits.max_depth, self.depth + 1)?;
+
+        let start = reader.stream_position()?;
+        let mut children = Vec::new();
+        self.depth += 1;
+        let result = self.read_format_expr(reader, format, &mut children);
+        self.depth -= 1;
+        let expr = result?;
+        let end = reader.stream_position()?;
+
+        let provenance = Provenance {
+            format: format.clone(),
+            start,
+            end,
+            children,
+        };
+
+        Ok((expr, provenance))
+    }
+
+    fn read_format_expr(
+        &mut self,
+        reader: &mut dyn SeekRead,
+        format: &ArcValue<'arena>,
+        children: &mut Vec<Provenance<'arena>>,
+    ) -> Result<ArcValue<'arena>, ReadError> {
         match self.elim_context().force(format).as_ref() {
-            Value::Stuck(Head::Prim(prim), slice) => self.read_prim(reader, *prim, slice),
+            Value::Stuck(Head::Prim(prim), slice) => self.read_prim(reader, *prim, slice, children),
             Value::FormatRecord(labels, formats) => {
                 let mut formats = formats.clone();
                 let mut exprs = Vec::with_capacity(formats.len());
+                children.reserve(formats.len());
 
-                while let Some((format, next_formats)) =
-                    self.elim_context().split_telescope(formats)
-                {
-                    let expr = self.read_format(reader, &format)?;
-                    exprs.push(expr.clone());
-                    formats = next_formats(expr);
+                for label in labels.iter() {
+                    let (expr, provenance, next_formats) =
+                        self.read_field(reader, *label, formats)?;
+                    exprs.push(expr);
+                    children.push(provenance);
+                    formats = next_formats;
                 }
 
                 Ok(Arc::new(Value::RecordLit(labels, exprs)))
@@ -83,16 +366,17 @@ impl<'arena, 'env> Context<'arena, 'env> {
 
                 let mut formats = formats.clone();
                 let mut exprs = Vec::with_capacity(formats.len());
+                children.reserve(formats.len());
 
-                while let Some((format, next_formats)) =
-                    self.elim_context().split_telescope(formats)
-                {
+                for label in labels.iter() {
                     // Reset the stream to the start
                     reader.seek(SeekFrom::Start(initial_pos))?;
 
-                    let expr = self.read_format(reader, &format)?;
-                    exprs.push(expr.clone());
-                    formats = next_formats(expr);
+                    let (expr, provenance, next_formats) =
+                        self.read_field(reader, *label, formats)?;
+                    exprs.push(expr);
+                    children.push(provenance);
+                    formats = next_formats;
 
                     // Update the max position
                     max_pos = std::cmp::max(max_pos, reader.stream_position()?);
@@ -106,60 +390,99 @@ impl<'arena, 'env> Context<'arena, 'env> {
 
             Value::Stuck(Head::RigidVar(_), _)
             | Value::Stuck(Head::FlexibleVar(_), _)
-            | Value::Universe
+            | Value::Universe(_)
             | Value::FunType(_, _, _)
             | Value::FunLit(_, _)
             | Value::RecordType(_, _)
             | Value::RecordLit(_, _)
             | Value::ArrayLit(_)
-            | Value::ConstLit(_) => Err(io::Error::new(io::ErrorKind::Other, "invalid format")),
+            | Value::ConstLit(_) => Err(ReadError::InvalidFormat),
         }
     }
 
+    /// Read the next field of a record format, returning the remaining field
+    /// formats. The field is recorded in the traces of any evaluation errors.
+    fn read_field(
+        &mut self,
+        reader: &mut dyn SeekRead,
+        label: StringId,
+        formats: Telescope<'arena>,
+    ) -> Result<(ArcValue<'arena>, Provenance<'arena>, Telescope<'arena>), ReadError> {
+        self.frames.push(Frame::ReadField(label));
+        let result = (|| {
+            let (format, next_formats) =
+                (self.elim_context().split_telescope(formats)).ok_or(ReadError::InvalidFormat)?;
+            let (expr, provenance) = self.read_format(reader, &format)?;
+            Ok((expr.clone(), provenance, next_formats(expr)))
+        })();
+        self.frames.pop();
+        result
+    }
+
     #[rustfmt::skip]
     fn read_prim(
         &mut self,
         reader: &mut dyn SeekRead,
         prim: Prim,
         slice: &[Elim<'arena>],
-    ) -> io::Result<ArcValue<'arena>> {
+        children: &mut Vec<Provenance<'arena>>,
+    ) -> Result<ArcValue<'arena>, ReadError> {
         use crate::core::semantics::Elim::FunApp;
 
         match (prim, &slice[..]) {
-            (Prim::FormatU8, []) => read_const(reader, |num| Const::U8(num, UIntStyle::Decimal), read_u8),
-            (Prim::FormatU16Be, []) => read_const(reader, |num| Const::U16(num, UIntStyle::Decimal), read_u16be),
-            (Prim::FormatU16Le, []) => read_const(reader, |num| Const::U16(num, UIntStyle::Decimal), read_u16le),
-            (Prim::FormatU32Be, []) => read_const(reader, |num| Const::U32(num, UIntStyle::Decimal), read_u32be),
-            (Prim::FormatU32Le, []) => read_const(reader, |num| Const::U32(num, UIntStyle::Decimal), read_u32le),
-            (Prim::FormatU64Be, []) => read_const(reader, |num| Const::U64(num, UIntStyle::Decimal), read_u64be),
-            (Prim::FormatU64Le, []) => read_const(reader, |num| Const::U64(num, UIntStyle::Decimal), read_u64le),
-            (Prim::FormatS8, []) => read_const(reader, Const::S8, read_s8),
-            (Prim::FormatS16Be, []) => read_const(reader, Const::S16, read_s16be),
-            (Prim::FormatS16Le, []) => read_const(reader, Const::S16, read_s16le),
-            (Prim::FormatS32Be, []) => read_const(reader, Const::S32, read_s32be),
-            (Prim::FormatS32Le, []) => read_const(reader, Const::S32, read_s32le),
-            (Prim::FormatS64Be, []) => read_const(reader, Const::S64, read_s64be),
-            (Prim::FormatS64Le, []) => read_const(reader, Const::S64, read_s64le),
-            (Prim::FormatF32Be, []) => read_const(reader, Const::F32, read_f32be),
-            (Prim::FormatF32Le, []) => read_const(reader, Const::F32, read_f32le),
-            (Prim::FormatF64Be, []) => read_const(reader, Const::F64, read_f64be),
-            (Prim::FormatF64Le, []) => read_const(reader, Const::F64, read_f64le),
-            (Prim::FormatArray8, [FunApp(len), FunApp(elem_format)]) => self.read_array(reader, len, elem_format),
-            (Prim::FormatArray16, [FunApp(len), FunApp(elem_format)]) => self.read_array(reader, len, elem_format),
-            (Prim::FormatArray32, [FunApp(len), FunApp(elem_format)]) => self.read_array(reader, len, elem_format),
-            (Prim::FormatArray64, [FunApp(len), FunApp(elem_format)]) => self.read_array(reader, len, elem_format),
-            (Prim::FormatRepeatUntilEnd, [FunApp(elem_format)]) => self.read_repeat_until_end(reader, elem_format),
+            (Prim::FormatU8, []) => self.read_const(reader, |num| Const::U8(num, UIntStyle::Decimal), read_u8),
+            (Prim::FormatU16Be, []) => self.read_const(reader, |num| Const::U16(num, UIntStyle::Decimal), read_u16be),
+            (Prim::FormatU16Le, []) => self.read_const(reader, |num| Const::U16(num, UIntStyle::Decimal), read_u16le),
+            (Prim::FormatU32Be, []) => self.read_const(reader, |num| Const::U32(num, UIntStyle::Decimal), read_u32be),
+            (Prim::FormatU32Le, []) => self.read_const(reader, |num| Const::U32(num, UIntStyle::Decimal), read_u32le),
+            (Prim::FormatU64Be, []) => self.read_const(reader, |num| Const::U64(num, UIntStyle::Decimal), read_u64be),
+            (Prim::FormatU64Le, []) => self.read_const(reader, |num| Const::U64(num, UIntStyle::Decimal), read_u64le),
+            (Prim::FormatS8, []) => self.read_const(reader, Const::S8, read_s8),
+            (Prim::FormatS16Be, []) => self.read_const(reader, Const::S16, read_s16be),
+            (Prim::FormatS16Le, []) => self.read_const(reader, Const::S16, read_s16le),
+            (Prim::FormatS32Be, []) => self.read_const(reader, Const::S32, read_s32be),
+            (Prim::FormatS32Le, []) => self.read_const(reader, Const::S32, read_s32le),
+            (Prim::FormatS64Be, []) => self.read_const(reader, Const::S64, read_s64be),
+            (Prim::FormatS64Le, []) => self.read_const(reader, Const::S64, read_s64le),
+            (Prim::FormatF32Be, []) => self.read_const(reader, Const::F32, read_f32be),
+            (Prim::FormatF32Le, []) => self.read_const(reader, Const::F32, read_f32le),
+            (Prim::FormatF64Be, []) => self.read_const(reader, Const::F64, read_f64be),
+            (Prim::FormatF64Le, []) => self.read_const(reader, Const::F64, read_f64le),
+            (Prim::FormatCString, []) => self.read_string(reader, Context::read_cstring),
+            (Prim::FormatUtf8Len8, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_utf8),
+            (Prim::FormatUtf8Len16, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_utf8),
+            (Prim::FormatUtf8Len32, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_utf8),
+            (Prim::FormatUtf8Len64, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_utf8),
+            (Prim::FormatUtf16BeLen8, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_utf16be),
+            (Prim::FormatUtf16BeLen16, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_utf16be),
+            (Prim::FormatUtf16BeLen32, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_utf16be),
+            (Prim::FormatUtf16BeLen64, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_utf16be),
+            (Prim::FormatAsciiLen8, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_ascii),
+            (Prim::FormatAsciiLen16, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_ascii),
+            (Prim::FormatAsciiLen32, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_ascii),
+            (Prim::FormatAsciiLen64, [FunApp(len)]) => self.read_string_with_len(reader, len, decode_ascii),
+            (Prim::FormatULeb128, []) => self.read_varint(reader, |num| Const::U64(num, UIntStyle::Decimal), read_uleb128),
+            (Prim::FormatSLeb128, []) => self.read_varint(reader, Const::S64, read_sleb128),
+            (Prim::FormatBase128Be, []) => self.read_varint(reader, |num| Const::U64(num, UIntStyle::Decimal), read_base128be),
+            (Prim::FormatArray8, [FunApp(len), FunApp(elem_format)]) => self.read_array(reader, len, elem_format, children),
+            (Prim::FormatArray16, [FunApp(len), FunApp(elem_format)]) => self.read_array(reader, len, elem_format, children),
+            (Prim::FormatArray32, [FunApp(len), FunApp(elem_format)]) => self.read_array(reader, len, elem_format, children),
+            (Prim::FormatArray64, [FunApp(len), FunApp(elem_format)]) => self.read_array(reader, len, elem_format, children),
+            (Prim::FormatRepeatUntilEnd, [FunApp(elem_format)]) => self.read_repeat_until_end(reader, elem_format, children),
             (Prim::FormatLink, [FunApp(pos), FunApp(elem_format)]) => self.read_link(pos, elem_format),
             (Prim::FormatDeref, [FunApp(elem_format), FunApp(r#ref)]) => self.read_deref(reader, elem_format, r#ref),
+            (Prim::FormatMap, [FunApp(format), _, FunApp(map_expr)]) => self.read_map(reader, format, map_expr),
             (Prim::FormatStreamPos, []) => read_stream_pos(reader),
+            (Prim::FormatEndOfInput, []) => read_end_of_input(reader),
+            (Prim::FormatRemainingBytes, []) => read_remaining_bytes(reader),
             (Prim::FormatSucceed, [_, FunApp(elem)]) => Ok(elem.clone()),
-            (Prim::FormatFail, []) => Err(io::Error::new(io::ErrorKind::Other, "parse failure")),
+            (Prim::FormatFail, []) => Err(ReadError::ReadFailFormat),
             (Prim::FormatUnwrap, [_, FunApp(option)]) => match option.match_prim_spine() {
-                Some((Prim::OptionSome, [FunApp(elem)])) => Ok(elem.clone()),
-                Some((Prim::OptionNone, [])) => Err(io::Error::new(io::ErrorKind::Other, "unwrapped none")),
-                _ => Err(io::Error::new(io::ErrorKind::Other, "invalid option")),
+                Some((Prim::OptionSome, [_, FunApp(elem)])) => Ok(elem.clone()),
+                Some((Prim::OptionNone, [_])) => Err(ReadError::UnwrappedNone),
+                _ => Err(ReadError::InvalidOption),
             },
-            _ => Err(io::Error::new(io::ErrorKind::Other, "invalid format")),
+            _ => Err(ReadError::InvalidFormat),
         }
     }
 
@@ -168,18 +491,27 @@ impl<'arena, 'env> Context<'arena, 'env> {
         reader: &mut dyn SeekRead,
         len: &ArcValue<'arena>,
         elem_format: &ArcValue<'arena>,
-    ) -> io::Result<ArcValue<'arena>> {
+        children: &mut Vec<Provenance<'arena>>,
+    ) -> Result<ArcValue<'arena>, ReadError> {
         let len = match self.elim_context().force(len).as_ref() {
             Value::ConstLit(Const::U8(len, _)) => *len as u64,
             Value::ConstLit(Const::U16(len, _)) => *len as u64,
             Value::ConstLit(Const::U32(len, _)) => *len as u64,
             Value::ConstLit(Const::U64(len, _)) => *len as u64,
-            _ => return Err(io::Error::new(io::ErrorKind::Other, "invalid array length")),
+            _ => return Err(ReadError::InvalidArrayLength),
         };
 
+        // Check the length before reading any elements, as it might have
+        // been read from untrusted data
+        check_limit(Limit::ArrayLen, self.limits.max_array_len, len)?;
+
         let elem_exprs = (0..len)
-            .map(|_| self.read_format(reader, elem_format))
-            .collect::<Result<_, _>>()?;
+            .map(|_| {
+                let (elem_expr, provenance) = self.read_format(reader, elem_format)?;
+                children.push(provenance);
+                Ok(elem_expr)
+            })
+            .collect::<Result<_, ReadError>>()?;
 
         Ok(Arc::new(Value::ArrayLit(elem_exprs)))
     }
@@ -188,41 +520,60 @@ impl<'arena, 'env> Context<'arena, 'env> {
         &mut self,
         reader: &mut dyn SeekRead,
         elem_format: &ArcValue<'arena>,
-    ) -> Result<ArcValue<'arena>, io::Error> {
+        children: &mut Vec<Provenance<'arena>>,
+    ) -> Result<ArcValue<'arena>, ReadError> {
         let mut current_pos = reader.stream_position()?;
         let mut elems = Vec::new();
 
         loop {
             match self.read_format(reader, elem_format) {
-                Ok(elem) => {
+                Ok((elem, provenance)) => {
                     elems.push(elem);
+                    let len = elems.len() as u64;
+                    check_limit(Limit::ArrayLen, self.limits.max_array_len, len)?;
+                    children.push(provenance);
                     current_pos = reader.stream_position()?;
                 }
-                Err(err) => match err.kind() {
-                    io::ErrorKind::UnexpectedEof => {
-                        // FIXME: should this be set to the end of the current stream?
-                        reader.seek(SeekFrom::Start(current_pos))?;
-                        return Ok(Arc::new(Value::ArrayLit(elems)));
-                    }
-                    _ => return Err(err),
-                },
+                Err(ReadError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    // FIXME: should this be set to the end of the current stream?
+                    reader.seek(SeekFrom::Start(current_pos))?;
+                    return Ok(Arc::new(Value::ArrayLit(elems)));
+                }
+                Err(err) => return Err(err),
             };
         }
     }
 
+    fn read_map(
+        &mut self,
+        reader: &mut dyn SeekRead,
+        format: &ArcValue<'arena>,
+        map_expr: &ArcValue<'arena>,
+    ) -> Result<ArcValue<'arena>, ReadError> {
+        // The provenance of the parsed value is not recorded, as it does not
+        // necessarily mirror the structure of the mapped value. The bytes that
+        // it was read from are still recorded in the coverage.
+        let (expr, _) = self.read_format(reader, format)?;
+        Ok(self.elim_context().fun_app(map_expr.clone(), expr))
+    }
+
     fn read_link(
         &mut self,
         pos: &ArcValue<'arena>,
         elem_format: &ArcValue<'arena>,
-    ) -> io::Result<ArcValue<'arena>> {
+    ) -> Result<ArcValue<'arena>, ReadError> {
         let pos = match self.elim_context().force(pos).as_ref() {
             Value::ConstLit(Const::Pos(pos)) => *pos,
-            _ => return Err(io::Error::new(io::ErrorKind::Other, "invalid link pos")),
+            _ => return Err(ReadError::InvalidLinkPos),
         };
 
-        self.pending_formats.push((pos, elem_format.clone()));
+        self.links_read += 1;
+        check_limit(Limit::Links, self.limits.max_links, self.links_read)?;
+
+        self.pending_formats
+            .push((pos, elem_format.clone(), self.current_ref));
 
-        Ok(Arc::new(Value::ConstLit(Const::Ref(pos))))
+        Ok(semantics::const_value(Const::Ref(pos)))
     }
 
     fn read_deref(
@@ -230,15 +581,10 @@ impl<'arena, 'env> Context<'arena, 'env> {
         reader: &mut dyn SeekRead,
         format: &ArcValue<'arena>,
         r#ref: &ArcValue<'arena>,
-    ) -> io::Result<ArcValue<'arena>> {
+    ) -> Result<ArcValue<'arena>, ReadError> {
         let pos = match self.elim_context().force(r#ref).as_ref() {
             Value::ConstLit(Const::Ref(pos)) => *pos,
-            _ => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "invalid format reference",
-                ))
-            }
+            _ => return Err(ReadError::InvalidRef),
         };
 
         self.read_cached_ref(reader, pos, format)
@@ -255,8 +601,32 @@ impl<'arena, 'env> Context<'arena, 'env> {
         // `ParsedRef` in the cache during `read_link`, and storing the index of
         // that parsed reference alongside the position in `Const::Ref`.
 
-        (self.cached_refs.get(&pos)?.iter())
-            .find(|r| self.conversion_context().is_equal(&r.format, &format))
+        (self.cached_refs.get(&pos)?.iter()).find(|r| self.is_equal_format(&r.format, format))
+    }
+
+    /// Check if two formats are equal, skipping the conversion check if they
+    /// are the same value.
+    fn is_equal_format(&self, format0: &ArcValue<'_>, format1: &ArcValue<'_>) -> bool {
+        Arc::ptr_eq(format0, format1) || self.conversion_context().is_equal(format0, format1)
+    }
+
+    /// Return an error if reading `format` at `pos` would lead back to a
+    /// reference in the chain that led to the current reference.
+    fn check_cyclic_ref(&self, pos: u64, format: &ArcValue<'arena>) -> Result<(), ReadError> {
+        let mut positions = vec![pos];
+        let mut current_ref = self.current_ref;
+
+        while let Some(index) = current_ref {
+            let (ref_pos, ref_format, linked_from) = &self.ref_chains[index];
+            positions.push(*ref_pos);
+            if *ref_pos == pos && self.is_equal_format(ref_format, format) {
+                positions.reverse();
+                return Err(ReadError::CyclicRef(positions));
+            }
+            current_ref = *linked_from;
+        }
+
+        Ok(())
     }
 
     fn read_cached_ref(
@@ -264,7 +634,13 @@ impl<'arena, 'env> Context<'arena, 'env> {
         reader: &mut dyn SeekRead,
         pos: u64,
         format: &ArcValue<'arena>,
-    ) -> io::Result<ArcValue<'arena>> {
+    ) -> Result<ArcValue<'arena>, ReadError> {
+        // Guard against chains of links and dereferences that lead back to a
+        // reference that they started from, which would otherwise never
+        // terminate. This is checked before the cache, as the reference might
+        // have already been read by the time the chain returns to it.
+        self.check_cyclic_ref(pos, format)?;
+
         if let Some(parsed_ref) = self.lookup_cached_ref(pos, &format) {
             return Ok(parsed_ref.expr.clone());
         }
@@ -274,18 +650,17 @@ impl<'arena, 'env> Context<'arena, 'env> {
         // Seek to current current ref location
         reader.seek(SeekFrom::Start(pos))?;
         // Parse the data at that location
-        let expr = self.read_format(reader, &format)?;
+        let linked_from = self.current_ref;
+        self.current_ref = Some(self.ref_chains.len());
+        self.ref_chains.push((pos, format.clone(), linked_from));
+        self.frames.push(Frame::ReadRef(pos));
+        let result = self.read_format(reader, &format);
+        self.frames.pop();
+        self.current_ref = linked_from;
+        let (expr, provenance) = result?;
         // Reset reader back to the original position
         reader.seek(SeekFrom::Start(initial_pos))?;
 
-        // We might have parsed the current reference during the above call to
-        // `read_format`. It's unclear if this could ever happen in practice,
-        // especially without succumbing to non-termination, but we'll panic
-        // here just in case.
-        if let Some(_) = self.lookup_cached_ref(pos, &format) {
-            panic!("recursion found when storing cached reference {}", pos);
-        }
-
         // Store the parsed reference in the reference cache
         self.cached_refs
             .entry(pos)
@@ -293,28 +668,188 @@ impl<'arena, 'env> Context<'arena, 'env> {
             .push(ParsedRef {
                 format: format.clone(),
                 expr: expr.clone(),
+                provenance,
             });
 
         Ok(expr)
     }
+
+    fn read_const<T>(
+        &mut self,
+        reader: &mut dyn SeekRead,
+        wrap_const: fn(T) -> Const<'arena>,
+        read: fn(&mut dyn SeekRead) -> io::Result<T>,
+    ) -> Result<ArcValue<'arena>, ReadError> {
+        self.count_bytes_read(std::mem::size_of::<T>() as u64)?;
+
+        let start = reader.stream_position()?;
+        let data = read(reader)?;
+        self.record_read(start, start + std::mem::size_of::<T>() as u64);
+
+        Ok(semantics::const_value(wrap_const(data)))
+    }
+
+    fn read_string_with_len(
+        &mut self,
+        reader: &mut dyn SeekRead,
+        len: &ArcValue<'arena>,
+        decode: fn(Vec<u8>) -> Result<String, ReadError>,
+    ) -> Result<ArcValue<'arena>, ReadError> {
+        let len = match self.elim_context().force(len).as_ref() {
+            Value::ConstLit(Const::U8(len, _)) => *len as u64,
+            Value::ConstLit(Const::U16(len, _)) => *len as u64,
+            Value::ConstLit(Const::U32(len, _)) => *len as u64,
+            Value::ConstLit(Const::U64(len, _)) => *len,
+            _ => return Err(ReadError::InvalidStringLength),
+        };
+
+        self.read_string(reader, |context, reader| {
+            // Avoid allocating a buffer for lengths that exceed the limits
+            context.count_bytes_read(len)?;
+
+            let mut data = Vec::new();
+            reader.take(len).read_to_end(&mut data)?;
+            match data.len() as u64 == len {
+                true => decode(data),
+                false => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            }
+        })
+    }
+
+    /// Read a NUL-terminated, UTF-8 encoded string, consuming the terminator.
+    fn read_cstring(&mut self, reader: &mut dyn SeekRead) -> Result<String, ReadError> {
+        let mut data = Vec::new();
+        loop {
+            // The length of the string is unknown, so the limit is checked
+            // before each byte is read
+            self.count_bytes_read(1)?;
+            match read_u8(reader)? {
+                0 => return decode_utf8(data),
+                byte => data.push(byte),
+            }
+        }
+    }
+
+    /// Read a string, where `read` is responsible for counting the bytes that
+    /// it reads towards the limits.
+    fn read_string(
+        &mut self,
+        reader: &mut dyn SeekRead,
+        read: impl FnOnce(&mut Self, &mut dyn SeekRead) -> Result<String, ReadError>,
+    ) -> Result<ArcValue<'arena>, ReadError> {
+        let start = reader.stream_position()?;
+        let string = read(self, reader)?;
+        let end = reader.stream_position()?;
+        self.record_read(start, end);
+
+        let string = alloc::str_to_scope(self.scope, &string);
+        Ok(semantics::const_value(Const::String(string)))
+    }
+
+    fn read_varint<T>(
+        &mut self,
+        reader: &mut dyn SeekRead,
+        wrap_const: fn(T) -> Const<'arena>,
+        read: fn(&mut dyn SeekRead) -> Result<T, ReadError>,
+    ) -> Result<ArcValue<'arena>, ReadError> {
+        // The length of a variable-length integer is only known once it has
+        // been read, so the limit is checked afterwards
+        let start = reader.stream_position()?;
+        let data = read(reader)?;
+        let end = reader.stream_position()?;
+        self.record_read(start, end);
+        self.count_bytes_read(end - start)?;
+
+        Ok(semantics::const_value(wrap_const(data)))
+    }
+}
+
+/// A region of the binary data, along with the number of times it was read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CoverageRegion {
+    /// The offset of the first byte in the region
+    pub start: u64,
+    /// The offset after the last byte in the region
+    pub end: u64,
+    /// The number of times the bytes in the region were read
+    pub reads: usize,
+}
+
+/// The number of times each byte of the binary data was read while parsing,
+/// as recorded by [`Context::coverage`].
+///
+/// Bytes that were never read suggest that a format description might be
+/// incomplete, and bytes that were read more than once might be the result of
+/// overlapping formats or references.
+#[derive(Debug, Clone)]
+pub struct Coverage {
+    regions: Vec<CoverageRegion>,
+}
+
+impl Coverage {
+    /// Compute the coverage of binary data of length `len` from the ranges of
+    /// bytes that were read from it.
+    fn new(len: u64, read_ranges: &[(u64, u64)]) -> Coverage {
+        let mut events = vec![(0, 0), (len, 0)];
+        for (start, end) in read_ranges {
+            events.push((*start, 1));
+            events.push((*end, -1));
+        }
+        events.sort_unstable();
+
+        let mut regions = Vec::<CoverageRegion>::new();
+        let mut reads = 0;
+        for (start, end) in Iterator::zip(events.iter(), events.iter().skip(1)) {
+            reads += start.1;
+            let (start, end) = (start.0, end.0);
+            if start == end {
+                continue;
+            }
+
+            let reads = reads as usize;
+            match regions.last_mut() {
+                Some(region) if region.end == start && region.reads == reads => region.end = end,
+                _ => regions.push(CoverageRegion { start, end, reads }),
+            }
+        }
+
+        Coverage { regions }
+    }
+
+    /// Contiguous regions of the binary data that were read the same number
+    /// of times, in order of their position.
+    pub fn regions(&self) -> &[CoverageRegion] {
+        &self.regions
+    }
 }
 
 pub trait SeekRead: Seek + Read {}
 
 impl<T: Seek + Read> SeekRead for T {}
 
-fn read_stream_pos<'arena>(reader: &mut dyn SeekRead) -> io::Result<ArcValue<'arena>> {
+fn read_stream_pos<'arena>(reader: &mut dyn SeekRead) -> Result<ArcValue<'arena>, ReadError> {
     let pos = reader.stream_position()?;
-    Ok(Arc::new(Value::ConstLit(Const::Pos(pos))))
+    Ok(semantics::const_value(Const::Pos(pos)))
 }
 
-fn read_const<'arena, T>(
-    reader: &mut dyn SeekRead,
-    wrap_const: fn(T) -> Const,
-    read: fn(&mut dyn SeekRead) -> io::Result<T>,
-) -> io::Result<ArcValue<'arena>> {
-    let data = read(reader)?;
-    Ok(Arc::new(Value::ConstLit(wrap_const(data))))
+fn read_end_of_input<'arena>(reader: &mut dyn SeekRead) -> Result<ArcValue<'arena>, ReadError> {
+    match remaining_bytes(reader)? {
+        0 => Ok(Arc::new(Value::RecordLit(&[], Vec::new()))),
+        _ => Err(ReadError::ExpectedEndOfInput),
+    }
+}
+
+fn read_remaining_bytes<'arena>(reader: &mut dyn SeekRead) -> Result<ArcValue<'arena>, ReadError> {
+    let len = remaining_bytes(reader)?;
+    Ok(semantics::const_value(Const::U64(len, UIntStyle::Decimal)))
+}
+
+/// The number of bytes between the current position and the end of the stream.
+fn remaining_bytes(reader: &mut dyn SeekRead) -> io::Result<u64> {
+    let pos = reader.stream_position()?;
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(pos))?;
+    Ok(len.saturating_sub(pos))
 }
 
 fn read_u8(reader: &mut dyn SeekRead) -> io::Result<u8> {
@@ -333,6 +868,90 @@ fn read_array<const N: usize>(reader: &mut dyn SeekRead) -> io::Result<[u8; N]>
     Ok(buf)
 }
 
+/// The maximum number of bytes in a base-128 encoded 64-bit integer, which is
+/// `ceil(64 / 7)`.
+const MAX_BASE128_LEN: u32 = 10;
+
+/// Read an unsigned, little-endian base-128 integer.
+fn read_uleb128(reader: &mut dyn SeekRead) -> Result<u64, ReadError> {
+    let mut num = 0;
+    for index in 0..MAX_BASE128_LEN {
+        let byte = read_u8(reader)?;
+        let (bits, shift) = (u64::from(byte & 0x7f), index * 7);
+        // Bits shifted past the end of the integer must be zero
+        if (bits << shift) >> shift != bits {
+            return Err(ReadError::VarIntOverflow);
+        }
+        num |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(num);
+        }
+    }
+    Err(ReadError::VarIntOverflow)
+}
+
+/// Read a signed, little-endian base-128 integer.
+fn read_sleb128(reader: &mut dyn SeekRead) -> Result<i64, ReadError> {
+    let mut num = 0;
+    for index in 0..MAX_BASE128_LEN {
+        let byte = read_u8(reader)?;
+        let (bits, shift) = (i64::from(byte & 0x7f), index * 7);
+        // The final byte may only contain the sign bit, and its extension
+        if index == MAX_BASE128_LEN - 1 && !(bits == 0 || bits == 0x7f) {
+            return Err(ReadError::VarIntOverflow);
+        }
+        num |= bits << shift;
+        if byte & 0x80 == 0 {
+            // Sign extend the integer if the sign bit is set
+            if shift + 7 < 64 && byte & 0x40 != 0 {
+                num |= -1 << (shift + 7);
+            }
+            return Ok(num);
+        }
+    }
+    Err(ReadError::VarIntOverflow)
+}
+
+/// Read an unsigned, big-endian base-128 integer.
+fn read_base128be(reader: &mut dyn SeekRead) -> Result<u64, ReadError> {
+    let mut num: u64 = 0;
+    // Leading zero digits don't change the number, so the length is capped to
+    // avoid reading them forever
+    for _ in 0..MAX_BASE128_LEN {
+        let byte = read_u8(reader)?;
+        if num.leading_zeros() < 7 {
+            return Err(ReadError::VarIntOverflow);
+        }
+        num = (num << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Ok(num);
+        }
+    }
+    Err(ReadError::VarIntOverflow)
+}
+
+fn decode_utf8(data: Vec<u8>) -> Result<String, ReadError> {
+    String::from_utf8(data).map_err(|_| ReadError::InvalidUtf8)
+}
+
+fn decode_utf16be(data: Vec<u8>) -> Result<String, ReadError> {
+    let units = data.chunks_exact(2);
+    if !units.remainder().is_empty() {
+        return Err(ReadError::InvalidUtf16);
+    }
+    let units = units.map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<_, _>>()
+        .map_err(|_| ReadError::InvalidUtf16)
+}
+
+fn decode_ascii(data: Vec<u8>) -> Result<String, ReadError> {
+    match data.is_ascii() {
+        true => decode_utf8(data),
+        false => Err(ReadError::InvalidAscii),
+    }
+}
+
 /// Generates a function that reads a multi-byte primitive.
 macro_rules! read_multibyte_prim {
     ($read_multibyte_prim:ident, $from_bytes:ident, $T:ident) => {
@@ -359,3 +978,420 @@ read_multibyte_prim!(read_f32le, from_le_bytes, f32);
 read_multibyte_prim!(read_f32be, from_be_bytes, f32);
 read_multibyte_prim!(read_f64le, from_le_bytes, f64);
 read_multibyte_prim!(read_f64be, from_be_bytes, f64);
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::core::semantics::EvalContext;
+    use crate::core::Term;
+    use crate::env::{LocalVar, SharedEnv, UniqueEnv};
+    use crate::StringInterner;
+
+    #[test]
+    fn read_provenance() {
+        let mut interner = StringInterner::new();
+        let (x, y) = (interner.get_or_intern("x"), interner.get_or_intern("y"));
+        let scope = Scope::new();
+
+        // { x <- u8, y <- array8 2 u16be }
+        let labels = [x, y];
+        let formats = [
+            Term::Prim(Prim::FormatU8),
+            Term::FunApp(
+                &Term::FunApp(
+                    &Term::Prim(Prim::FormatArray8),
+                    &Term::ConstLit(Const::U8(2, UIntStyle::Decimal)),
+                ),
+                &Term::Prim(Prim::FormatU16Be),
+            ),
+        ];
+
+        let flexible_exprs = UniqueEnv::new();
+        let format = EvalContext::new(&mut SharedEnv::new(), &flexible_exprs)
+            .eval(&Term::FormatRecord(&labels, &formats));
+        let mut reader = Cursor::new([1, 0, 2, 0, 3]);
+        let refs = Context::new(&scope, &flexible_exprs)
+            .read_entrypoint(&mut reader, format)
+            .unwrap();
+
+        let ranges = |provenance: &Provenance<'_>| {
+            (provenance.children.iter())
+                .map(|child| (child.start, child.end))
+                .collect::<Vec<_>>()
+        };
+        let provenance = &refs[&0][0].provenance;
+        assert_eq!((provenance.start, provenance.end), (0, 5));
+        assert_eq!(ranges(provenance), [(0, 1), (1, 5)]);
+        assert_eq!(ranges(&provenance.children[1]), [(1, 3), (3, 5)]);
+    }
+
+    #[test]
+    fn read_coverage() {
+        let mut interner = StringInterner::new();
+        let (x, y) = (interner.get_or_intern("x"), interner.get_or_intern("y"));
+        let scope = Scope::new();
+
+        // overlap { x <- u16be, y <- u8 }
+        let labels = [x, y];
+        let formats = [Term::Prim(Prim::FormatU16Be), Term::Prim(Prim::FormatU8)];
+
+        let flexible_exprs = UniqueEnv::new();
+        let format = EvalContext::new(&mut SharedEnv::new(), &flexible_exprs)
+            .eval(&Term::FormatOverlap(&labels, &formats));
+        let mut reader = Cursor::new([1, 2, 3]);
+        let mut context = Context::new(&scope, &flexible_exprs);
+        context.read_entrypoint(&mut reader, format).unwrap();
+
+        let region = |start, end, reads| CoverageRegion { start, end, reads };
+        assert_eq!(
+            context.coverage(3).regions(),
+            [region(0, 1, 2), region(1, 2, 1), region(2, 3, 0)],
+        );
+    }
+
+    #[test]
+    fn read_error_trace() {
+        let mut interner = StringInterner::new();
+        let (x, y) = (interner.get_or_intern("x"), interner.get_or_intern("y"));
+        let scope = Scope::new();
+
+        // { x <- u8, y <- array8 (true false) u8 }
+        let labels = [x, y];
+        let formats = [
+            Term::Prim(Prim::FormatU8),
+            Term::FunApp(
+                &Term::FunApp(
+                    &Term::Prim(Prim::FormatArray8),
+                    &Term::FunApp(
+                        &Term::ConstLit(Const::Bool(true)),
+                        &Term::ConstLit(Const::Bool(false)),
+                    ),
+                ),
+                &Term::Prim(Prim::FormatU8),
+            ),
+        ];
+
+        let flexible_exprs = UniqueEnv::new();
+        let format = EvalContext::new(&mut SharedEnv::new(), &flexible_exprs)
+            .eval(&Term::FormatRecord(&labels, &formats));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut reader = Cursor::new([1, 2, 3]);
+            let mut context = Context::new(&scope, &flexible_exprs);
+            let _ = context.read_entrypoint(&mut reader, format);
+        }));
+        let error = result.unwrap_err().downcast::<semantics::Error>().unwrap();
+
+        assert!(matches!(
+            error.kind,
+            semantics::ErrorKind::InvalidFunctionApp
+        ));
+        assert!(matches!(
+            error.trace[..],
+            [Frame::ReadField(label), Frame::ReadRef(0)] if label == y,
+        ));
+    }
+
+    #[test]
+    fn read_cyclic_ref() {
+        let mut interner = StringInterner::new();
+        let (ptr, next) = (
+            interner.get_or_intern("ptr"),
+            interner.get_or_intern("next"),
+        );
+        let scope = Scope::new();
+
+        // Formats can't be defined recursively in the surface language, so we
+        // refer to the format through a flexible variable that is solved with
+        // the format itself, producing:
+        //
+        // ?0 := { ptr <- u8, next <- link (pos_add_u8 0 ptr) ?0 }
+        let format_var = crate::env::global_vars().next().unwrap();
+        let labels = [ptr, next];
+        let pos = Term::FunApp(
+            &Term::FunApp(&Term::Prim(Prim::PosAddU8), &Term::ConstLit(Const::Pos(0))),
+            &Term::RigidVar(LocalVar::last()),
+        );
+        let formats = [
+            Term::Prim(Prim::FormatU8),
+            Term::FunApp(
+                &Term::FunApp(&Term::Prim(Prim::FormatLink), &pos),
+                &Term::FlexibleVar(format_var),
+            ),
+        ];
+
+        let mut flexible_exprs = UniqueEnv::new();
+        let format = EvalContext::new(&mut SharedEnv::new(), &flexible_exprs)
+            .eval(&Term::FormatRecord(&labels, &formats));
+        flexible_exprs.push(Some(format.clone()));
+
+        // A chain of links from 0 -> 2 -> 1 -> 2
+        let mut reader = Cursor::new([2, 2, 1]);
+        let mut context = Context::new(&scope, &flexible_exprs);
+        match context.read_entrypoint(&mut reader, format.clone()) {
+            Err(ReadError::CyclicRef(positions)) => assert_eq!(positions, [2, 1, 2]),
+            result => panic!("unexpected result: {:?}", result.map(|_| ())),
+        }
+
+        // A chain of links that ends with a position outside of the data
+        let mut reader = Cursor::new([2, 2, 3]);
+        let mut context = Context::new(&scope, &flexible_exprs);
+        assert!(matches!(
+            context.read_entrypoint(&mut reader, format),
+            Err(ReadError::Io(_)),
+        ));
+    }
+
+    #[test]
+    fn read_limits() {
+        // array8 200 u8
+        let array_format = Term::FunApp(
+            &Term::Prim(Prim::FormatArray8),
+            &Term::ConstLit(Const::U8(200, UIntStyle::Decimal)),
+        );
+        let format = Term::FunApp(&array_format, &Term::Prim(Prim::FormatU8));
+
+        let scope = Scope::new();
+        let flexible_exprs = UniqueEnv::new();
+        let format = EvalContext::new(&mut SharedEnv::new(), &flexible_exprs).eval(&format);
+        let read_with_limits = |limits| {
+            let mut context = Context::new(&scope, &flexible_exprs);
+            context.set_limits(limits);
+            context.read_entrypoint(&mut Cursor::new([0; 256]), format.clone())
+        };
+
+        let limits = Limits::default();
+        assert!(read_with_limits(limits).is_ok());
+
+        let limits = Limits::UNLIMITED;
+        assert!(read_with_limits(limits).is_ok());
+
+        let limits = Limits {
+            max_array_len: Some(100),
+            ..Limits::default()
+        };
+        match read_with_limits(limits) {
+            Err(ReadError::LimitExceeded(Limit::ArrayLen, 100)) => {}
+            result => panic!("unexpected result: {:?}", result.map(|_| ())),
+        }
+
+        let limits = Limits {
+            max_bytes_read: Some(100),
+            ..Limits::default()
+        };
+        match read_with_limits(limits) {
+            Err(ReadError::LimitExceeded(Limit::BytesRead, 100)) => {}
+            result => panic!("unexpected result: {:?}", result.map(|_| ())),
+        }
+
+        let limits = Limits {
+            max_depth: Some(1),
+            ..Limits::default()
+        };
+        match read_with_limits(limits) {
+            Err(ReadError::LimitExceeded(Limit::Depth, 1)) => {}
+            result => panic!("unexpected result: {:?}", result.map(|_| ())),
+        };
+
+        // The limit should be reached before an unterminated string is read
+        // through to the end of the data
+        let cstring_format = semantics::prim_value(Prim::FormatCString);
+        let mut context = Context::new(&scope, &flexible_exprs);
+        context.set_limits(Limits {
+            max_bytes_read: Some(100),
+            ..Limits::default()
+        });
+        match context.read_entrypoint(&mut Cursor::new(vec![b'a'; 1 << 20]), cstring_format) {
+            Err(ReadError::LimitExceeded(Limit::BytesRead, 100)) => {}
+            result => panic!("unexpected result: {:?}", result.map(|_| ())),
+        };
+        assert_eq!(context.bytes_read, 101);
+    }
+
+    #[test]
+    fn read_default_limits() {
+        // Zero-width elements never reach the end of the data, so these
+        // formats should be stopped by the default limits
+        let max_len = Term::ConstLit(Const::U64(u64::MAX, UIntStyle::Decimal));
+        let array_format = Term::FunApp(&Term::Prim(Prim::FormatArray64), &max_len);
+        let array_format = Term::FunApp(&array_format, &Term::Prim(Prim::FormatStreamPos));
+        let repeat_format = Term::FunApp(
+            &Term::Prim(Prim::FormatRepeatUntilEnd),
+            &Term::Prim(Prim::FormatStreamPos),
+        );
+
+        let scope = Scope::new();
+        let flexible_exprs = UniqueEnv::new();
+        for format in [array_format, repeat_format] {
+            let format = EvalContext::new(&mut SharedEnv::new(), &flexible_exprs).eval(&format);
+            let mut context = Context::new(&scope, &flexible_exprs);
+            match context.read_entrypoint(&mut Cursor::new([]), format) {
+                Err(ReadError::LimitExceeded(_, _)) => {}
+                result => panic!("unexpected result: {:?}", result.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn read_varints() {
+        let uleb128 = |data: &[u8]| read_uleb128(&mut Cursor::new(data));
+        let sleb128 = |data: &[u8]| read_sleb128(&mut Cursor::new(data));
+        let base128be = |data: &[u8]| read_base128be(&mut Cursor::new(data));
+
+        assert_eq!(uleb128(&[0x02]).ok(), Some(2));
+        assert_eq!(uleb128(&[0xe5, 0x8e, 0x26]).ok(), Some(624485));
+        assert_eq!(
+            uleb128(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]).ok(),
+            Some(u64::MAX)
+        );
+        assert!(matches!(
+            uleb128(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02]),
+            Err(ReadError::VarIntOverflow)
+        ));
+        assert!(matches!(
+            uleb128(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00]),
+            Err(ReadError::VarIntOverflow)
+        ));
+        assert!(matches!(uleb128(&[0x80]), Err(ReadError::Io(_))));
+
+        assert_eq!(sleb128(&[0x02]).ok(), Some(2));
+        assert_eq!(sleb128(&[0x7e]).ok(), Some(-2));
+        assert_eq!(sleb128(&[0xc0, 0xbb, 0x78]).ok(), Some(-123456));
+        assert_eq!(
+            sleb128(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f]).ok(),
+            Some(i64::MIN)
+        );
+        assert_eq!(
+            sleb128(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]).ok(),
+            Some(i64::MAX)
+        );
+        assert!(matches!(
+            sleb128(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+            Err(ReadError::VarIntOverflow)
+        ));
+
+        assert_eq!(base128be(&[0x06]).ok(), Some(6));
+        assert_eq!(base128be(&[0x86, 0xf7, 0x0d]).ok(), Some(113549));
+        assert_eq!(
+            base128be(&[0x81, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]).ok(),
+            Some(u64::MAX)
+        );
+        assert!(matches!(
+            base128be(&[0x82, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00]),
+            Err(ReadError::VarIntOverflow)
+        ));
+        assert_eq!(
+            base128be(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01]).ok(),
+            Some(1)
+        );
+        assert!(matches!(
+            base128be(&[0x80; 1 << 20]),
+            Err(ReadError::VarIntOverflow)
+        ));
+    }
+
+    #[test]
+    fn read_strings() {
+        let scope = Scope::new();
+        let flexible_exprs = UniqueEnv::new();
+        let read_string = |format: &Term<'_>, data: &[u8]| {
+            let format = EvalContext::new(&mut SharedEnv::new(), &flexible_exprs).eval(format);
+            let refs = Context::new(&scope, &flexible_exprs)
+                .read_entrypoint(&mut Cursor::new(data), format)?;
+            match refs[&0][0].expr.as_ref() {
+                Value::ConstLit(Const::String(string)) => Ok(string.to_string()),
+                _ => panic!("expected a string"),
+            }
+        };
+        let with_len = |prim, len| {
+            let len = Term::ConstLit(Const::U64(len, UIntStyle::Decimal));
+            move |data: &[u8]| read_string(&Term::FunApp(&Term::Prim(prim), &len), data)
+        };
+
+        let cstring = |data: &[u8]| read_string(&Term::Prim(Prim::FormatCString), data);
+        assert_eq!(cstring(b"hello\0world").ok().as_deref(), Some("hello"));
+        assert!(matches!(cstring(b"hello"), Err(ReadError::Io(_))));
+        assert!(matches!(cstring(b"\xff\0"), Err(ReadError::InvalidUtf8)));
+
+        assert_eq!(
+            with_len(Prim::FormatUtf8Len64, 4)("héllo".as_bytes())
+                .ok()
+                .as_deref(),
+            Some("hél")
+        );
+        assert!(matches!(
+            with_len(Prim::FormatUtf8Len64, 2)("héllo".as_bytes()),
+            Err(ReadError::InvalidUtf8)
+        ));
+        assert!(matches!(
+            with_len(Prim::FormatUtf8Len64, 6)(b"hello"),
+            Err(ReadError::Io(_))
+        ));
+
+        assert_eq!(
+            with_len(Prim::FormatUtf16BeLen64, 4)(b"\0h\0i")
+                .ok()
+                .as_deref(),
+            Some("hi")
+        );
+        assert!(matches!(
+            with_len(Prim::FormatUtf16BeLen64, 3)(b"\0h\0i"),
+            Err(ReadError::InvalidUtf16)
+        ));
+        assert!(matches!(
+            with_len(Prim::FormatUtf16BeLen64, 2)(b"\xd8\0"),
+            Err(ReadError::InvalidUtf16)
+        ));
+
+        assert_eq!(
+            with_len(Prim::FormatAsciiLen64, 2)(b"hi").ok().as_deref(),
+            Some("hi")
+        );
+        assert!(matches!(
+            with_len(Prim::FormatAsciiLen64, 2)("é".as_bytes()),
+            Err(ReadError::InvalidAscii)
+        ));
+
+        let len = Term::ConstLit(Const::U8(2, UIntStyle::Decimal));
+        let utf8_8 = Term::FunApp(&Term::Prim(Prim::FormatUtf8Len8), &len);
+        assert_eq!(read_string(&utf8_8, b"hi!").ok().as_deref(), Some("hi"));
+    }
+
+    #[test]
+    fn read_many_strings() {
+        // More distinct strings than could be stored in the string interner
+        const LEN: usize = 70_000;
+        let data = (0..LEN).fold(Vec::new(), |mut data, i| {
+            data.extend_from_slice(format!("string {}\0", i).as_bytes());
+            data
+        });
+
+        // repeat_until_end cstring
+        let format = Term::FunApp(
+            &Term::Prim(Prim::FormatRepeatUntilEnd),
+            &Term::Prim(Prim::FormatCString),
+        );
+        let scope = Scope::new();
+        let flexible_exprs = UniqueEnv::new();
+        let format = EvalContext::new(&mut SharedEnv::new(), &flexible_exprs).eval(&format);
+        let refs = Context::new(&scope, &flexible_exprs)
+            .read_entrypoint(&mut Cursor::new(data), format)
+            .unwrap();
+
+        let strings = match refs[&0][0].expr.as_ref() {
+            Value::ArrayLit(exprs) => (exprs.iter())
+                .map(|expr| match expr.as_ref() {
+                    Value::ConstLit(r#const) => *r#const,
+                    _ => panic!("expected a string"),
+                })
+                .collect::<Vec<_>>(),
+            _ => panic!("expected an array"),
+        };
+        assert_eq!(strings.len(), LEN);
+        assert_eq!(strings[LEN - 1], Const::String("string 69999"));
+
+        // Strings are ordered by their contents, not the order they were read in
+        assert!(strings[10] < strings[2]);
+    }
+}
@@ -2,11 +2,13 @@
 //! [normalisation by evaluation](https://en.wikipedia.org/wiki/Normalisation_by_evaluation).
 
 use scoped_arena::Scope;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::panic::panic_any;
 use std::sync::Arc;
 
-use crate::alloc::SliceVec;
-use crate::core::{Const, EntryInfo, Prim, Term, UIntStyle};
+use crate::alloc::{self, SliceVec};
+use crate::core::{Const, EntryInfo, Prim, Term, UIntStyle, UniverseLevel};
 use crate::env::{EnvLen, GlobalVar, SharedEnv, SliceEnv};
 use crate::StringId;
 
@@ -24,7 +26,7 @@ pub enum Value<'arena> {
     Stuck(Head, Vec<Elim<'arena>>),
 
     /// Universes.
-    Universe,
+    Universe(UniverseLevel),
 
     /// Dependent function types.
     FunType(Option<StringId>, ArcValue<'arena>, Closure<'arena>),
@@ -46,7 +48,7 @@ pub enum Value<'arena> {
     FormatOverlap(&'arena [StringId], Telescope<'arena>),
 
     /// Constant literals.
-    ConstLit(Const),
+    ConstLit(Const<'arena>),
 }
 
 impl<'arena> Value<'arena> {
@@ -71,6 +73,99 @@ impl<'arena> Value<'arena> {
     }
 }
 
+/// Commonly used values, allocated once and then shared in order to avoid
+/// allocating temporary values during elaboration and evaluation.
+///
+/// Values are covariant in `'arena`, so these can be used in place of values
+/// that were allocated in any arena.
+struct InternedValues {
+    /// Universes, indexed by their level.
+    universes: Vec<ArcValue<'static>>,
+    /// Primitives with empty spines, indexed by their position in [`Prim::ALL`].
+    prims: Vec<ArcValue<'static>>,
+    /// Boolean constants, indexed by their value.
+    bools: [ArcValue<'static>; 2],
+    /// Small, unsigned integer constants using [`UIntStyle::Decimal`], indexed
+    /// by their value.
+    u8s: Vec<ArcValue<'static>>,
+    u16s: Vec<ArcValue<'static>>,
+    u32s: Vec<ArcValue<'static>>,
+    u64s: Vec<ArcValue<'static>>,
+}
+
+impl InternedValues {
+    /// The number of universe levels to intern.
+    const UNIVERSE_COUNT: UniverseLevel = 4;
+    /// The largest unsigned integer to intern for each integer type.
+    const UINT_MAX: u8 = u8::MAX;
+
+    fn get() -> &'static InternedValues {
+        lazy_static::lazy_static! {
+            static ref INTERNED_VALUES: InternedValues = InternedValues::new();
+        }
+
+        &INTERNED_VALUES
+    }
+
+    fn new() -> InternedValues {
+        let uints = |wrap_const: fn(u8) -> Const<'static>| {
+            (0..=InternedValues::UINT_MAX)
+                .map(|num| Arc::new(Value::ConstLit(wrap_const(num))))
+                .collect()
+        };
+
+        InternedValues {
+            universes: (0..InternedValues::UNIVERSE_COUNT)
+                .map(|level| Arc::new(Value::Universe(level)))
+                .collect(),
+            prims: (Prim::ALL.iter())
+                .map(|prim| Arc::new(Value::prim(*prim, [])))
+                .collect(),
+            bools: [false, true].map(|b| Arc::new(Value::ConstLit(Const::Bool(b)))),
+            u8s: uints(|num| Const::U8(num, UIntStyle::Decimal)),
+            u16s: uints(|num| Const::U16(num.into(), UIntStyle::Decimal)),
+            u32s: uints(|num| Const::U32(num.into(), UIntStyle::Decimal)),
+            u64s: uints(|num| Const::U64(num.into(), UIntStyle::Decimal)),
+        }
+    }
+}
+
+/// Construct a [universe][Value::Universe], reusing a shared value for small
+/// universe levels.
+pub fn universe_value<'arena>(level: UniverseLevel) -> ArcValue<'arena> {
+    match InternedValues::get().universes.get(level as usize) {
+        Some(value) => value.clone(),
+        None => Arc::new(Value::Universe(level)),
+    }
+}
+
+/// Construct a primitive with an empty spine, reusing a shared value.
+pub fn prim_value<'arena>(prim: Prim) -> ArcValue<'arena> {
+    // `Prim` has no explicit discriminants, so its discriminants are the same
+    // as its position in `Prim::ALL`.
+    InternedValues::get().prims[prim as usize].clone()
+}
+
+/// Construct a [constant literal][Value::ConstLit], reusing a shared value
+/// for booleans and small, unsigned integers.
+pub fn const_value<'arena>(r#const: Const<'arena>) -> ArcValue<'arena> {
+    let interned_values = InternedValues::get();
+    let interned_uint = |uints: &[ArcValue<'static>], num: u64| -> Option<ArcValue<'arena>> {
+        uints.get(usize::try_from(num).ok()?).cloned()
+    };
+
+    let value = match r#const {
+        Const::Bool(b) => Some(interned_values.bools[usize::from(b)].clone()),
+        Const::U8(num, UIntStyle::Decimal) => interned_uint(&interned_values.u8s, num.into()),
+        Const::U16(num, UIntStyle::Decimal) => interned_uint(&interned_values.u16s, num.into()),
+        Const::U32(num, UIntStyle::Decimal) => interned_uint(&interned_values.u32s, num.into()),
+        Const::U64(num, UIntStyle::Decimal) => interned_uint(&interned_values.u64s, num),
+        _ => None,
+    };
+
+    value.unwrap_or_else(|| Arc::new(Value::ConstLit(r#const)))
+}
+
 /// The head of a [stuck value][Value::Stuck].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Head {
@@ -91,7 +186,7 @@ pub enum Elim<'arena> {
     /// Record projections.
     RecordProj(StringId),
     /// Match on a constant.
-    ConstMatch(Branches<'arena, Const>),
+    ConstMatch(Branches<'arena, Const<'arena>>),
 }
 
 /// A closure is a term that can later be instantiated with a value.
@@ -197,9 +292,23 @@ pub enum SplitBranches<'arena, P> {
 }
 
 /// Errors encountered while interpreting terms.
-// TODO: include stack trace(??)
 #[derive(Clone, Debug)]
-pub enum Error {
+pub struct Error {
+    /// The kind of error that was encountered.
+    pub kind: ErrorKind,
+    /// The evaluation frames that were active when the error was encountered,
+    /// starting with the innermost frame.
+    pub trace: Vec<Frame>,
+}
+
+impl Error {
+    pub fn description(&self) -> &str {
+        self.kind.description()
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum ErrorKind {
     InvalidRigidVar,
     InvalidFlexibleVar,
     InvalidFunctionApp,
@@ -209,18 +318,71 @@ pub enum Error {
     MissingConstDefault,
 }
 
-impl Error {
+impl ErrorKind {
     pub fn description(&self) -> &str {
         match &self {
-            Error::InvalidRigidVar => "invalid rigid variable",
-            Error::InvalidFlexibleVar => "invalid flexible variable",
-            Error::InvalidFunctionApp => "invalid function application",
-            Error::InvalidRecordProj => "invalid record projection",
-            Error::InvalidConstMatch => "invalid constant match",
-            Error::InvalidFormatRepr => "invalid format repr",
-            Error::MissingConstDefault => "missing default expression",
+            ErrorKind::InvalidRigidVar => "invalid rigid variable",
+            ErrorKind::InvalidFlexibleVar => "invalid flexible variable",
+            ErrorKind::InvalidFunctionApp => "invalid function application",
+            ErrorKind::InvalidRecordProj => "invalid record projection",
+            ErrorKind::InvalidConstMatch => "invalid constant match",
+            ErrorKind::InvalidFormatRepr => "invalid format repr",
+            ErrorKind::MissingConstDefault => "missing default expression",
+        }
+    }
+}
+
+/// A frame of the evaluation stack, recorded in [errors][Error] to help locate
+/// where they occurred.
+#[derive(Copy, Clone, Debug)]
+pub enum Frame {
+    /// Evaluating the definition of a let expression.
+    LetDef(Option<StringId>),
+    /// Evaluating a field of a record literal.
+    RecordField(StringId),
+    /// Applying a function literal with the given parameter name.
+    FunApp(Option<StringId>),
+    /// Applying a primitive operation.
+    PrimApp(Prim),
+    /// Reading a field of a record format.
+    ReadField(StringId),
+    /// Reading a reference at the given position.
+    ReadRef(u64),
+}
+
+/// The stack of frames that are currently being evaluated.
+///
+/// This is a linked list that lives on the Rust stack, so pushing a frame is
+/// cheap and does not require any allocations.
+#[derive(Copy, Clone)]
+enum Trace<'trace> {
+    /// Frames that were pushed before evaluation began, starting with the
+    /// outermost frame.
+    Root(&'trace [Frame]),
+    /// A frame pushed on top of a parent trace.
+    Frame(Frame, &'trace Trace<'trace>),
+}
+
+/// Raise an error, recording the frames in the current trace.
+fn error(mut trace: &Trace<'_>, kind: ErrorKind) -> ! {
+    let mut frames = Vec::new();
+    loop {
+        match trace {
+            Trace::Root(root_frames) => {
+                frames.extend(root_frames.iter().rev());
+                break;
+            }
+            Trace::Frame(frame, parent) => {
+                frames.push(*frame);
+                trace = parent;
+            }
         }
     }
+
+    panic_any(Error {
+        kind,
+        trace: frames,
+    })
 }
 
 /// Evaluation context.
@@ -230,6 +392,7 @@ impl Error {
 pub struct EvalContext<'arena, 'env> {
     rigid_exprs: &'env mut SharedEnv<ArcValue<'arena>>,
     flexible_exprs: &'env SliceEnv<Option<ArcValue<'arena>>>,
+    trace: Trace<'env>,
 }
 
 impl<'arena, 'env> EvalContext<'arena, 'env> {
@@ -240,11 +403,25 @@ impl<'arena, 'env> EvalContext<'arena, 'env> {
         EvalContext {
             rigid_exprs,
             flexible_exprs,
+            trace: Trace::Root(&[]),
         }
     }
 
     fn elim_context(&self) -> ElimContext<'arena, 'env> {
-        ElimContext::new(self.flexible_exprs)
+        ElimContext {
+            flexible_exprs: self.flexible_exprs,
+            trace: self.trace,
+        }
+    }
+
+    /// Evaluate a term with a frame pushed onto the evaluation trace.
+    fn eval_in_frame(&mut self, frame: Frame, term: &Term<'arena>) -> ArcValue<'arena> {
+        let mut context = EvalContext {
+            rigid_exprs: self.rigid_exprs,
+            flexible_exprs: self.flexible_exprs,
+            trace: Trace::Frame(frame, &self.trace),
+        };
+        context.eval(term)
     }
 
     /// Fully normalise a term by first [evaluating][EvalContext::eval] it into
@@ -268,12 +445,12 @@ impl<'arena, 'env> EvalContext<'arena, 'env> {
         match term {
             Term::RigidVar(var) => match self.rigid_exprs.get_local(*var) {
                 Some(value) => value.clone(),
-                None => panic_any(Error::InvalidRigidVar),
+                None => error(&self.trace, ErrorKind::InvalidRigidVar),
             },
             Term::FlexibleVar(var) => match self.flexible_exprs.get_global(*var) {
                 Some(Some(value)) => value.clone(),
                 Some(None) => Arc::new(Value::flexible_var(*var)),
-                None => panic_any(Error::InvalidFlexibleVar),
+                None => error(&self.trace, ErrorKind::InvalidFlexibleVar),
             },
             Term::FlexibleInsertion(var, rigid_infos) => {
                 let mut head_expr = self.eval(&Term::FlexibleVar(*var));
@@ -288,15 +465,15 @@ impl<'arena, 'env> EvalContext<'arena, 'env> {
                 head_expr
             }
             Term::Ann(expr, _) => self.eval(expr),
-            Term::Let(_, _, def_expr, output_expr) => {
-                let def_expr = self.eval(def_expr);
+            Term::Let(def_name, _, def_expr, output_expr) => {
+                let def_expr = self.eval_in_frame(Frame::LetDef(*def_name), def_expr);
                 self.rigid_exprs.push(def_expr);
                 let output_expr = self.eval(output_expr);
                 self.rigid_exprs.pop();
                 output_expr
             }
 
-            Term::Universe => Arc::new(Value::Universe),
+            Term::Universe(level) => universe_value(*level),
 
             Term::FunType(input_name, input_type, output_type) => Arc::new(Value::FunType(
                 *input_name,
@@ -318,7 +495,9 @@ impl<'arena, 'env> EvalContext<'arena, 'env> {
                 Arc::new(Value::RecordType(labels, types))
             }
             Term::RecordLit(labels, exprs) => {
-                let exprs = exprs.iter().map(|expr| self.eval(expr)).collect();
+                let exprs = Iterator::zip(labels.iter(), exprs.iter())
+                    .map(|(label, expr)| self.eval_in_frame(Frame::RecordField(*label), expr))
+                    .collect();
                 Arc::new(Value::RecordLit(labels, exprs))
             }
             Term::RecordProj(head_expr, label) => {
@@ -342,9 +521,9 @@ impl<'arena, 'env> EvalContext<'arena, 'env> {
                 Arc::new(Value::FormatOverlap(labels, formats))
             }
 
-            Term::Prim(prim) => Arc::new(Value::prim(*prim, [])),
+            Term::Prim(prim) => prim_value(*prim),
 
-            Term::ConstLit(r#const) => Arc::new(Value::ConstLit(*r#const)),
+            Term::ConstLit(r#const) => const_value(*r#const),
             Term::ConstMatch(head_expr, branches, default_expr) => {
                 let head_expr = self.eval(head_expr);
                 let branches = Branches::new(self.rigid_exprs.clone(), branches, *default_expr);
@@ -370,13 +549,36 @@ macro_rules! step {
 macro_rules! const_step {
     ([$($input:ident : $Input:ident),*] => $output:expr) => {
         step!(_, [$($input),*] => match ($($input.as_ref(),)*) {
-            ($(Value::ConstLit(Const::$Input($input, ..)),)*) => Arc::new(Value::ConstLit($output)),
+            ($(Value::ConstLit(Const::$Input($input, ..)),)*) => const_value($output),
             _ => return None,
         })
     };
     ([$($input:ident , $style:ident : $Input:ident),*] => $output:expr) => {
         step!(_, [$($input),*] => match ($($input.as_ref(),)*) {
-            ($(Value::ConstLit(Const::$Input($input, $style)),)*) => Arc::new(Value::ConstLit($output)),
+            ($(Value::ConstLit(Const::$Input($input, $style)),)*) => const_value($output),
+            _ => return None,
+        })
+    };
+}
+
+/// Like `const_step!`, but for partial operations that return an optional
+/// constant of the given element type.
+macro_rules! const_option_step {
+    ([$($input:ident : $Input:ident),*] => $Output:ident, $output:expr) => {
+        step!(_, [$($input),*] => match ($($input.as_ref(),)*) {
+            ($(Value::ConstLit(Const::$Input($input, ..)),)*) => {
+                let elem = Option::map($output, const_value);
+                option_value(&prim_value(Prim::$Output), elem.as_ref())
+            }
+            _ => return None,
+        })
+    };
+    ([$($input:ident , $style:ident : $Input:ident),*] => $Output:ident, $output:expr) => {
+        step!(_, [$($input),*] => match ($($input.as_ref(),)*) {
+            ($(Value::ConstLit(Const::$Input($input, $style)),)*) => {
+                let elem = Option::map($output, const_value);
+                option_value(&prim_value(Prim::$Output), elem.as_ref())
+            }
             _ => return None,
         })
     };
@@ -521,33 +723,135 @@ fn prim_step(prim: Prim) -> Option<PrimStep> {
         Prim::S64Abs => const_step!([x: S64] => Const::S64(i64::abs(*x))),
         Prim::S64UAbs => const_step!([x: S64] => Const::U64(i64::unsigned_abs(*x), UIntStyle::Decimal)),
 
+        Prim::F32Eq => const_step!([x: F32, y: F32] => Const::Bool(x == y)),
+        Prim::F32Neq => const_step!([x: F32, y: F32] => Const::Bool(x != y)),
+        Prim::F32Gt => const_step!([x: F32, y: F32] => Const::Bool(x > y)),
+        Prim::F32Lt => const_step!([x: F32, y: F32] => Const::Bool(x < y)),
+        Prim::F32Gte => const_step!([x: F32, y: F32] => Const::Bool(x >= y)),
+        Prim::F32Lte => const_step!([x: F32, y: F32] => Const::Bool(x <= y)),
+        Prim::F32Neg => const_step!([x: F32] => Const::F32(-x)),
+        Prim::F32Add => const_step!([x: F32, y: F32] => Const::F32(x + y)),
+        Prim::F32Sub => const_step!([x: F32, y: F32] => Const::F32(x - y)),
+        Prim::F32Mul => const_step!([x: F32, y: F32] => Const::F32(x * y)),
+        Prim::F32Div => const_step!([x: F32, y: F32] => Const::F32(x / y)),
+        Prim::F32Abs => const_step!([x: F32] => Const::F32(f32::abs(*x))),
+        Prim::F32IsNan => const_step!([x: F32] => Const::Bool(f32::is_nan(*x))),
+
+        Prim::F64Eq => const_step!([x: F64, y: F64] => Const::Bool(x == y)),
+        Prim::F64Neq => const_step!([x: F64, y: F64] => Const::Bool(x != y)),
+        Prim::F64Gt => const_step!([x: F64, y: F64] => Const::Bool(x > y)),
+        Prim::F64Lt => const_step!([x: F64, y: F64] => Const::Bool(x < y)),
+        Prim::F64Gte => const_step!([x: F64, y: F64] => Const::Bool(x >= y)),
+        Prim::F64Lte => const_step!([x: F64, y: F64] => Const::Bool(x <= y)),
+        Prim::F64Neg => const_step!([x: F64] => Const::F64(-x)),
+        Prim::F64Add => const_step!([x: F64, y: F64] => Const::F64(x + y)),
+        Prim::F64Sub => const_step!([x: F64, y: F64] => Const::F64(x - y)),
+        Prim::F64Mul => const_step!([x: F64, y: F64] => Const::F64(x * y)),
+        Prim::F64Div => const_step!([x: F64, y: F64] => Const::F64(x / y)),
+        Prim::F64Abs => const_step!([x: F64] => Const::F64(f64::abs(*x))),
+        Prim::F64IsNan => const_step!([x: F64] => Const::Bool(f64::is_nan(*x))),
+
+        Prim::U8ToU16 => const_step!([x, style: U8] => Const::U16(u16::from(*x), *style)),
+        Prim::U8ToU32 => const_step!([x, style: U8] => Const::U32(u32::from(*x), *style)),
+        Prim::U8ToU64 => const_step!([x, style: U8] => Const::U64(u64::from(*x), *style)),
+        Prim::U16ToU32 => const_step!([x, style: U16] => Const::U32(u32::from(*x), *style)),
+        Prim::U16ToU64 => const_step!([x, style: U16] => Const::U64(u64::from(*x), *style)),
+        Prim::U32ToU64 => const_step!([x, style: U32] => Const::U64(u64::from(*x), *style)),
+
+        Prim::S8ToS16 => const_step!([x: S8] => Const::S16(i16::from(*x))),
+        Prim::S8ToS32 => const_step!([x: S8] => Const::S32(i32::from(*x))),
+        Prim::S8ToS64 => const_step!([x: S8] => Const::S64(i64::from(*x))),
+        Prim::S16ToS32 => const_step!([x: S16] => Const::S32(i32::from(*x))),
+        Prim::S16ToS64 => const_step!([x: S16] => Const::S64(i64::from(*x))),
+        Prim::S32ToS64 => const_step!([x: S32] => Const::S64(i64::from(*x))),
+
+        Prim::U8ToS16 => const_step!([x: U8] => Const::S16(i16::from(*x))),
+        Prim::U8ToS32 => const_step!([x: U8] => Const::S32(i32::from(*x))),
+        Prim::U8ToS64 => const_step!([x: U8] => Const::S64(i64::from(*x))),
+        Prim::U16ToS32 => const_step!([x: U16] => Const::S32(i32::from(*x))),
+        Prim::U16ToS64 => const_step!([x: U16] => Const::S64(i64::from(*x))),
+        Prim::U32ToS64 => const_step!([x: U32] => Const::S64(i64::from(*x))),
+
+        Prim::U16ToU8 => const_option_step!([x, style: U16] => U8Type, u8::try_from(*x).ok().map(|x| Const::U8(x, *style))),
+        Prim::U32ToU8 => const_option_step!([x, style: U32] => U8Type, u8::try_from(*x).ok().map(|x| Const::U8(x, *style))),
+        Prim::U32ToU16 => const_option_step!([x, style: U32] => U16Type, u16::try_from(*x).ok().map(|x| Const::U16(x, *style))),
+        Prim::U64ToU8 => const_option_step!([x, style: U64] => U8Type, u8::try_from(*x).ok().map(|x| Const::U8(x, *style))),
+        Prim::U64ToU16 => const_option_step!([x, style: U64] => U16Type, u16::try_from(*x).ok().map(|x| Const::U16(x, *style))),
+        Prim::U64ToU32 => const_option_step!([x, style: U64] => U32Type, u32::try_from(*x).ok().map(|x| Const::U32(x, *style))),
+
+        Prim::S16ToS8 => const_option_step!([x: S16] => S8Type, i8::try_from(*x).ok().map(Const::S8)),
+        Prim::S32ToS8 => const_option_step!([x: S32] => S8Type, i8::try_from(*x).ok().map(Const::S8)),
+        Prim::S32ToS16 => const_option_step!([x: S32] => S16Type, i16::try_from(*x).ok().map(Const::S16)),
+        Prim::S64ToS8 => const_option_step!([x: S64] => S8Type, i8::try_from(*x).ok().map(Const::S8)),
+        Prim::S64ToS16 => const_option_step!([x: S64] => S16Type, i16::try_from(*x).ok().map(Const::S16)),
+        Prim::S64ToS32 => const_option_step!([x: S64] => S32Type, i32::try_from(*x).ok().map(Const::S32)),
+
+        Prim::U8ToS8 => const_option_step!([x: U8] => S8Type, i8::try_from(*x).ok().map(Const::S8)),
+        Prim::U16ToS16 => const_option_step!([x: U16] => S16Type, i16::try_from(*x).ok().map(Const::S16)),
+        Prim::U32ToS32 => const_option_step!([x: U32] => S32Type, i32::try_from(*x).ok().map(Const::S32)),
+        Prim::U64ToS64 => const_option_step!([x: U64] => S64Type, i64::try_from(*x).ok().map(Const::S64)),
+        Prim::S8ToU8 => const_option_step!([x: S8] => U8Type, u8::try_from(*x).ok().map(|x| Const::U8(x, UIntStyle::Decimal))),
+        Prim::S16ToU16 => const_option_step!([x: S16] => U16Type, u16::try_from(*x).ok().map(|x| Const::U16(x, UIntStyle::Decimal))),
+        Prim::S32ToU32 => const_option_step!([x: S32] => U32Type, u32::try_from(*x).ok().map(|x| Const::U32(x, UIntStyle::Decimal))),
+        Prim::S64ToU64 => const_option_step!([x: S64] => U64Type, u64::try_from(*x).ok().map(|x| Const::U64(x, UIntStyle::Decimal))),
+
+        Prim::U8ToF32 => const_step!([x: U8] => Const::F32(f32::from(*x))),
+        Prim::U16ToF32 => const_step!([x: U16] => Const::F32(f32::from(*x))),
+        Prim::S8ToF32 => const_step!([x: S8] => Const::F32(f32::from(*x))),
+        Prim::S16ToF32 => const_step!([x: S16] => Const::F32(f32::from(*x))),
+        Prim::U8ToF64 => const_step!([x: U8] => Const::F64(f64::from(*x))),
+        Prim::U16ToF64 => const_step!([x: U16] => Const::F64(f64::from(*x))),
+        Prim::U32ToF64 => const_step!([x: U32] => Const::F64(f64::from(*x))),
+        Prim::S8ToF64 => const_step!([x: S8] => Const::F64(f64::from(*x))),
+        Prim::S16ToF64 => const_step!([x: S16] => Const::F64(f64::from(*x))),
+        Prim::S32ToF64 => const_step!([x: S32] => Const::F64(f64::from(*x))),
+        Prim::F32ToF64 => const_step!([x: F32] => Const::F64(f64::from(*x))),
+
         Prim::OptionFold => step!(context, [_, _, on_none, on_some, option] => {
             match option.match_prim_spine()? {
-                (Prim::OptionSome, [Elim::FunApp(value)]) => {
+                (Prim::OptionSome, [_, Elim::FunApp(value)]) => {
                     context.fun_app(on_some.clone(), value.clone())
                 },
-                (Prim::OptionNone, []) => on_none.clone(),
+                (Prim::OptionNone, [_]) => on_none.clone(),
                 _ => return None,
             }
         }),
 
+        Prim::Array8Len | Prim::Array16Len | Prim::Array32Len | Prim::Array64Len => {
+            step!(_, [len, _, _] => len.clone())
+        }
+        Prim::ArrayLen => step!(_, [_, array] => match array.as_ref() {
+            Value::ArrayLit(elems) => const_value(Const::U64(elems.len() as u64, UIntStyle::Decimal)),
+            _ => return None,
+        }),
+        Prim::Array8Index | Prim::Array16Index | Prim::Array32Index | Prim::Array64Index => {
+            step!(_, [_, elem_type, index, array] => array_index(elem_type, index, array)?)
+        }
+        Prim::ArrayIndex => step!(_, [elem_type, index, array] => array_index(elem_type, index, array)?),
+        Prim::Array8Fold | Prim::Array16Fold | Prim::Array32Fold | Prim::Array64Fold => {
+            step!(context, [_, _, _, init, f, array] => array_fold(context, init, f, array)?)
+        }
+        Prim::ArrayFold => step!(context, [_, _, init, f, array] => array_fold(context, init, f, array)?),
+        Prim::Array8Map | Prim::Array16Map | Prim::Array32Map | Prim::Array64Map => {
+            step!(context, [_, _, _, f, array] => array_map(context, f, array)?)
+        }
+        Prim::ArrayMap => step!(context, [_, _, f, array] => array_map(context, f, array)?),
+        Prim::Array8All | Prim::Array16All | Prim::Array32All | Prim::Array64All => {
+            step!(context, [_, _, pred, array] => array_all(context, pred, array)?)
+        }
+        Prim::ArrayAll => step!(context, [_, pred, array] => array_all(context, pred, array)?),
+        Prim::Array8Any | Prim::Array16Any | Prim::Array32Any | Prim::Array64Any => {
+            step!(context, [_, _, pred, array] => array_any(context, pred, array)?)
+        }
+        Prim::ArrayAny => step!(context, [_, pred, array] => array_any(context, pred, array)?),
         Prim::Array8Find | Prim::Array16Find | Prim::Array32Find | Prim::Array64Find => {
-            step!(context, [_, _, pred, array] => match array.as_ref() {
-                Value::ArrayLit(elems) => {
-                    for elem in elems {
-                        match context.fun_app(pred.clone(), elem.clone()).as_ref() {
-                            Value::ConstLit(Const::Bool(true)) => {
-                                return Some(Arc::new(Value::prim(Prim::OptionSome, [elem.clone()])))
-                            },
-                            Value::ConstLit(Const::Bool(false)) => {}
-                            _ => return None,
-                        }
-                    }
-                    Arc::new(Value::prim(Prim::OptionNone, []))
-                }
-                _ => return None,
-            })
+            step!(context, [_, elem_type, pred, array] => array_find(context, elem_type, pred, array)?)
+        }
+        Prim::ArrayFind => step!(context, [elem_type, pred, array] => array_find(context, elem_type, pred, array)?),
+        Prim::Array8BinarySearch | Prim::Array16BinarySearch | Prim::Array32BinarySearch | Prim::Array64BinarySearch => {
+            step!(context, [_, elem_type, compare, array] => array_binary_search(context, elem_type, compare, array)?)
         }
+        Prim::ArrayBinarySearch => step!(context, [elem_type, compare, array] => array_binary_search(context, elem_type, compare, array)?),
 
         Prim::PosAddU8 => const_step!([x: Pos, y: U8] => Const::Pos(u64::checked_add(*x, u64::from(*y))?)),
         Prim::PosAddU16 => const_step!([x: Pos, y: U16] => Const::Pos(u64::checked_add(*x, u64::from(*y))?)),
@@ -558,19 +862,220 @@ fn prim_step(prim: Prim) -> Option<PrimStep> {
     }
 }
 
+/// Look up an element of an array literal, returning `some A elem` if the
+/// index is in bounds, or `none A` otherwise.
+fn array_index<'arena>(
+    elem_type: &ArcValue<'arena>,
+    index: &ArcValue<'arena>,
+    array: &ArcValue<'arena>,
+) -> Option<ArcValue<'arena>> {
+    let index = match index.as_ref() {
+        Value::ConstLit(Const::U8(index, _)) => u64::from(*index),
+        Value::ConstLit(Const::U16(index, _)) => u64::from(*index),
+        Value::ConstLit(Const::U32(index, _)) => u64::from(*index),
+        Value::ConstLit(Const::U64(index, _)) => *index,
+        _ => return None,
+    };
+    match array.as_ref() {
+        Value::ArrayLit(elems) => {
+            let elem = usize::try_from(index)
+                .ok()
+                .and_then(|index| elems.get(index));
+            Some(option_value(elem_type, elem))
+        }
+        _ => None,
+    }
+}
+
+fn array_fold<'arena>(
+    context: &ElimContext<'arena, '_>,
+    init: &ArcValue<'arena>,
+    f: &ArcValue<'arena>,
+    array: &ArcValue<'arena>,
+) -> Option<ArcValue<'arena>> {
+    match array.as_ref() {
+        Value::ArrayLit(elems) => Some(elems.iter().fold(init.clone(), |acc, elem| {
+            let f = context.fun_app(f.clone(), acc);
+            context.fun_app(f, elem.clone())
+        })),
+        _ => None,
+    }
+}
+
+fn array_map<'arena>(
+    context: &ElimContext<'arena, '_>,
+    f: &ArcValue<'arena>,
+    array: &ArcValue<'arena>,
+) -> Option<ArcValue<'arena>> {
+    match array.as_ref() {
+        Value::ArrayLit(elems) => Some(Arc::new(Value::ArrayLit(
+            (elems.iter())
+                .map(|elem| context.fun_app(f.clone(), elem.clone()))
+                .collect(),
+        ))),
+        _ => None,
+    }
+}
+
+fn array_all<'arena>(
+    context: &ElimContext<'arena, '_>,
+    pred: &ArcValue<'arena>,
+    array: &ArcValue<'arena>,
+) -> Option<ArcValue<'arena>> {
+    match array.as_ref() {
+        Value::ArrayLit(elems) => {
+            for elem in elems {
+                match context.fun_app(pred.clone(), elem.clone()).as_ref() {
+                    Value::ConstLit(Const::Bool(true)) => {}
+                    Value::ConstLit(Const::Bool(false)) => return Some(bool_value(false)),
+                    _ => return None,
+                }
+            }
+            Some(bool_value(true))
+        }
+        _ => None,
+    }
+}
+
+fn array_any<'arena>(
+    context: &ElimContext<'arena, '_>,
+    pred: &ArcValue<'arena>,
+    array: &ArcValue<'arena>,
+) -> Option<ArcValue<'arena>> {
+    match array.as_ref() {
+        Value::ArrayLit(elems) => {
+            for elem in elems {
+                match context.fun_app(pred.clone(), elem.clone()).as_ref() {
+                    Value::ConstLit(Const::Bool(true)) => return Some(bool_value(true)),
+                    Value::ConstLit(Const::Bool(false)) => {}
+                    _ => return None,
+                }
+            }
+            Some(bool_value(false))
+        }
+        _ => None,
+    }
+}
+
+fn array_find<'arena>(
+    context: &ElimContext<'arena, '_>,
+    elem_type: &ArcValue<'arena>,
+    pred: &ArcValue<'arena>,
+    array: &ArcValue<'arena>,
+) -> Option<ArcValue<'arena>> {
+    match array.as_ref() {
+        Value::ArrayLit(elems) => {
+            for elem in elems {
+                match context.fun_app(pred.clone(), elem.clone()).as_ref() {
+                    Value::ConstLit(Const::Bool(true)) => {
+                        return Some(option_value(elem_type, Some(elem)));
+                    }
+                    Value::ConstLit(Const::Bool(false)) => {}
+                    _ => return None,
+                }
+            }
+            Some(option_value(elem_type, None))
+        }
+        _ => None,
+    }
+}
+
+/// Search a sorted array literal using a comparison function, which should
+/// return a negative number if an element is ordered before the one being
+/// searched for, zero if it matches, and a positive number if it is ordered
+/// after it.
+fn array_binary_search<'arena>(
+    context: &ElimContext<'arena, '_>,
+    elem_type: &ArcValue<'arena>,
+    compare: &ArcValue<'arena>,
+    array: &ArcValue<'arena>,
+) -> Option<ArcValue<'arena>> {
+    match array.as_ref() {
+        Value::ArrayLit(elems) => {
+            let (mut start, mut end) = (0, elems.len());
+            while start < end {
+                let mid = start + (end - start) / 2;
+                match context
+                    .fun_app(compare.clone(), elems[mid].clone())
+                    .as_ref()
+                {
+                    Value::ConstLit(Const::S8(ordering)) => match ordering.cmp(&0) {
+                        Ordering::Less => start = mid + 1,
+                        Ordering::Equal => return Some(option_value(elem_type, Some(&elems[mid]))),
+                        Ordering::Greater => end = mid,
+                    },
+                    _ => return None,
+                }
+            }
+            Some(option_value(elem_type, None))
+        }
+        _ => None,
+    }
+}
+
+fn bool_value<'arena>(value: bool) -> ArcValue<'arena> {
+    const_value(Const::Bool(value))
+}
+
+fn option_value<'arena>(
+    elem_type: &ArcValue<'arena>,
+    elem: Option<&ArcValue<'arena>>,
+) -> ArcValue<'arena> {
+    match elem {
+        Some(elem) => Arc::new(Value::prim(
+            Prim::OptionSome,
+            [elem_type.clone(), elem.clone()],
+        )),
+        None => Arc::new(Value::prim(Prim::OptionNone, [elem_type.clone()])),
+    }
+}
+
 /// Elimination context.
 ///
 /// Contains enough state to run computations, but does not contain a rigid
 /// environment that would be needed for full evaluation.
 pub struct ElimContext<'arena, 'env> {
     flexible_exprs: &'env SliceEnv<Option<ArcValue<'arena>>>,
+    trace: Trace<'env>,
 }
 
 impl<'arena, 'env> ElimContext<'arena, 'env> {
     pub fn new(
         flexible_exprs: &'env SliceEnv<Option<ArcValue<'arena>>>,
     ) -> ElimContext<'arena, 'env> {
-        ElimContext { flexible_exprs }
+        ElimContext {
+            flexible_exprs,
+            trace: Trace::Root(&[]),
+        }
+    }
+
+    /// Include frames that were pushed outside of the evaluator in the traces
+    /// of any errors, starting with the outermost frame.
+    pub fn with_frames(self, frames: &'env [Frame]) -> ElimContext<'arena, 'env> {
+        ElimContext {
+            flexible_exprs: self.flexible_exprs,
+            trace: Trace::Root(frames),
+        }
+    }
+
+    /// Run a computation with a frame pushed onto the evaluation trace.
+    fn in_frame<T>(&self, frame: Frame, f: impl FnOnce(&ElimContext<'arena, '_>) -> T) -> T {
+        f(&ElimContext {
+            flexible_exprs: self.flexible_exprs,
+            trace: Trace::Frame(frame, &self.trace),
+        })
+    }
+
+    /// Construct an evaluation context that continues the current trace.
+    fn eval_context<'this>(
+        &'this self,
+        rigid_exprs: &'this mut SharedEnv<ArcValue<'arena>>,
+    ) -> EvalContext<'arena, 'this> {
+        EvalContext {
+            rigid_exprs,
+            flexible_exprs: self.flexible_exprs,
+            trace: self.trace,
+        }
     }
 
     /// Bring a value up-to-date with any new unification solutions that
@@ -586,7 +1091,7 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
                 // There's no solution for this flexible variable yet, meaning
                 // that we've forced the value as much as possible for now
                 Some(None) => break,
-                None => panic_any(Error::InvalidFlexibleVar),
+                None => error(&self.trace, ErrorKind::InvalidFlexibleVar),
             }
         }
         forced_value
@@ -600,7 +1105,7 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
     ) -> ArcValue<'arena> {
         let mut rigid_exprs = closure.rigid_exprs.clone();
         rigid_exprs.push(value);
-        EvalContext::new(&mut rigid_exprs, self.flexible_exprs).eval(closure.term)
+        self.eval_context(&mut rigid_exprs).eval(closure.term)
     }
 
     /// Split a telescope into the first value, and a continuation that returns
@@ -613,7 +1118,7 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
         impl FnOnce(ArcValue<'arena>) -> Telescope<'arena>,
     )> {
         let (term, terms) = telescope.terms.split_first()?;
-        let mut context = EvalContext::new(&mut telescope.rigid_exprs, self.flexible_exprs);
+        let mut context = self.eval_context(&mut telescope.rigid_exprs);
         let value = match telescope.apply_repr {
             true => context.elim_context().format_repr(&context.eval(term)),
             false => context.eval(term),
@@ -633,7 +1138,7 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
         match branches.pattern_branches.split_first() {
             Some(((r#const, output_expr), pattern_branches)) => {
                 branches.pattern_branches = pattern_branches;
-                let mut context = EvalContext::new(&mut branches.rigid_exprs, self.flexible_exprs);
+                let mut context = self.eval_context(&mut branches.rigid_exprs);
                 SplitBranches::Branch((*r#const, context.eval(output_expr)), branches)
             }
             None => match branches.default_expr {
@@ -656,19 +1161,24 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
     ) -> ArcValue<'arena> {
         match Arc::make_mut(&mut head_expr) {
             // Beta-reduction
-            Value::FunLit(_, output_expr) => self.apply_closure(output_expr, input_expr),
+            Value::FunLit(input_name, output_expr) => self
+                .in_frame(Frame::FunApp(*input_name), |context| {
+                    context.apply_closure(output_expr, input_expr)
+                }),
             // The computation is stuck, preventing further reduction
             Value::Stuck(head, spine) => {
                 spine.push(Elim::FunApp(input_expr));
 
                 match head {
                     Head::Prim(prim) => prim_step(*prim)
-                        .and_then(|step| step(self, spine))
+                        .and_then(|step| {
+                            self.in_frame(Frame::PrimApp(*prim), |context| step(context, spine))
+                        })
                         .unwrap_or(head_expr),
                     _ => head_expr,
                 }
             }
-            _ => panic_any(Error::InvalidFunctionApp),
+            _ => error(&self.trace, ErrorKind::InvalidFunctionApp),
         }
     }
 
@@ -686,13 +1196,13 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
             Value::RecordLit(labels, exprs) => (labels.iter())
                 .position(|current_label| *current_label == label)
                 .and_then(|expr_index| exprs.get(expr_index).cloned())
-                .unwrap_or_else(|| panic_any(Error::InvalidRecordProj)),
+                .unwrap_or_else(|| error(&self.trace, ErrorKind::InvalidRecordProj)),
             // The computation is stuck, preventing further reduction
             Value::Stuck(_, spine) => {
                 spine.push(Elim::RecordProj(label));
                 head_expr
             }
-            _ => panic_any(Error::InvalidRecordProj),
+            _ => error(&self.trace, ErrorKind::InvalidRecordProj),
         }
     }
 
@@ -710,7 +1220,8 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
                 // Try each branch
                 for (branch_const, output_expr) in branches.pattern_branches {
                     if r#const == branch_const {
-                        return EvalContext::new(&mut branches.rigid_exprs, self.flexible_exprs)
+                        return self
+                            .eval_context(&mut branches.rigid_exprs)
                             .eval(output_expr);
                     }
                 }
@@ -718,10 +1229,8 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
                 let mut rigid_exprs = branches.rigid_exprs.clone();
                 rigid_exprs.push(head_expr);
                 match branches.default_expr {
-                    Some(default_expr) => {
-                        EvalContext::new(&mut rigid_exprs, self.flexible_exprs).eval(default_expr)
-                    }
-                    None => panic_any(Error::MissingConstDefault),
+                    Some(default_expr) => self.eval_context(&mut rigid_exprs).eval(default_expr),
+                    None => error(&self.trace, ErrorKind::MissingConstDefault),
                 }
             }
             // The computation is stuck, preventing further reduction
@@ -729,7 +1238,7 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
                 spine.push(Elim::ConstMatch(branches));
                 head_expr
             }
-            _ => panic_any(Error::InvalidConstMatch),
+            _ => error(&self.trace, ErrorKind::InvalidConstMatch),
         }
     }
 
@@ -749,24 +1258,40 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
                 Arc::new(Value::RecordType(labels, formats.clone().apply_repr()))
             }
             Value::Stuck(Head::Prim(prim), spine) => match (prim, &spine[..]) {
-                (Prim::FormatU8, []) => Arc::new(Value::prim(Prim::U8Type, [])),
-                (Prim::FormatU16Be, []) => Arc::new(Value::prim(Prim::U16Type, [])),
-                (Prim::FormatU16Le, []) => Arc::new(Value::prim(Prim::U16Type, [])),
-                (Prim::FormatU32Be, []) => Arc::new(Value::prim(Prim::U32Type, [])),
-                (Prim::FormatU32Le, []) => Arc::new(Value::prim(Prim::U32Type, [])),
-                (Prim::FormatU64Be, []) => Arc::new(Value::prim(Prim::U64Type, [])),
-                (Prim::FormatU64Le, []) => Arc::new(Value::prim(Prim::U64Type, [])),
-                (Prim::FormatS8, []) => Arc::new(Value::prim(Prim::S8Type, [])),
-                (Prim::FormatS16Be, []) => Arc::new(Value::prim(Prim::S16Type, [])),
-                (Prim::FormatS16Le, []) => Arc::new(Value::prim(Prim::S16Type, [])),
-                (Prim::FormatS32Be, []) => Arc::new(Value::prim(Prim::S32Type, [])),
-                (Prim::FormatS32Le, []) => Arc::new(Value::prim(Prim::S32Type, [])),
-                (Prim::FormatS64Be, []) => Arc::new(Value::prim(Prim::S64Type, [])),
-                (Prim::FormatS64Le, []) => Arc::new(Value::prim(Prim::S64Type, [])),
-                (Prim::FormatF32Be, []) => Arc::new(Value::prim(Prim::F32Type, [])),
-                (Prim::FormatF32Le, []) => Arc::new(Value::prim(Prim::F32Type, [])),
-                (Prim::FormatF64Be, []) => Arc::new(Value::prim(Prim::F64Type, [])),
-                (Prim::FormatF64Le, []) => Arc::new(Value::prim(Prim::F64Type, [])),
+                (Prim::FormatU8, []) => prim_value(Prim::U8Type),
+                (Prim::FormatU16Be, []) => prim_value(Prim::U16Type),
+                (Prim::FormatU16Le, []) => prim_value(Prim::U16Type),
+                (Prim::FormatU32Be, []) => prim_value(Prim::U32Type),
+                (Prim::FormatU32Le, []) => prim_value(Prim::U32Type),
+                (Prim::FormatU64Be, []) => prim_value(Prim::U64Type),
+                (Prim::FormatU64Le, []) => prim_value(Prim::U64Type),
+                (Prim::FormatS8, []) => prim_value(Prim::S8Type),
+                (Prim::FormatS16Be, []) => prim_value(Prim::S16Type),
+                (Prim::FormatS16Le, []) => prim_value(Prim::S16Type),
+                (Prim::FormatS32Be, []) => prim_value(Prim::S32Type),
+                (Prim::FormatS32Le, []) => prim_value(Prim::S32Type),
+                (Prim::FormatS64Be, []) => prim_value(Prim::S64Type),
+                (Prim::FormatS64Le, []) => prim_value(Prim::S64Type),
+                (Prim::FormatF32Be, []) => prim_value(Prim::F32Type),
+                (Prim::FormatF32Le, []) => prim_value(Prim::F32Type),
+                (Prim::FormatF64Be, []) => prim_value(Prim::F64Type),
+                (Prim::FormatF64Le, []) => prim_value(Prim::F64Type),
+                (Prim::FormatCString, []) => prim_value(Prim::StringType),
+                (Prim::FormatUtf8Len8, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatUtf8Len16, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatUtf8Len32, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatUtf8Len64, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatUtf16BeLen8, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatUtf16BeLen16, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatUtf16BeLen32, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatUtf16BeLen64, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatAsciiLen8, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatAsciiLen16, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatAsciiLen32, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatAsciiLen64, [_]) => prim_value(Prim::StringType),
+                (Prim::FormatULeb128, []) => prim_value(Prim::U64Type),
+                (Prim::FormatSLeb128, []) => prim_value(Prim::S64Type),
+                (Prim::FormatBase128Be, []) => prim_value(Prim::U64Type),
                 (Prim::FormatArray8, [Elim::FunApp(len), Elim::FunApp(elem)]) => Arc::new(
                     Value::prim(Prim::Array8Type, [len.clone(), self.format_repr(elem)]),
                 ),
@@ -788,15 +1313,21 @@ impl<'arena, 'env> ElimContext<'arena, 'env> {
                 (Prim::FormatDeref, [Elim::FunApp(elem), Elim::FunApp(_)]) => {
                     self.format_repr(elem)
                 }
-                (Prim::FormatStreamPos, []) => Arc::new(Value::prim(Prim::PosType, [])),
+                (Prim::FormatMap, [_, Elim::FunApp(elem), _]) => elem.clone(),
+                (Prim::FormatStreamPos, []) => prim_value(Prim::PosType),
+                (Prim::FormatEndOfInput, []) => Arc::new(Value::RecordType(
+                    &[],
+                    Telescope::new(SharedEnv::new(), &[]),
+                )),
+                (Prim::FormatRemainingBytes, []) => prim_value(Prim::U64Type),
                 (Prim::FormatSucceed, [Elim::FunApp(elem), _]) => elem.clone(),
-                (Prim::FormatFail, []) => Arc::new(Value::prim(Prim::VoidType, [])),
+                (Prim::FormatFail, []) => prim_value(Prim::VoidType),
                 (Prim::FormatUnwrap, [Elim::FunApp(elem), _]) => elem.clone(),
-                (Prim::ReportedError, []) => Arc::new(Value::prim(Prim::ReportedError, [])),
+                (Prim::ReportedError, []) => prim_value(Prim::ReportedError),
                 _ => Arc::new(Value::prim(Prim::FormatRepr, [format.clone()])),
             },
             Value::Stuck(_, _) => Arc::new(Value::prim(Prim::FormatRepr, [format.clone()])),
-            _ => panic_any(Error::InvalidFormatRepr),
+            _ => error(&self.trace, ErrorKind::InvalidFormatRepr),
         }
     }
 }
@@ -867,7 +1398,10 @@ impl<'in_arena, 'out_arena, 'env> QuoteContext<'in_arena, 'out_arena, 'env> {
                         let default_expr = loop {
                             match self.elim_context().split_branches(branches) {
                                 SplitBranches::Branch((r#const, output_expr), next_branches) => {
-                                    pattern_branches.push((r#const, self.quote(&output_expr)));
+                                    pattern_branches.push((
+                                        self.quote_const(r#const),
+                                        self.quote(&output_expr),
+                                    ));
                                     branches = next_branches;
                                 }
                                 SplitBranches::Default(default_expr) => {
@@ -886,7 +1420,7 @@ impl<'in_arena, 'out_arena, 'env> QuoteContext<'in_arena, 'out_arena, 'env> {
                 })
             }
 
-            Value::Universe => Term::Universe,
+            Value::Universe(level) => Term::Universe(*level),
 
             Value::FunType(input_name, input_type, output_type) => {
                 let input_type = self.quote(input_type);
@@ -937,7 +1471,27 @@ impl<'in_arena, 'out_arena, 'env> QuoteContext<'in_arena, 'out_arena, 'env> {
                 Term::FormatOverlap(labels, formats)
             }
 
-            Value::ConstLit(r#const) => Term::ConstLit(*r#const),
+            Value::ConstLit(r#const) => Term::ConstLit(self.quote_const(*r#const)),
+        }
+    }
+
+    /// Quote a [constant][Const], copying strings into the output arena.
+    fn quote_const(&self, r#const: Const<'in_arena>) -> Const<'out_arena> {
+        match r#const {
+            Const::Bool(b) => Const::Bool(b),
+            Const::U8(n, style) => Const::U8(n, style),
+            Const::U16(n, style) => Const::U16(n, style),
+            Const::U32(n, style) => Const::U32(n, style),
+            Const::U64(n, style) => Const::U64(n, style),
+            Const::S8(n) => Const::S8(n),
+            Const::S16(n) => Const::S16(n),
+            Const::S32(n) => Const::S32(n),
+            Const::S64(n) => Const::S64(n),
+            Const::F32(n) => Const::F32(n),
+            Const::F64(n) => Const::F64(n),
+            Const::Pos(pos) => Const::Pos(pos),
+            Const::Ref(pos) => Const::Ref(pos),
+            Const::String(string) => Const::String(alloc::str_to_scope(self.scope, string)),
         }
     }
 
@@ -1041,7 +1595,7 @@ impl<'arena, 'env> ConversionContext<'arena, 'env> {
                         }
                     })
             }
-            (Value::Universe, Value::Universe) => true,
+            (Value::Universe(level0), Value::Universe(level1)) => level0 == level1,
 
             (
                 Value::FunType(_, input_type0, output_type0),
@@ -1081,6 +1635,7 @@ impl<'arena, 'env> ConversionContext<'arena, 'env> {
                 labels0 == labels1 && self.is_equal_telescopes(formats0, formats1)
             }
 
+            // NOTE: Floating point constants are compared using total equality
             (Value::ConstLit(const0), Value::ConstLit(const1)) => const0 == const1,
 
             (_, _) => false,
@@ -1199,3 +1754,102 @@ impl<'arena, 'env> ConversionContext<'arena, 'env> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::{LocalVar, UniqueEnv};
+    use crate::StringInterner;
+
+    #[test]
+    fn error_trace() {
+        let mut interner = StringInterner::new();
+        let (x, y) = (interner.get_or_intern("x"), interner.get_or_intern("y"));
+
+        // let x = { y = true false }; x
+        let labels = [y];
+        let exprs = [Term::FunApp(
+            &Term::ConstLit(Const::Bool(true)),
+            &Term::ConstLit(Const::Bool(false)),
+        )];
+        let term = Term::Let(
+            Some(x),
+            &Term::Universe(0),
+            &Term::RecordLit(&labels, &exprs),
+            &Term::RigidVar(LocalVar::last()),
+        );
+
+        let result = std::panic::catch_unwind(|| {
+            let flexible_exprs = UniqueEnv::new();
+            EvalContext::new(&mut SharedEnv::new(), &flexible_exprs).eval(&term);
+        });
+        let error = result.unwrap_err().downcast::<Error>().unwrap();
+
+        assert!(matches!(error.kind, ErrorKind::InvalidFunctionApp));
+        assert!(matches!(
+            error.trace[..],
+            [Frame::RecordField(label), Frame::LetDef(Some(name))] if label == y && name == x,
+        ));
+    }
+
+    #[test]
+    fn error_trace_fun_app() {
+        let mut interner = StringInterner::new();
+        let x = interner.get_or_intern("x");
+
+        // (fun x => true false) 1
+        let term = Term::FunApp(
+            &Term::FunLit(
+                Some(x),
+                &Term::FunApp(
+                    &Term::ConstLit(Const::Bool(true)),
+                    &Term::ConstLit(Const::Bool(false)),
+                ),
+            ),
+            &Term::ConstLit(Const::U8(1, UIntStyle::Decimal)),
+        );
+
+        let result = std::panic::catch_unwind(|| {
+            let flexible_exprs = UniqueEnv::new();
+            EvalContext::new(&mut SharedEnv::new(), &flexible_exprs).eval(&term);
+        });
+        let error = result.unwrap_err().downcast::<Error>().unwrap();
+
+        assert!(matches!(error.kind, ErrorKind::InvalidFunctionApp));
+        assert!(matches!(error.trace[..], [Frame::FunApp(Some(name))] if name == x));
+    }
+
+    #[test]
+    fn interned_values() {
+        let is_shared = |v0: ArcValue<'_>, v1: ArcValue<'_>| Arc::ptr_eq(&v0, &v1);
+
+        assert!(is_shared(universe_value(0), universe_value(0)));
+        assert!(is_shared(
+            prim_value(Prim::U8Type),
+            prim_value(Prim::U8Type)
+        ));
+        assert!(!is_shared(
+            prim_value(Prim::U8Type),
+            prim_value(Prim::U16Type)
+        ));
+
+        let u8_const = |num, style| const_value(Const::U8(num, style));
+        assert!(is_shared(
+            u8_const(255, UIntStyle::Decimal),
+            u8_const(255, UIntStyle::Decimal)
+        ));
+        assert!(!is_shared(
+            u8_const(1, UIntStyle::Hexadecimal),
+            u8_const(1, UIntStyle::Hexadecimal)
+        ));
+
+        let u64_const = |num| const_value(Const::U64(num, UIntStyle::Decimal));
+        assert!(is_shared(u64_const(42), u64_const(42)));
+        assert!(!is_shared(u64_const(256), u64_const(256)));
+
+        // Interned primitives should be the same as those constructed directly
+        for prim in Prim::ALL {
+            assert!(matches!(prim_value(*prim).match_prim_spine(), Some((p, [])) if p == *prim));
+        }
+    }
+}
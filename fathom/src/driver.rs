@@ -1,15 +1,31 @@
-use codespan_reporting::diagnostic::{Diagnostic, Severity};
-use codespan_reporting::files::SimpleFiles;
-use codespan_reporting::term::termcolor::{BufferedStandardStream, ColorChoice, WriteColor};
-use std::cell::RefCell;
-use std::io::Read;
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use codespan_reporting::files::{Files, SimpleFiles};
+use codespan_reporting::term::termcolor::{
+    BufferedStandardStream, ColorChoice, NoColor, WriteColor,
+};
+use itertools::Itertools;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::io::{self, Read, SeekFrom, Write};
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::core::binary;
+use crate::core::semantics::{ArcValue, Value};
+use crate::core::{binary, semantics, Const};
 use crate::source::{ByteRange, FileId};
 use crate::surface::{self, elaboration};
 use crate::StringInterner;
 
+const BUG_REPORT_URL: &str = concat!(env!("CARGO_PKG_REPOSITORY"), "/issues/new");
+
+thread_local! {
+    /// Set while evaluation errors are being caught by
+    /// [`Driver::catch_eval_errors`], which reports them with a trace.
+    static CATCHING_EVAL_ERRORS: Cell<bool> = Cell::new(false);
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum Status {
     Ok,
@@ -25,6 +41,40 @@ impl Status {
     }
 }
 
+/// How binary data should be emitted by [`Driver::read_format`].
+#[derive(Debug, Copy, Clone)]
+pub enum DataOutput {
+    /// Emit the parsed references, in order of their position.
+    Refs,
+    /// Emit a hexdump of the binary data, annotated with the fields that each
+    /// range of bytes was parsed as.
+    Hexdump,
+    /// Emit a table of the regions of the binary data that were either never
+    /// read, or read more than once.
+    Coverage,
+}
+
+/// How diagnostics should be emitted by the [`Driver`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Render diagnostics for display in a terminal.
+    Human,
+    /// Serialise diagnostics as JSON objects, one per line.
+    Json,
+}
+
+impl FromStr for MessageFormat {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<MessageFormat, String> {
+        match src {
+            "human" => Ok(MessageFormat::Human),
+            "json" => Ok(MessageFormat::Json),
+            _ => Err(format!("unknown message format `{}`", src)),
+        }
+    }
+}
+
 pub struct Driver<'surface, 'core> {
     files: SimpleFiles<String, String>,
     interner: RefCell<StringInterner>,
@@ -32,11 +82,15 @@ pub struct Driver<'surface, 'core> {
     core_scope: scoped_arena::Scope<'core>,
 
     allow_errors: bool,
+    message_format: MessageFormat,
     codespan_config: codespan_reporting::term::Config,
     diagnostic_writer: RefCell<Box<dyn WriteColor>>,
 
     emit_width: usize,
     emit_writer: RefCell<Box<dyn WriteColor>>,
+
+    data_output: DataOutput,
+    binary_limits: binary::Limits,
 }
 
 impl<'surface, 'core> Driver<'surface, 'core> {
@@ -48,6 +102,7 @@ impl<'surface, 'core> Driver<'surface, 'core> {
             files: SimpleFiles::new(),
 
             allow_errors: false,
+            message_format: MessageFormat::Human,
             codespan_config: codespan_reporting::term::Config::default(),
             diagnostic_writer: RefCell::new(Box::new(BufferedStandardStream::stderr(
                 if atty::is(atty::Stream::Stderr) {
@@ -65,28 +120,36 @@ impl<'surface, 'core> Driver<'surface, 'core> {
                     ColorChoice::Never
                 },
             ))),
+
+            data_output: DataOutput::Refs,
+            binary_limits: binary::Limits::default(),
         }
     }
 
     /// Setup a global panic hook
     pub fn install_panic_hook(&self) {
-        use crate::core::semantics;
-
-        const BUG_REPORT_URL: &str = concat!(env!("CARGO_PKG_REPOSITORY"), "/issues/new");
-
-        // Use the currently set codespan configuration
+        // Use the currently set message format and codespan configuration
+        let message_format = self.message_format;
         let term_config = self.codespan_config.clone();
-        // Fetch the default hook (which prints the panic message and an optional backtrace)
+        // Fetch the default hook (which prints the panic message and an optional
+        // backtrace). This is skipped when emitting JSON, so that standard error
+        // only contains diagnostics.
         let default_hook = std::panic::take_hook();
 
         std::panic::set_hook(Box::new(move |info| {
+            // Evaluation errors that are being caught are reported by the
+            // driver, which is able to include a trace of where they occurred.
+            if info.payload().is::<semantics::Error>() && CATCHING_EVAL_ERRORS.with(Cell::get) {
+                return;
+            }
+
             let location = info.location();
-            let message = if let Some(error) = info.payload().downcast_ref::<semantics::Error>() {
-                error.description()
-            } else if let Some(message) = info.payload().downcast_ref::<String>() {
+            let message = if let Some(message) = info.payload().downcast_ref::<String>() {
                 message.as_str()
             } else if let Some(message) = info.payload().downcast_ref::<&str>() {
                 message
+            } else if let Some(error) = info.payload().downcast_ref::<semantics::Error>() {
+                error.description()
             } else {
                 "unknown panic type"
             };
@@ -100,7 +163,6 @@ impl<'surface, 'core> Driver<'surface, 'core> {
                     },
                     format!("please file a bug report at: {}", BUG_REPORT_URL),
                     // TODO: print rust backtrace
-                    // TODO: print fathom backtrace
                 ]);
 
             let mut writer = BufferedStandardStream::stderr(if atty::is(atty::Stream::Stderr) {
@@ -110,10 +172,25 @@ impl<'surface, 'core> Driver<'surface, 'core> {
             });
             let dummy_files = SimpleFiles::<String, String>::new();
 
-            default_hook(info);
-            eprintln!();
-            codespan_reporting::term::emit(&mut writer, &term_config, &dummy_files, &diagnostic)
-                .unwrap();
+            match message_format {
+                MessageFormat::Human => {
+                    default_hook(info);
+                    eprintln!();
+                    codespan_reporting::term::emit(
+                        &mut writer,
+                        &term_config,
+                        &dummy_files,
+                        &diagnostic,
+                    )
+                    .unwrap();
+                }
+                MessageFormat::Json => {
+                    let diagnostic = JsonDiagnostic::new(&dummy_files, &term_config, &diagnostic);
+                    serde_json::to_writer(&mut writer, &diagnostic).unwrap();
+                    writeln!(writer).unwrap();
+                    writer.flush().unwrap();
+                }
+            }
         }));
     }
 
@@ -122,6 +199,11 @@ impl<'surface, 'core> Driver<'surface, 'core> {
         self.allow_errors = allow_errors;
     }
 
+    /// Set how diagnostics should be emitted
+    pub fn set_message_format(&mut self, message_format: MessageFormat) {
+        self.message_format = message_format;
+    }
+
     /// Set the writer to use when rendering diagnostics
     pub fn set_diagnostic_writer(&mut self, stream: impl 'static + WriteColor) {
         self.diagnostic_writer = RefCell::new(Box::new(stream) as Box<dyn WriteColor>);
@@ -137,6 +219,17 @@ impl<'surface, 'core> Driver<'surface, 'core> {
         self.emit_writer = RefCell::new(Box::new(stream) as Box<dyn WriteColor>);
     }
 
+    /// Set how binary data should be emitted
+    pub fn set_data_output(&mut self, data_output: DataOutput) {
+        self.data_output = data_output;
+    }
+
+    /// Set the limits to enforce when reading binary data, replacing the
+    /// [default limits][binary::Limits::default]
+    pub fn set_binary_limits(&mut self, binary_limits: binary::Limits) {
+        self.binary_limits = binary_limits;
+    }
+
     /// Read a source file using a reader.
     pub fn read_source(&mut self, name: &str, mut reader: impl Read) -> FileId {
         // TODO: render diagnostics
@@ -152,7 +245,28 @@ impl<'surface, 'core> Driver<'surface, 'core> {
         self.files.add(path.display().to_string(), source)
     }
 
+    /// Open a file containing binary data, emitting a diagnostic and
+    /// returning `None` if it could not be opened.
+    pub fn open_binary_path(&self, path: &Path) -> Option<std::fs::File> {
+        match std::fs::File::open(path) {
+            Ok(file) => Some(file),
+            Err(error) => {
+                let diagnostic = Diagnostic::error().with_message(format!(
+                    "failed to open binary file `{}`: {}",
+                    path.display(),
+                    error,
+                ));
+                self.emit_diagnostics(std::iter::once(diagnostic));
+                None
+            }
+        }
+    }
+
     pub fn elaborate(&mut self, file_id: FileId) -> Status {
+        self.catch_eval_errors(|this| this.elaborate_term(file_id))
+    }
+
+    fn elaborate_term(&mut self, file_id: FileId) -> Status {
         let (surface_term, parse_diagnostics) = self.parse_term(file_id);
         let err_scope = scoped_arena::Scope::new();
         let mut context = elaboration::Context::new(&self.interner, &self.core_scope, &err_scope);
@@ -179,6 +293,10 @@ impl<'surface, 'core> Driver<'surface, 'core> {
     }
 
     pub fn normalise(&mut self, file_id: FileId) -> Status {
+        self.catch_eval_errors(|this| this.normalise_term(file_id))
+    }
+
+    fn normalise_term(&mut self, file_id: FileId) -> Status {
         let (surface_term, parse_diagnostics) = self.parse_term(file_id);
         let err_scope = scoped_arena::Scope::new();
         let mut context = elaboration::Context::new(&self.interner, &self.core_scope, &err_scope);
@@ -206,6 +324,10 @@ impl<'surface, 'core> Driver<'surface, 'core> {
     }
 
     pub fn r#type(&mut self, file_id: FileId) -> Status {
+        self.catch_eval_errors(|this| this.type_term(file_id))
+    }
+
+    fn type_term(&mut self, file_id: FileId) -> Status {
         let (surface_term, parse_diagnostics) = self.parse_term(file_id);
         let err_scope = scoped_arena::Scope::new();
         let mut context = elaboration::Context::new(&self.interner, &self.core_scope, &err_scope);
@@ -231,17 +353,18 @@ impl<'surface, 'core> Driver<'surface, 'core> {
     }
 
     pub fn read_format(&mut self, file_id: FileId, reader: &mut dyn binary::SeekRead) -> Status {
-        use itertools::Itertools;
+        self.catch_eval_errors(|this| this.read_format_data(file_id, reader))
+    }
+
+    fn read_format_data(&mut self, file_id: FileId, reader: &mut dyn binary::SeekRead) -> Status {
         use pretty::DocAllocator;
-        use std::sync::Arc;
 
-        use crate::core::semantics::Value;
         use crate::core::Prim;
 
         let (surface_term, parse_diagnostics) = self.parse_term(file_id);
         let err_scope = scoped_arena::Scope::new();
         let mut context = elaboration::Context::new(&self.interner, &self.core_scope, &err_scope);
-        let format = context.check(&surface_term, &Arc::new(Value::prim(Prim::FormatType, [])));
+        let format = context.check(&surface_term, &semantics::prim_value(Prim::FormatType));
 
         let diagnostics = {
             let elab_messages = context.drain_messages();
@@ -253,10 +376,71 @@ impl<'surface, 'core> Driver<'surface, 'core> {
         }
 
         let format = context.eval_context().eval(&format);
-        let refs = context
-            .binary_context()
-            .read_entrypoint(reader, format)
-            .unwrap(); // TODO: render nicer errors
+        let mut binary_context = context.binary_context(&self.core_scope);
+        binary_context.set_limits(self.binary_limits);
+        let refs = match binary_context.read_entrypoint(reader, format) {
+            Ok(refs) => refs,
+            Err(error) => {
+                let diagnostic = Diagnostic::error()
+                    .with_message(format!("failed to read binary data: {}", error));
+                self.emit_diagnostics(std::iter::once(diagnostic));
+                return Status::Error;
+            }
+        };
+
+        if let DataOutput::Hexdump = self.data_output {
+            let mut data = Vec::new();
+            (reader.seek(SeekFrom::Start(0)))
+                .and_then(|_| reader.read_to_end(&mut data))
+                .unwrap(); // TODO: render nicer errors
+
+            let mut annotations = Vec::new();
+            for (pos, parsed_refs) in refs.iter().sorted_by_key(|(pos, _)| **pos) {
+                for parsed_ref in parsed_refs {
+                    let path = format!("@{}", pos);
+                    let (expr, provenance) = (&parsed_ref.expr, &parsed_ref.provenance);
+                    collect_annotations(&self.interner, path, expr, provenance, &mut annotations);
+                }
+            }
+            annotations.sort_by_key(|(start, end, _, _, _)| (*start, *end));
+
+            let mut emit_writer = self.emit_writer.borrow_mut();
+            let mut hexdump = Hexdump::new(&data);
+            for (start, end, path, expr, _) in annotations {
+                self.surface_scope.reset(); // Reuse the surface scope for distillation
+
+                let expr = context.quote_context(&self.core_scope).quote(&expr);
+                let expr = context
+                    .distillation_context(&self.surface_scope)
+                    .check(&expr);
+                let context = surface::pretty::Context::new(&self.interner, &self.surface_scope);
+                let expr = context
+                    .term(&expr)
+                    .into_doc()
+                    .pretty(usize::MAX)
+                    .to_string();
+
+                let annotation = format!("{} = {}", path, expr);
+                hexdump
+                    .write_range(&mut *emit_writer, start, end, &annotation)
+                    .unwrap();
+            }
+            hexdump.write_rest(&mut *emit_writer).unwrap();
+            emit_writer.flush().unwrap();
+
+            return Status::Ok;
+        }
+
+        if let DataOutput::Coverage = self.data_output {
+            let len = reader.seek(SeekFrom::End(0)).unwrap(); // TODO: render nicer errors
+            let coverage = binary_context.coverage(len);
+
+            let mut emit_writer = self.emit_writer.borrow_mut();
+            write_coverage(&mut *emit_writer, len, &coverage).unwrap();
+            emit_writer.flush().unwrap();
+
+            return Status::Ok;
+        }
 
         for (pos, parsed_refs) in refs.into_iter().sorted_by_key(|(pos, _)| *pos) {
             self.surface_scope.reset(); // Reuse the surface scope for distillation
@@ -296,6 +480,228 @@ impl<'surface, 'core> Driver<'surface, 'core> {
         Status::Ok
     }
 
+    /// Parse two binary files with the same format, emitting the fields that
+    /// were added, removed, or changed between them.
+    pub fn diff_format(
+        &mut self,
+        file_id: FileId,
+        old_reader: &mut dyn binary::SeekRead,
+        new_reader: &mut dyn binary::SeekRead,
+    ) -> Status {
+        self.catch_eval_errors(|this| this.diff_format_data(file_id, old_reader, new_reader))
+    }
+
+    fn diff_format_data<'a>(
+        &mut self,
+        file_id: FileId,
+        old_reader: &'a mut dyn binary::SeekRead,
+        new_reader: &'a mut dyn binary::SeekRead,
+    ) -> Status {
+        use crate::core::Prim;
+
+        let (surface_term, parse_diagnostics) = self.parse_term(file_id);
+        let err_scope = scoped_arena::Scope::new();
+        let mut context = elaboration::Context::new(&self.interner, &self.core_scope, &err_scope);
+        let format = context.check(&surface_term, &semantics::prim_value(Prim::FormatType));
+
+        let diagnostics = {
+            let elab_messages = context.drain_messages();
+            parse_diagnostics.chain(elab_messages.map(|m| m.to_diagnostic(&self.interner, file_id)))
+        };
+
+        if !(self.emit_diagnostics(diagnostics) || self.allow_errors) {
+            return Status::Error;
+        }
+
+        let format = context.eval_context().eval(&format);
+        let mut fields = Vec::new();
+
+        for (name, reader) in [("old", old_reader), ("new", new_reader)] {
+            let mut binary_context = context.binary_context(&self.core_scope);
+            binary_context.set_limits(self.binary_limits);
+            let refs = match binary_context.read_entrypoint(reader, format.clone()) {
+                Ok(refs) => refs,
+                Err(error) => {
+                    let diagnostic = Diagnostic::error()
+                        .with_message(format!("failed to read {} binary data: {}", name, error));
+                    self.emit_diagnostics(std::iter::once(diagnostic));
+                    return Status::Error;
+                }
+            };
+
+            // Referenced data is keyed by the path of the first reference that
+            // points to it, so that it can be compared even if it was moved.
+            // Data parsed with different formats at the same position is keyed
+            // separately, by the first reference with each format, so that it
+            // does not depend on the order that the references were parsed in.
+            // Data that is not reached by any reference path falls back to
+            // being keyed by its position.
+            let mut ref_keys = HashMap::new();
+            let mut pending_refs = VecDeque::new();
+            let mut positions = refs.keys().copied().sorted();
+            let mut ref_fields = Vec::new();
+            loop {
+                let (key, ref_indices) = match pending_refs.pop_front() {
+                    Some(pending_ref) => pending_ref,
+                    None => {
+                        let unkeyed_refs = positions.find_map(|pos| {
+                            let indices = (0..refs[&pos].len())
+                                .filter(|index| !ref_keys.contains_key(&(pos, *index)))
+                                .map(|index| (pos, index))
+                                .collect::<Vec<_>>();
+                            (!indices.is_empty()).then(|| (pos, indices))
+                        });
+                        match unkeyed_refs {
+                            Some((pos, ref_indices)) => {
+                                let key = format!("@{}", pos);
+                                for ref_index in &ref_indices {
+                                    ref_keys.insert(*ref_index, key.clone());
+                                }
+                                (key, ref_indices)
+                            }
+                            None => break,
+                        }
+                    }
+                };
+
+                let mut annotations = Vec::new();
+                for (pos, index) in ref_indices {
+                    let parsed_ref = &refs[&pos][index];
+                    let (expr, provenance) = (&parsed_ref.expr, &parsed_ref.provenance);
+                    collect_annotations(
+                        &self.interner,
+                        key.clone(),
+                        expr,
+                        provenance,
+                        &mut annotations,
+                    );
+                }
+
+                let mut diff_fields = Vec::with_capacity(annotations.len());
+                for (start, end, path, expr, format) in annotations {
+                    if let Value::ConstLit(Const::Ref(ref_pos)) = expr.as_ref() {
+                        let parsed_refs = refs.get(ref_pos).map_or(&[][..], Vec::as_slice);
+                        let linked_format = linked_format(&context, &format);
+                        let ref_indices = (0..parsed_refs.len())
+                            .filter(|index| !ref_keys.contains_key(&(*ref_pos, *index)))
+                            .filter(|index| match &linked_format {
+                                Some(format) => {
+                                    let ref_format = &parsed_refs[*index].format;
+                                    Arc::ptr_eq(ref_format, format)
+                                        || context.conversion_context().is_equal(ref_format, format)
+                                }
+                                None => true,
+                            })
+                            .map(|index| (*ref_pos, index))
+                            .collect::<Vec<_>>();
+
+                        if !ref_indices.is_empty() {
+                            let ref_key = format!("*{}", path);
+                            for ref_index in &ref_indices {
+                                ref_keys.insert(*ref_index, ref_key.clone());
+                            }
+                            pending_refs.push_back((ref_key, ref_indices));
+                        }
+                    }
+
+                    self.surface_scope.reset(); // Reuse the surface scope for distillation
+
+                    let expr = context.quote_context(&self.core_scope).quote(&expr);
+                    let expr = context
+                        .distillation_context(&self.surface_scope)
+                        .check(&expr);
+                    let context =
+                        surface::pretty::Context::new(&self.interner, &self.surface_scope);
+                    let expr = context
+                        .term(&expr)
+                        .into_doc()
+                        .pretty(usize::MAX)
+                        .to_string();
+
+                    diff_fields.push(DiffField {
+                        path,
+                        start,
+                        end,
+                        expr,
+                    });
+                }
+                ref_fields.push((key, diff_fields));
+            }
+            fields.push(ref_fields);
+        }
+
+        let mut emit_writer = self.emit_writer.borrow_mut();
+        write_diff(&mut *emit_writer, &fields[0], &fields[1]).unwrap();
+        emit_writer.flush().unwrap();
+
+        Status::Ok
+    }
+
+    /// Run a command, reporting any errors encountered during evaluation as
+    /// bugs, along with a trace of the definitions, fields, functions and
+    /// references that were being evaluated or read when they occurred.
+    fn catch_eval_errors(&mut self, command: impl FnOnce(&mut Self) -> Status) -> Status {
+        let was_catching = CATCHING_EVAL_ERRORS.with(|catching| catching.replace(true));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| command(self)));
+        CATCHING_EVAL_ERRORS.with(|catching| catching.set(was_catching));
+
+        match result {
+            Ok(status) => status,
+            Err(payload) => match payload.downcast::<semantics::Error>() {
+                Ok(error) => {
+                    let diagnostic = self.eval_error_diagnostic(&error);
+                    self.emit_diagnostics(std::iter::once(diagnostic));
+                    Status::Error
+                }
+                Err(payload) => std::panic::resume_unwind(payload),
+            },
+        }
+    }
+
+    fn eval_error_diagnostic(&self, error: &semantics::Error) -> Diagnostic<FileId> {
+        let interner = self.interner.borrow();
+        let name = |name| interner.resolve(name).unwrap_or("<unknown>");
+
+        let trace = error.trace.iter().map(|frame| match frame {
+            semantics::Frame::LetDef(Some(def_name)) => {
+                format!("while evaluating the definition of `{}`", name(*def_name))
+            }
+            semantics::Frame::LetDef(None) => "while evaluating a definition".to_owned(),
+            semantics::Frame::RecordField(label) => {
+                format!("while evaluating the field `{}`", name(*label))
+            }
+            semantics::Frame::FunApp(Some(input_name)) => {
+                format!(
+                    "while applying the function `fun {} => ...`",
+                    name(*input_name)
+                )
+            }
+            semantics::Frame::FunApp(None) => {
+                "while applying the function `fun _ => ...`".to_owned()
+            }
+            semantics::Frame::PrimApp(prim) => {
+                format!("while applying the primitive `{}`", prim.name())
+            }
+            semantics::Frame::ReadField(label) => {
+                format!("while reading the field `{}`", name(*label))
+            }
+            semantics::Frame::ReadRef(pos) => {
+                format!("while reading the reference at position {}", pos)
+            }
+        });
+
+        Diagnostic::bug()
+            .with_message(format!("compiler panicked at '{}'", error.description()))
+            .with_notes(
+                trace
+                    .chain(std::iter::once(format!(
+                        "please file a bug report at: {}",
+                        BUG_REPORT_URL
+                    )))
+                    .collect(),
+            )
+    }
+
     fn parse_term(
         &'surface self,
         file_id: FileId,
@@ -326,13 +732,22 @@ impl<'surface, 'core> Driver<'surface, 'core> {
 
         for diagnostic in diagnostics {
             let mut diagnostic_writer = self.diagnostic_writer.borrow_mut();
-            codespan_reporting::term::emit(
-                &mut *diagnostic_writer,
-                &self.codespan_config,
-                &self.files,
-                &diagnostic,
-            )
-            .unwrap();
+            match self.message_format {
+                MessageFormat::Human => {
+                    codespan_reporting::term::emit(
+                        &mut *diagnostic_writer,
+                        &self.codespan_config,
+                        &self.files,
+                        &diagnostic,
+                    )
+                    .unwrap();
+                }
+                MessageFormat::Json => {
+                    let json = JsonDiagnostic::new(&self.files, &self.codespan_config, &diagnostic);
+                    serde_json::to_writer(&mut *diagnostic_writer, &json).unwrap();
+                    writeln!(diagnostic_writer).unwrap();
+                }
+            }
             diagnostic_writer.flush().unwrap();
 
             is_ok &= diagnostic.severity < Severity::Error;
@@ -341,3 +756,448 @@ impl<'surface, 'core> Driver<'surface, 'core> {
         is_ok
     }
 }
+
+/// A diagnostic, in the form that is emitted by [`MessageFormat::Json`].
+#[derive(Debug, serde::Serialize)]
+struct JsonDiagnostic {
+    severity: &'static str,
+    code: Option<String>,
+    message: String,
+    labels: Vec<JsonLabel>,
+    notes: Vec<String>,
+    /// The diagnostic as it would have been rendered by [`MessageFormat::Human`]
+    rendered: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct JsonLabel {
+    style: &'static str,
+    file: String,
+    start: JsonLocation,
+    end: JsonLocation,
+    message: String,
+}
+
+/// A location in a source file. Line and column numbers start at one.
+#[derive(Debug, serde::Serialize)]
+struct JsonLocation {
+    byte: usize,
+    line: usize,
+    column: usize,
+}
+
+impl JsonDiagnostic {
+    fn new<'files, F: Files<'files, FileId = FileId>>(
+        files: &'files F,
+        config: &codespan_reporting::term::Config,
+        diagnostic: &Diagnostic<FileId>,
+    ) -> JsonDiagnostic {
+        let mut rendered = NoColor::new(Vec::new());
+        codespan_reporting::term::emit(&mut rendered, config, files, diagnostic).unwrap();
+
+        JsonDiagnostic {
+            severity: match diagnostic.severity {
+                Severity::Bug => "bug",
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Note => "note",
+                Severity::Help => "help",
+            },
+            code: diagnostic.code.clone(),
+            message: diagnostic.message.clone(),
+            labels: (diagnostic.labels.iter())
+                .map(|label| JsonLabel {
+                    style: match label.style {
+                        LabelStyle::Primary => "primary",
+                        LabelStyle::Secondary => "secondary",
+                    },
+                    file: files.name(label.file_id).unwrap().to_string(),
+                    start: JsonLocation::new(files, label.file_id, label.range.start),
+                    end: JsonLocation::new(files, label.file_id, label.range.end),
+                    message: label.message.clone(),
+                })
+                .collect(),
+            notes: diagnostic.notes.clone(),
+            rendered: String::from_utf8_lossy(&rendered.into_inner()).into_owned(),
+        }
+    }
+}
+
+impl JsonLocation {
+    fn new<'files, F: Files<'files, FileId = FileId>>(
+        files: &'files F,
+        file_id: FileId,
+        byte: usize,
+    ) -> JsonLocation {
+        let location = files.location(file_id, byte).unwrap();
+
+        JsonLocation {
+            byte,
+            line: location.line_number,
+            column: location.column_number,
+        }
+    }
+}
+
+/// Collect the leaves of a parsed expression, along with the range of bytes
+/// they were parsed from, and the path of labels and indices leading to them.
+fn collect_annotations<'arena>(
+    interner: &RefCell<StringInterner>,
+    path: String,
+    expr: &ArcValue<'arena>,
+    provenance: &binary::Provenance<'arena>,
+    annotations: &mut Vec<(u64, u64, String, ArcValue<'arena>, ArcValue<'arena>)>,
+) {
+    let children = &provenance.children;
+
+    match expr.as_ref() {
+        Value::RecordLit(labels, exprs) if !exprs.is_empty() && exprs.len() == children.len() => {
+            for ((label, expr), provenance) in labels.iter().zip(exprs).zip(children) {
+                let label = interner
+                    .borrow()
+                    .resolve(*label)
+                    .unwrap_or("<unknown>")
+                    .to_owned();
+                let path = format!("{}.{}", path, label);
+                collect_annotations(interner, path, expr, provenance, annotations);
+            }
+        }
+        Value::ArrayLit(exprs) if !exprs.is_empty() && exprs.len() == children.len() => {
+            for (index, (expr, provenance)) in exprs.iter().zip(children).enumerate() {
+                let path = format!("{}[{}]", path, index);
+                collect_annotations(interner, path, expr, provenance, annotations);
+            }
+        }
+        _ => annotations.push((
+            provenance.start,
+            provenance.end,
+            path,
+            expr.clone(),
+            provenance.format.clone(),
+        )),
+    }
+}
+
+/// The format of the data that is linked to by a field parsed with `format`,
+/// if `format` is a link format.
+fn linked_format<'arena>(
+    context: &elaboration::Context<'_, 'arena, '_>,
+    format: &ArcValue<'arena>,
+) -> Option<ArcValue<'arena>> {
+    use crate::core::semantics::{Elim, Head};
+    use crate::core::Prim;
+
+    match context.elim_context().force(format).as_ref() {
+        Value::Stuck(Head::Prim(Prim::FormatLink), spine) => match spine.as_slice() {
+            [Elim::FunApp(_), Elim::FunApp(elem_format)] => Some(elem_format.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Writes binary data as hexadecimal and ASCII columns, with annotations
+/// beside the ranges of bytes that they describe.
+struct Hexdump<'data> {
+    data: &'data [u8],
+    /// The offset after the last byte that has been written
+    offset: usize,
+}
+
+impl<'data> Hexdump<'data> {
+    /// The number of bytes to write on each line
+    const LINE_WIDTH: usize = 16;
+
+    fn new(data: &'data [u8]) -> Hexdump<'data> {
+        Hexdump { data, offset: 0 }
+    }
+
+    /// Write an annotated range of bytes, along with any unannotated bytes
+    /// that precede it. Ranges that overlap with bytes that have already been
+    /// written are written again in full.
+    fn write_range(
+        &mut self,
+        writer: &mut dyn Write,
+        start: u64,
+        end: u64,
+        annotation: &str,
+    ) -> io::Result<()> {
+        let start =
+            usize::try_from(start).map_or(self.data.len(), |start| start.min(self.data.len()));
+        let end = usize::try_from(end).map_or(self.data.len(), |end| end.min(self.data.len()));
+
+        self.write_unannotated(writer, start)?;
+
+        let mut lines = self.data[start..end].chunks(Self::LINE_WIDTH);
+        write_hexdump_line(writer, start, lines.next().unwrap_or(&[]), Some(annotation))?;
+        for (index, line) in lines.enumerate() {
+            write_hexdump_line(writer, start + (index + 1) * Self::LINE_WIDTH, line, None)?;
+        }
+
+        self.offset = std::cmp::max(self.offset, end);
+        Ok(())
+    }
+
+    /// Write the bytes following the last annotated range.
+    fn write_rest(&mut self, writer: &mut dyn Write) -> io::Result<()> {
+        self.write_unannotated(writer, self.data.len())
+    }
+
+    fn write_unannotated(&mut self, writer: &mut dyn Write, end: usize) -> io::Result<()> {
+        while self.offset < end {
+            let line_end = std::cmp::min(self.offset + Self::LINE_WIDTH, end);
+            write_hexdump_line(writer, self.offset, &self.data[self.offset..line_end], None)?;
+            self.offset = line_end;
+        }
+        Ok(())
+    }
+}
+
+fn write_hexdump_line(
+    writer: &mut dyn Write,
+    offset: usize,
+    bytes: &[u8],
+    annotation: Option<&str>,
+) -> io::Result<()> {
+    let hex = bytes.iter().map(|byte| format!("{:02x}", byte)).join(" ");
+    let ascii = (bytes.iter())
+        .map(|byte| match byte {
+            0x20..=0x7e => char::from(*byte),
+            _ => '.',
+        })
+        .collect::<String>();
+
+    match annotation {
+        Some(annotation) => writeln!(
+            writer,
+            "{:08x}  {:<47}  |{:<16}|  {}",
+            offset, hex, ascii, annotation
+        ),
+        None => writeln!(writer, "{:08x}  {:<47}  |{:<16}|", offset, hex, ascii),
+    }
+}
+
+/// Write a table of the regions that were not read exactly once, followed by
+/// a summary of the coverage.
+fn write_coverage(writer: &mut dyn Write, len: u64, coverage: &binary::Coverage) -> io::Result<()> {
+    let mut bytes_unread = 0;
+    let mut bytes_reread = 0;
+
+    let regions = (coverage.regions().iter()).filter(|region| region.reads != 1);
+    for (index, region) in regions.enumerate() {
+        if index == 0 {
+            writeln!(
+                writer,
+                "{:<8}  {:<8}  {:>8}  reads",
+                "start", "end", "length"
+            )?;
+        }
+
+        let length = region.end - region.start;
+        match region.reads {
+            0 => bytes_unread += length,
+            _ => bytes_reread += length,
+        }
+        writeln!(
+            writer,
+            "{:08x}  {:08x}  {:>8}  {}",
+            region.start, region.end, length, region.reads,
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "{} of {} bytes read, {} bytes unread, {} bytes read more than once",
+        len - bytes_unread,
+        len,
+        bytes_unread,
+        bytes_reread,
+    )
+}
+
+/// A field of some parsed binary data, to be compared with [`write_diff`].
+struct DiffField {
+    /// The reference position, labels, and indices leading to the field
+    path: String,
+    /// The offset of the first byte of the field
+    start: u64,
+    /// The offset after the last byte of the field
+    end: u64,
+    /// The pretty printed value of the field
+    expr: String,
+}
+
+/// Write the fields that differ between the references parsed from two binary
+/// files, followed by a summary of the differences. Fields are aligned by the
+/// key of the reference they were parsed from, followed by the labels and
+/// indices leading to them. References are keyed by the path of the first
+/// field that links to them with the format they were parsed with, prefixed
+/// with a `*` (for example `*@0.name`), or by their position (for example
+/// `@0`) if no such field was found.
+fn write_diff(
+    writer: &mut dyn Write,
+    old_refs: &[(String, Vec<DiffField>)],
+    new_refs: &[(String, Vec<DiffField>)],
+) -> io::Result<()> {
+    let mut summary = DiffSummary::default();
+
+    let old_fields = old_refs
+        .iter()
+        .map(|(key, fields)| (key, fields))
+        .collect::<HashMap<_, _>>();
+    let new_fields = new_refs
+        .iter()
+        .map(|(key, fields)| (key, fields))
+        .collect::<HashMap<_, _>>();
+
+    let keys = Iterator::chain(old_refs.iter(), new_refs.iter())
+        .map(|(key, _)| key)
+        .unique();
+    for key in keys {
+        let old = old_fields
+            .get(key)
+            .map_or(&[][..], |fields| fields.as_slice());
+        let new = new_fields
+            .get(key)
+            .map_or(&[][..], |fields| fields.as_slice());
+        write_ref_diff(writer, old, new, &mut summary)?;
+    }
+
+    writeln!(
+        writer,
+        "{} changed, {} removed, {} added",
+        summary.fields_changed, summary.fields_removed, summary.fields_added,
+    )
+}
+
+#[derive(Default)]
+struct DiffSummary {
+    fields_changed: usize,
+    fields_removed: usize,
+    fields_added: usize,
+}
+
+/// Write the fields that differ between two lists of fields parsed from the
+/// same reference, interleaving the added fields with the others.
+fn write_ref_diff(
+    writer: &mut dyn Write,
+    old: &[DiffField],
+    new: &[DiffField],
+    summary: &mut DiffSummary,
+) -> io::Result<()> {
+    // Paths can be repeated if more than one format was parsed at the same
+    // position, so we pair them up in the order that they were parsed
+    let mut new_indices = HashMap::new();
+    let mut path_counts = HashMap::new();
+    for (index, field) in new.iter().enumerate() {
+        let count = path_counts.entry(field.path.as_str()).or_insert(0);
+        new_indices.insert((field.path.as_str(), *count), index);
+        *count += 1;
+    }
+
+    let mut is_matched = vec![false; new.len()];
+    let mut next_new = 0;
+    let mut path_counts = HashMap::new();
+    for old_field in old {
+        let count = path_counts.entry(old_field.path.as_str()).or_insert(0);
+        let new_index = new_indices.get(&(old_field.path.as_str(), *count));
+        *count += 1;
+
+        let new_index = match new_index {
+            Some(new_index) => *new_index,
+            None => {
+                summary.fields_removed += 1;
+                writeln!(
+                    writer,
+                    "- {} = {} (old {:08x}..{:08x})",
+                    old_field.path, old_field.expr, old_field.start, old_field.end,
+                )?;
+                continue;
+            }
+        };
+
+        // Write the added fields that precede the matching field
+        is_matched[new_index] = true;
+        for (new_field, is_matched) in new[..new_index].iter().zip(&is_matched).skip(next_new) {
+            if !is_matched {
+                summary.fields_added += 1;
+                write_added_field(writer, new_field)?;
+            }
+        }
+        next_new = std::cmp::max(next_new, new_index + 1);
+
+        let new_field = &new[new_index];
+        if old_field.expr != new_field.expr {
+            summary.fields_changed += 1;
+            writeln!(
+                writer,
+                "~ {} = {} -> {} (old {:08x}..{:08x}, new {:08x}..{:08x})",
+                old_field.path,
+                old_field.expr,
+                new_field.expr,
+                old_field.start,
+                old_field.end,
+                new_field.start,
+                new_field.end,
+            )?;
+        }
+    }
+
+    for (new_field, is_matched) in new.iter().zip(&is_matched).skip(next_new) {
+        if !is_matched {
+            summary.fields_added += 1;
+            write_added_field(writer, new_field)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_added_field(writer: &mut dyn Write, field: &DiffField) -> io::Result<()> {
+    writeln!(
+        writer,
+        "+ {} = {} (new {:08x}..{:08x})",
+        field.path, field.expr, field.start, field.end,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use codespan_reporting::diagnostic::Label;
+
+    use super::*;
+
+    #[test]
+    fn json_diagnostic_locations() {
+        let mut files = SimpleFiles::new();
+        let file_id = files.add("test.fathom".to_owned(), "let x = 1;\nfoo x".to_owned());
+        let diagnostic = Diagnostic::error()
+            .with_message("cannot find `foo` in scope")
+            .with_labels(vec![
+                Label::primary(file_id, 11..14).with_message("unbound name")
+            ])
+            .with_notes(vec!["help: did you mean `x`?".to_owned()]);
+
+        let config = codespan_reporting::term::Config::default();
+        let json = JsonDiagnostic::new(&files, &config, &diagnostic);
+        let json = serde_json::to_value(&json).unwrap();
+
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["code"], serde_json::Value::Null);
+        assert_eq!(json["message"], "cannot find `foo` in scope");
+        assert_eq!(json["notes"][0], "help: did you mean `x`?");
+
+        let label = &json["labels"][0];
+        assert_eq!(label["style"], "primary");
+        assert_eq!(label["file"], "test.fathom");
+        assert_eq!(label["message"], "unbound name");
+        assert_eq!(label["start"]["byte"], 11);
+        assert_eq!(label["start"]["line"], 2);
+        assert_eq!(label["start"]["column"], 1);
+        assert_eq!(label["end"]["column"], 4);
+
+        let rendered = json["rendered"].as_str().unwrap();
+        assert!(rendered.starts_with("error: cannot find `foo` in scope"));
+        assert!(rendered.contains("test.fathom:2:1"));
+    }
+}
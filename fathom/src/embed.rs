@@ -0,0 +1,506 @@
+//! A library interface for compiling formats and reading binary data with
+//! them, for use when embedding Fathom in other programs.
+//!
+//! Unlike the [`Driver`][crate::Driver], nothing is written to the standard
+//! output or error streams. Diagnostics are returned as [`Diagnostics`], and
+//! parsed data is returned as an owned tree of [`Data`] that does not borrow
+//! from the arenas used during elaboration and evaluation. Parsed data can
+//! also be deserialized into Rust types with [`from_data`].
+//!
+//! Errors encountered during evaluation are bugs in the compiler. They are
+//! caught while formats are compiled and parsed, and returned as
+//! [`Diagnostics`] and [`ReadError::Eval`] respectively. Note that these are
+//! reported by unwinding, so they are also passed to the current panic hook.
+
+use codespan_reporting::diagnostic::{Diagnostic, Severity};
+use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::term::termcolor::{NoColor, WriteColor};
+use scoped_arena::Scope;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::core::semantics::{self, ArcValue, Elim, Head, Value};
+use crate::core::{binary, Const, Prim};
+use crate::source::FileId;
+use crate::surface::{self, elaboration};
+use crate::StringInterner;
+
+mod de;
+
+pub use self::de::{from_data, DeserializeError};
+
+/// A format that has been successfully elaborated, and can be used to read
+/// binary data.
+///
+/// The elaborated format is retained, along with the arenas it was allocated
+/// in, so it can be reused each time that data is [parsed].
+///
+/// [parsed]: Format::parse
+#[derive(Clone)]
+pub struct Format {
+    name: String,
+    compiled: Rc<CompiledFormat>,
+    limits: binary::Limits,
+}
+
+/// The string interner and arenas that an elaborated format is allocated in.
+struct Arenas {
+    interner: RefCell<StringInterner>,
+    core_scope: Scope<'static>,
+    err_scope: Scope<'static>,
+}
+
+/// A format that has been elaborated and evaluated.
+struct Elaborated<'arenas> {
+    context: RefCell<elaboration::Context<'arenas, 'arenas, 'arenas>>,
+    format: ArcValue<'arenas>,
+}
+
+self_cell::self_cell!(
+    struct CompiledFormat {
+        owner: Arenas,
+
+        #[not_covariant]
+        dependent: Elaborated,
+    }
+);
+
+impl Format {
+    /// Compile a format from its source code.
+    pub fn compile(source: &str) -> Result<Format, Diagnostics> {
+        Format::compile_named("<format>", source)
+    }
+
+    /// Compile a format from its source code, using the supplied name to
+    /// refer to the source in diagnostics.
+    pub fn compile_named(name: &str, source: &str) -> Result<Format, Diagnostics> {
+        let mut files = SimpleFiles::new();
+        let file_id = files.add(name.to_owned(), source.to_owned());
+
+        let arenas = Arenas {
+            interner: RefCell::new(StringInterner::new()),
+            core_scope: Scope::new(),
+            err_scope: Scope::new(),
+        };
+
+        let compiled = CompiledFormat::try_new(arenas, |arenas| {
+            let interner = &arenas.interner;
+            let surface_scope = Scope::new();
+
+            let (surface_term, parse_messages) =
+                surface::Term::parse(interner, &surface_scope, source);
+            let mut context =
+                elaboration::Context::new(interner, &arenas.core_scope, &arenas.err_scope);
+            let result = catch_eval_errors(|| {
+                let format = context.check(&surface_term, &semantics::prim_value(Prim::FormatType));
+                context.eval_context().eval(&format)
+            });
+
+            let (format, diagnostics) = match result {
+                Ok(format) => {
+                    let diagnostics = (parse_messages.into_iter())
+                        .map(|message| message.to_diagnostic(file_id))
+                        .chain(
+                            (context.drain_messages())
+                                .map(|message| message.to_diagnostic(interner, file_id)),
+                        )
+                        .collect();
+                    (Some(format), diagnostics)
+                }
+                Err(kind) => {
+                    let diagnostic = Diagnostic::bug()
+                        .with_message(format!("compiler panicked at '{}'", kind.description()));
+                    (None, vec![diagnostic])
+                }
+            };
+            let diagnostics = Diagnostics {
+                files: files.clone(),
+                diagnostics,
+            };
+
+            match format {
+                Some(format) if !diagnostics.has_errors() => Ok(Elaborated {
+                    context: RefCell::new(context),
+                    format,
+                }),
+                _ => Err(diagnostics),
+            }
+        })?;
+
+        Ok(Format {
+            name: name.to_owned(),
+            compiled: Rc::new(compiled),
+            limits: binary::Limits::default(),
+        })
+    }
+
+    /// The name used to refer to the source of the format in diagnostics.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Set the limits to enforce when reading binary data, replacing the
+    /// [default limits][binary::Limits::default]
+    pub fn set_limits(&mut self, limits: binary::Limits) {
+        self.limits = limits;
+    }
+
+    /// Parse binary data, starting with this format at the beginning of the
+    /// data.
+    pub fn parse(&self, reader: &mut dyn binary::SeekRead) -> Result<ParsedData, ReadError> {
+        self.compiled.with_dependent(|arenas, elaborated| {
+            // Values read from the binary data are only needed until they are
+            // converted to `Data`, so they are allocated in a separate arena
+            // that is dropped after parsing.
+            let scope = Scope::new();
+            let mut context = elaborated.context.borrow_mut();
+            let format = &elaborated.format;
+
+            let refs = catch_eval_errors(|| {
+                let mut binary_context = context.binary_context(&scope);
+                binary_context.set_limits(self.limits);
+                binary_context.read_entrypoint(reader, format.clone())
+            })
+            .map_err(ReadError::Eval)??;
+
+            let mut context = DataContext {
+                interner: &arenas.interner,
+                scope: &scope,
+                context: &mut context,
+            };
+
+            catch_eval_errors(|| {
+                let mut entrypoint = None;
+                let refs = (refs.into_iter())
+                    .map(|(pos, parsed_refs)| {
+                        let exprs = (parsed_refs.iter())
+                            .map(|parsed_ref| {
+                                let data = context.data(&parsed_ref.expr);
+                                if pos == 0 && Arc::ptr_eq(&parsed_ref.format, format) {
+                                    entrypoint = Some(data.clone());
+                                }
+                                data
+                            })
+                            .collect();
+                        (pos, exprs)
+                    })
+                    .collect();
+
+                Ok(ParsedData {
+                    entrypoint: entrypoint.ok_or(ReadError::MissingEntrypoint)?,
+                    refs,
+                })
+            })
+            .map_err(ReadError::Eval)?
+        })
+    }
+}
+
+impl fmt::Debug for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Format")
+            .field("name", &self.name)
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Diagnostics produced while compiling a [`Format`].
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    files: SimpleFiles<String, String>,
+    diagnostics: Vec<Diagnostic<FileId>>,
+}
+
+impl Diagnostics {
+    /// Returns `true` if any of the diagnostics are errors.
+    pub fn has_errors(&self) -> bool {
+        (self.diagnostics.iter()).any(|diagnostic| diagnostic.severity >= Severity::Error)
+    }
+
+    /// The diagnostics, in the order they were encountered.
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic<FileId>> {
+        self.diagnostics.iter()
+    }
+
+    /// The source files that the diagnostics refer to.
+    pub fn files(&self) -> &SimpleFiles<String, String> {
+        &self.files
+    }
+
+    /// Render the diagnostics to a writer.
+    pub fn emit(
+        &self,
+        writer: &mut dyn WriteColor,
+        config: &codespan_reporting::term::Config,
+    ) -> Result<(), codespan_reporting::files::Error> {
+        for diagnostic in &self.diagnostics {
+            codespan_reporting::term::emit(writer, config, &self.files, diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut writer = NoColor::new(Vec::new());
+        let config = codespan_reporting::term::Config::default();
+        self.emit(&mut writer, &config).map_err(|_| fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&writer.into_inner()))
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+/// Errors encountered while parsing binary data with a [`Format`].
+#[derive(Debug)]
+pub enum ReadError {
+    /// The binary data could not be read with the format.
+    Read(binary::ReadError),
+    /// A bug was encountered while evaluating the format.
+    Eval(semantics::ErrorKind),
+    /// The data parsed with the format at the beginning of the binary data
+    /// could not be found. This is a bug in the compiler.
+    MissingEntrypoint,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Read(error) => write!(f, "failed to read binary data: {}", error),
+            ReadError::Eval(kind) => write!(f, "compiler panicked at '{}'", kind.description()),
+            ReadError::MissingEntrypoint => write!(f, "entrypoint was not parsed"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Read(error) => Some(error),
+            ReadError::Eval(_) | ReadError::MissingEntrypoint => None,
+        }
+    }
+}
+
+impl From<binary::ReadError> for ReadError {
+    fn from(error: binary::ReadError) -> ReadError {
+        ReadError::Read(error)
+    }
+}
+
+/// Binary data that was parsed with a [`Format`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedData {
+    entrypoint: Data,
+    refs: BTreeMap<u64, Vec<Data>>,
+}
+
+impl ParsedData {
+    /// The data parsed with the format at the beginning of the binary data.
+    pub fn entrypoint(&self) -> &Data {
+        &self.entrypoint
+    }
+
+    /// The references that were parsed, keyed by their position in the binary
+    /// data. This includes the entrypoint, along with any data parsed through
+    /// links.
+    pub fn refs(&self) -> &BTreeMap<u64, Vec<Data>> {
+        &self.refs
+    }
+}
+
+/// An owned representation of the values produced when parsing binary data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Data {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    S8(i8),
+    S16(i16),
+    S32(i32),
+    S64(i64),
+    F32(f32),
+    F64(f64),
+    /// Stream positions.
+    Pos(u64),
+    /// References to data that were parsed at the given position.
+    Ref(u64),
+    String(String),
+    /// Optional data.
+    Option(Option<Box<Data>>),
+    /// Records, with their fields in the order they were defined.
+    Record(Vec<(String, Data)>),
+    Array(Vec<Data>),
+    /// A value with no other representation, pretty printed as a term.
+    Term(String),
+}
+
+impl Data {
+    /// Lookup a field of a record.
+    pub fn field(&self, label: &str) -> Option<&Data> {
+        match self {
+            Data::Record(fields) => fields.iter().find(|(l, _)| l == label).map(|(_, d)| d),
+            _ => None,
+        }
+    }
+}
+
+/// Context for converting parsed values into [`Data`]. The values are
+/// allocated in `scope`, which is also used when pretty printing them.
+struct DataContext<'a, 'interner, 'arena, 'error, 'surface> {
+    interner: &'interner RefCell<StringInterner>,
+    scope: &'surface Scope<'surface>,
+    context: &'a mut elaboration::Context<'interner, 'arena, 'error>,
+}
+
+impl<'a, 'interner, 'arena, 'error, 'surface> DataContext<'a, 'interner, 'arena, 'error, 'surface>
+where
+    'arena: 'surface,
+{
+    fn resolve(&self, name: crate::StringId) -> String {
+        let interner = self.interner.borrow();
+        interner.resolve(name).unwrap_or("<unknown>").to_owned()
+    }
+
+    fn data(&mut self, value: &ArcValue<'surface>) -> Data {
+        let elim_context: semantics::ElimContext<'surface, '_> = self.context.elim_context();
+        let value = elim_context.force(value);
+
+        match value.as_ref() {
+            Value::ConstLit(r#const) => match *r#const {
+                Const::Bool(b) => Data::Bool(b),
+                Const::U8(n, _) => Data::U8(n),
+                Const::U16(n, _) => Data::U16(n),
+                Const::U32(n, _) => Data::U32(n),
+                Const::U64(n, _) => Data::U64(n),
+                Const::S8(n) => Data::S8(n),
+                Const::S16(n) => Data::S16(n),
+                Const::S32(n) => Data::S32(n),
+                Const::S64(n) => Data::S64(n),
+                Const::F32(n) => Data::F32(n),
+                Const::F64(n) => Data::F64(n),
+                Const::Pos(pos) => Data::Pos(pos),
+                Const::Ref(pos) => Data::Ref(pos),
+                Const::String(string) => Data::String(string.to_owned()),
+            },
+            Value::RecordLit(labels, exprs) => Data::Record(
+                Iterator::zip(labels.iter(), exprs.iter())
+                    .map(|(label, expr)| (self.resolve(*label), self.data(expr)))
+                    .collect(),
+            ),
+            Value::ArrayLit(exprs) => {
+                Data::Array(exprs.iter().map(|expr| self.data(expr)).collect())
+            }
+            Value::Stuck(Head::Prim(Prim::OptionSome), spine) => match spine.as_slice() {
+                [Elim::FunApp(_), Elim::FunApp(expr)] => {
+                    Data::Option(Some(Box::new(self.data(expr))))
+                }
+                _ => self.term(&value),
+            },
+            Value::Stuck(Head::Prim(Prim::OptionNone), spine) if spine.len() == 1 => {
+                Data::Option(None)
+            }
+            _ => self.term(&value),
+        }
+    }
+
+    fn term(&mut self, value: &ArcValue<'surface>) -> Data {
+        let mut quote_context: semantics::QuoteContext<'surface, 'surface, '_> =
+            self.context.quote_context(self.scope);
+        let term = quote_context.quote(value);
+        let term = self.context.distillation_context(self.scope).check(&term);
+        let context = surface::pretty::Context::new(self.interner, self.scope);
+        Data::Term(
+            context
+                .term(&term)
+                .into_doc()
+                .pretty(usize::MAX)
+                .to_string(),
+        )
+    }
+}
+
+/// Run a command, catching any errors encountered during evaluation.
+fn catch_eval_errors<T>(command: impl FnOnce() -> T) -> Result<T, semantics::ErrorKind> {
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(command));
+
+    match result {
+        Ok(result) => Ok(result),
+        Err(payload) => match payload.downcast::<semantics::Error>() {
+            Ok(error) => Err(error.kind),
+            Err(payload) => std::panic::resume_unwind(payload),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn parse_record() {
+        let format = Format::compile("{ len <- u8, data <- array8 len u16be }").unwrap();
+        let parsed_data = format.parse(&mut Cursor::new([2, 0, 1, 1, 0])).unwrap();
+
+        assert_eq!(
+            parsed_data.entrypoint(),
+            &Data::Record(vec![
+                ("len".to_owned(), Data::U8(2)),
+                (
+                    "data".to_owned(),
+                    Data::Array(vec![Data::U16(1), Data::U16(256)])
+                ),
+            ]),
+        );
+        assert_eq!(parsed_data.entrypoint().field("len"), Some(&Data::U8(2)));
+        assert_eq!(parsed_data.refs().len(), 1);
+    }
+
+    #[test]
+    fn parse_multiple() {
+        let format = Format::compile("{ len <- u8, data <- array8 len u8 }").unwrap();
+
+        for (len, data) in [(0, vec![]), (1, vec![Data::U8(7)])] {
+            let mut bytes = vec![len];
+            bytes.extend((data.iter()).map(|_| 7));
+            let parsed_data = format.parse(&mut Cursor::new(bytes)).unwrap();
+
+            assert_eq!(parsed_data.entrypoint().field("len"), Some(&Data::U8(len)));
+            assert_eq!(
+                parsed_data.entrypoint().field("data"),
+                Some(&Data::Array(data)),
+            );
+        }
+    }
+
+    #[test]
+    fn parse_fail() {
+        let format = Format::compile("{ len <- u8, data <- array8 len u16be }").unwrap();
+        let result = format.parse(&mut Cursor::new([2, 0]));
+
+        assert!(matches!(
+            result,
+            Err(ReadError::Read(binary::ReadError::Io(_)))
+        ));
+    }
+
+    #[test]
+    fn compile_errors() {
+        let diagnostics =
+            Format::compile_named("test.fathom", "{ len <- u8, data <- foo }").unwrap_err();
+
+        assert!(diagnostics.has_errors());
+        assert_eq!(diagnostics.iter().count(), 1);
+        assert!(diagnostics
+            .to_string()
+            .starts_with("error: cannot find `foo` in scope"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Deserialization of parsed [`Data`] into Rust types, using [`serde`].
+//!
+//! Records are deserialized as maps keyed by their labels, arrays as
+//! sequences, numbers as the corresponding Rust primitives, and options as
+//! Rust options. Stream positions and references are deserialized as `u64`
+//! offsets into the binary data.
+
+use serde::de::{
+    self, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Unexpected, Visitor,
+};
+use serde::Deserialize;
+use std::fmt;
+
+use super::Data;
+
+/// Deserialize an instance of type `T` from some parsed data.
+pub fn from_data<'de, T: Deserialize<'de>>(data: &'de Data) -> Result<T, DeserializeError> {
+    T::deserialize(data)
+}
+
+/// Errors encountered while deserializing [`Data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializeError {
+    message: String,
+}
+
+impl de::Error for DeserializeError {
+    fn custom<T: fmt::Display>(message: T) -> DeserializeError {
+        DeserializeError {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl Data {
+    fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            Data::Bool(b) => Unexpected::Bool(*b),
+            Data::U8(n) => Unexpected::Unsigned(u64::from(*n)),
+            Data::U16(n) => Unexpected::Unsigned(u64::from(*n)),
+            Data::U32(n) => Unexpected::Unsigned(u64::from(*n)),
+            Data::U64(n) | Data::Pos(n) | Data::Ref(n) => Unexpected::Unsigned(*n),
+            Data::S8(n) => Unexpected::Signed(i64::from(*n)),
+            Data::S16(n) => Unexpected::Signed(i64::from(*n)),
+            Data::S32(n) => Unexpected::Signed(i64::from(*n)),
+            Data::S64(n) => Unexpected::Signed(*n),
+            Data::F32(n) => Unexpected::Float(f64::from(*n)),
+            Data::F64(n) => Unexpected::Float(*n),
+            Data::String(s) => Unexpected::Str(s),
+            Data::Option(_) => Unexpected::Option,
+            Data::Record(fields) if fields.is_empty() => Unexpected::Unit,
+            Data::Record(_) => Unexpected::Map,
+            Data::Array(_) => Unexpected::Seq,
+            Data::Term(_) => Unexpected::Other("term"),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &'de Data {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+        match self {
+            Data::Bool(b) => visitor.visit_bool(*b),
+            Data::U8(n) => visitor.visit_u8(*n),
+            Data::U16(n) => visitor.visit_u16(*n),
+            Data::U32(n) => visitor.visit_u32(*n),
+            Data::U64(n) | Data::Pos(n) | Data::Ref(n) => visitor.visit_u64(*n),
+            Data::S8(n) => visitor.visit_i8(*n),
+            Data::S16(n) => visitor.visit_i16(*n),
+            Data::S32(n) => visitor.visit_i32(*n),
+            Data::S64(n) => visitor.visit_i64(*n),
+            Data::F32(n) => visitor.visit_f32(*n),
+            Data::F64(n) => visitor.visit_f64(*n),
+            Data::String(s) => visitor.visit_borrowed_str(s),
+            Data::Option(None) => visitor.visit_none(),
+            Data::Option(Some(data)) => visitor.visit_some(data.as_ref()),
+            Data::Record(fields) if fields.is_empty() => visitor.visit_unit(),
+            Data::Record(fields) => visitor.visit_map(RecordAccess::new(fields)),
+            Data::Array(elems) => visitor.visit_seq(ArrayAccess::new(elems)),
+            Data::Term(_) => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+        match self {
+            Data::Option(None) => visitor.visit_none(),
+            Data::Option(Some(data)) => visitor.visit_some(data.as_ref()),
+            // Data that was not parsed with an option format is always present
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+        match self {
+            Data::Record(fields) if fields.is_empty() => visitor.visit_unit(),
+            _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError> {
+        match self {
+            Data::Record(fields) => visitor.visit_map(RecordAccess::new(fields)),
+            _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError> {
+        match self {
+            // Strings can be used to select unit variants
+            Data::String(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+/// Access to the fields of a record, in the order they were defined.
+struct RecordAccess<'de> {
+    fields: std::slice::Iter<'de, (String, Data)>,
+    next_data: Option<&'de Data>,
+}
+
+impl<'de> RecordAccess<'de> {
+    fn new(fields: &'de [(String, Data)]) -> RecordAccess<'de> {
+        RecordAccess {
+            fields: fields.iter(),
+            next_data: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for RecordAccess<'de> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeserializeError> {
+        match self.fields.next() {
+            Some((label, data)) => {
+                self.next_data = Some(data);
+                let label: de::value::BorrowedStrDeserializer<'de, DeserializeError> =
+                    de::value::BorrowedStrDeserializer::new(label);
+                seed.deserialize(label).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, DeserializeError> {
+        match self.next_data.take() {
+            Some(data) => seed.deserialize(data),
+            None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+/// Access to the elements of an array.
+struct ArrayAccess<'de> {
+    elems: std::slice::Iter<'de, Data>,
+}
+
+impl<'de> ArrayAccess<'de> {
+    fn new(elems: &'de [Data]) -> ArrayAccess<'de> {
+        ArrayAccess {
+            elems: elems.iter(),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for ArrayAccess<'de> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeserializeError> {
+        match self.elems.next() {
+            Some(data) => seed.deserialize(data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.elems.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::embed::Format;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Header {
+        magic: u32,
+        len: u16,
+        data: Vec<i16>,
+        flag: Option<u8>,
+    }
+
+    #[test]
+    fn deserialize_record() {
+        let format =
+            Format::compile("{ magic <- u16be, len <- u8, data <- array8 len s8, flag <- u8 }")
+                .unwrap();
+        let parsed_data = format
+            .parse(&mut Cursor::new([0, 7, 2, 1, 0xff, 3]))
+            .unwrap();
+        let header: Header = from_data(parsed_data.entrypoint()).unwrap();
+
+        assert_eq!(
+            header,
+            Header {
+                magic: 7,
+                len: 2,
+                data: vec![1, -1],
+                flag: Some(3),
+            },
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_type() {
+        let data = Data::Record(vec![("magic".to_owned(), Data::String("x".to_owned()))]);
+        let error = from_data::<Header>(&data).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "invalid type: string \"x\", expected u32",
+        );
+    }
+
+    #[test]
+    fn deserialize_out_of_range() {
+        let error = from_data::<u8>(&Data::U16(256)).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "invalid value: integer `256`, expected u8",
+        );
+    }
+}
@@ -12,8 +12,12 @@ pub mod surface;
 // Top level driver
 mod driver;
 
+// Library interface
+mod embed;
+
 // Public exports
-pub use driver::{Driver, Status};
+pub use driver::{DataOutput, Driver, MessageFormat, Status};
+pub use embed::{from_data, Data, DeserializeError, Diagnostics, Format, ParsedData, ReadError};
 
 /// Interned strings.
 pub type StringId = string_interner::symbol::SymbolU16;
@@ -35,40 +35,129 @@ enum Options {
         /// Path to a file containing the surface term (`-` to read from stdin)
         #[clap(long = "term", name = "FILE", default_value = "-", parse(from_str))]
         term_input: Input,
-        /// Continue even if errors were encountered.
-        #[clap(long = "allow-errors")]
-        allow_errors: bool,
+        #[clap(flatten)]
+        diagnostics: DiagnosticOptions,
     },
     /// Elaborate a term, printing its normal form and type
     Norm {
         /// Path to a file containing the surface term (`-` to read from stdin)
         #[clap(long = "term", name = "FILE", default_value = "-", parse(from_str))]
         term_input: Input,
-        /// Continue even if errors were encountered.
-        #[clap(long = "allow-errors")]
-        allow_errors: bool,
+        #[clap(flatten)]
+        diagnostics: DiagnosticOptions,
     },
     /// Elaborate a term, printing its type
     Type {
         /// Path to a file containing the surface term (`-` to read from stdin)
         #[clap(long = "term", name = "FILE", default_value = "-", parse(from_str))]
         term_input: Input,
-        /// Continue even if errors were encountered. v
-        #[clap(long = "allow-errors")]
-        allow_errors: bool,
+        #[clap(flatten)]
+        diagnostics: DiagnosticOptions,
     },
     /// Manipulate binary data
     Data {
         /// Path to a file containing the surface term (`-` to read from stdin)
         #[clap(long = "format", name = "FILE", parse(from_str))]
         format_input: Input,
-        /// Continue even if errors were encountered
-        #[clap(long = "allow-errors")]
-        allow_errors: bool,
+        #[clap(flatten)]
+        diagnostics: DiagnosticOptions,
+        /// Print a hexdump of the binary data, annotated with the parsed fields
+        #[clap(long = "hexdump", conflicts_with = "coverage")]
+        hexdump: bool,
+        /// Print the regions of the binary data that were never read, or were
+        /// read more than once
+        #[clap(long = "coverage")]
+        coverage: bool,
+        #[clap(flatten)]
+        limits: LimitOptions,
         /// The binary file to read
         #[clap(name = "BINARY", parse(from_str))]
         binary_path: PathBuf, // TODO: parse multiple binary files?
     },
+    /// Compare the data parsed from two binary files
+    Diff {
+        /// Path to a file containing the surface term (`-` to read from stdin)
+        #[clap(long = "format", name = "FILE", parse(from_str))]
+        format_input: Input,
+        #[clap(flatten)]
+        diagnostics: DiagnosticOptions,
+        #[clap(flatten)]
+        limits: LimitOptions,
+        /// The binary file to compare against
+        #[clap(name = "OLD", parse(from_str))]
+        old_path: PathBuf,
+        /// The binary file to compare
+        #[clap(name = "NEW", parse(from_str))]
+        new_path: PathBuf,
+    },
+}
+
+/// Options controlling how errors are reported
+#[derive(clap::Args)]
+struct DiagnosticOptions {
+    /// Continue even if errors were encountered
+    #[clap(long = "allow-errors")]
+    allow_errors: bool,
+    /// How diagnostics should be emitted (`human` or `json`)
+    #[clap(
+        long = "message-format",
+        name = "FMT",
+        default_value = "human",
+        possible_values = ["human", "json"]
+    )]
+    message_format: fathom::MessageFormat,
+}
+
+impl DiagnosticOptions {
+    fn apply(&self, driver: &mut fathom::Driver<'_, '_>) {
+        driver.set_message_format(self.message_format);
+        driver.install_panic_hook();
+        driver.set_allow_errors(self.allow_errors);
+    }
+}
+
+/// Limits on the resources used when reading binary data
+#[derive(clap::Args)]
+struct LimitOptions {
+    /// The maximum number of elements allowed in a single array
+    /// [default: 4194304]
+    #[clap(long = "max-array-len", name = "ARRAY_LEN")]
+    max_array_len: Option<u64>,
+    /// The maximum number of values allowed to be read [default: 4194304]
+    #[clap(long = "max-values", name = "VALUES")]
+    max_values: Option<u64>,
+    /// The maximum number of links allowed to be read [default: 65536]
+    #[clap(long = "max-links", name = "LINKS")]
+    max_links: Option<u64>,
+    /// The maximum depth that formats are allowed to be nested [default: 256]
+    #[clap(long = "max-depth", name = "DEPTH")]
+    max_depth: Option<u64>,
+    /// The maximum number of bytes allowed to be read [default: 1073741824]
+    #[clap(long = "max-bytes-read", name = "BYTES")]
+    max_bytes_read: Option<u64>,
+    /// Only enforce the limits that are set explicitly. This should only be
+    /// used when reading trusted data.
+    #[clap(long = "no-default-limits")]
+    no_default_limits: bool,
+}
+
+impl LimitOptions {
+    fn to_limits(&self) -> fathom::core::binary::Limits {
+        use fathom::core::binary::Limits;
+
+        let default_limits = match self.no_default_limits {
+            true => Limits::UNLIMITED,
+            false => Limits::default(),
+        };
+
+        Limits {
+            max_array_len: self.max_array_len.or(default_limits.max_array_len),
+            max_values: self.max_values.or(default_limits.max_values),
+            max_links: self.max_links.or(default_limits.max_links),
+            max_depth: self.max_depth.or(default_limits.max_depth),
+            max_bytes_read: self.max_bytes_read.or(default_limits.max_bytes_read),
+        }
+    }
 }
 
 enum Input {
@@ -96,11 +185,10 @@ fn main() -> ! {
     match Options::parse() {
         Options::Elab {
             term_input,
-            allow_errors,
+            diagnostics,
         } => {
             let mut driver = fathom::Driver::new();
-            driver.install_panic_hook();
-            driver.set_allow_errors(allow_errors);
+            diagnostics.apply(&mut driver);
             driver.set_emit_width(get_pretty_width());
 
             let file_id = match term_input {
@@ -114,11 +202,10 @@ fn main() -> ! {
         }
         Options::Norm {
             term_input,
-            allow_errors,
+            diagnostics,
         } => {
             let mut driver = fathom::Driver::new();
-            driver.install_panic_hook();
-            driver.set_allow_errors(allow_errors);
+            diagnostics.apply(&mut driver);
             driver.set_emit_width(get_pretty_width());
 
             let file_id = match term_input {
@@ -132,11 +219,10 @@ fn main() -> ! {
         }
         Options::Type {
             term_input,
-            allow_errors,
+            diagnostics,
         } => {
             let mut driver = fathom::Driver::new();
-            driver.install_panic_hook();
-            driver.set_allow_errors(allow_errors);
+            diagnostics.apply(&mut driver);
             driver.set_emit_width(get_pretty_width());
 
             let file_id = match term_input {
@@ -150,21 +236,61 @@ fn main() -> ! {
         }
         Options::Data {
             format_input,
-            allow_errors,
+            diagnostics,
+            hexdump,
+            coverage,
+            limits,
             binary_path,
         } => {
             let mut driver = fathom::Driver::new();
-            driver.install_panic_hook();
-            driver.set_allow_errors(allow_errors);
+            diagnostics.apply(&mut driver);
+            driver.set_emit_width(get_pretty_width());
+            if hexdump {
+                driver.set_data_output(fathom::DataOutput::Hexdump);
+            } else if coverage {
+                driver.set_data_output(fathom::DataOutput::Coverage);
+            }
+            driver.set_binary_limits(limits.to_limits());
+
+            let file_id = match format_input {
+                Input::StdIn => driver.read_source("<stdin>", std::io::stdin()),
+                Input::File(path) => driver.read_source_path(&path),
+            };
+
+            let status = match driver.open_binary_path(&binary_path) {
+                Some(file) => driver.read_format(file_id, &mut BufReader::new(file)),
+                None => fathom::Status::Error,
+            };
+
+            std::process::exit(status.exit_code());
+        }
+        Options::Diff {
+            format_input,
+            diagnostics,
+            limits,
+            old_path,
+            new_path,
+        } => {
+            let mut driver = fathom::Driver::new();
+            diagnostics.apply(&mut driver);
             driver.set_emit_width(get_pretty_width());
+            driver.set_binary_limits(limits.to_limits());
 
             let file_id = match format_input {
                 Input::StdIn => driver.read_source("<stdin>", std::io::stdin()),
                 Input::File(path) => driver.read_source_path(&path),
             };
 
-            let mut reader = BufReader::new(std::fs::File::open(binary_path).unwrap()); // TODO: report errors
-            let status = driver.read_format(file_id, &mut reader);
+            let old_file = driver.open_binary_path(&old_path);
+            let new_file = driver.open_binary_path(&new_path);
+            let status = match (old_file, new_file) {
+                (Some(old_file), Some(new_file)) => driver.diff_format(
+                    file_id,
+                    &mut BufReader::new(old_file),
+                    &mut BufReader::new(new_file),
+                ),
+                (_, _) => fathom::Status::Error,
+            };
 
             std::process::exit(status.exit_code());
         }
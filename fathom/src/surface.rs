@@ -5,6 +5,7 @@ use lalrpop_util::lalrpop_mod;
 use scoped_arena::Scope;
 use std::cell::RefCell;
 
+use crate::core::{Prim, UniverseLevel};
 use crate::source::{ByteRange, FileId};
 use crate::{StringId, StringInterner};
 
@@ -17,7 +18,7 @@ pub mod elaboration;
 
 /// Surface patterns.
 #[derive(Debug, Clone)]
-pub enum Pattern<Range> {
+pub enum Pattern<'arena, Range> {
     /// Named patterns, eg. `x`, `true`, `false`
     Name(Range, StringId),
     /// Placeholder patterns, eg. `_`
@@ -26,19 +27,19 @@ pub enum Pattern<Range> {
     ///
     /// As with [term literals][Term::StringLiteral], these will be parsed fully
     /// during [elaboration].
-    StringLiteral(Range, StringId),
+    StringLiteral(Range, &'arena str),
     /// Number literal patterns, eg. `1`, `0x00FF`
     ///
     /// As with [term literals][Term::NumberLiteral], these will be parsed fully
     /// during [elaboration].
-    NumberLiteral(Range, StringId),
+    NumberLiteral(Range, &'arena str),
     /// Boolean literal patterns
     BooleanLiteral(Range, bool),
     // TODO: Record literal patterns
     // RecordLiteral(Range, &'arena [((ByteRange, StringId), Pattern<'arena, Range>)]),
 }
 
-impl<Range: Clone> Pattern<Range> {
+impl<'arena, Range: Clone> Pattern<'arena, Range> {
     fn range(&self) -> Range {
         match self {
             Pattern::Name(range, _)
@@ -68,7 +69,7 @@ pub enum Term<'arena, Range> {
     /// Let expressions.
     Let(
         Range,
-        Pattern<Range>,
+        Pattern<'arena, Range>,
         Option<&'arena Term<'arena, Range>>,
         &'arena Term<'arena, Range>,
         &'arena Term<'arena, Range>,
@@ -77,10 +78,10 @@ pub enum Term<'arena, Range> {
     Match(
         Range,
         &'arena Term<'arena, Range>,
-        &'arena [(Pattern<Range>, Term<'arena, Range>)],
+        &'arena [(Pattern<'arena, Range>, Term<'arena, Range>)],
     ),
-    /// The type of types.
-    Universe(Range),
+    /// The type of types, at a given universe level.
+    Universe(Range, UniverseLevel),
     /// Arrow types.
     Arrow(
         Range,
@@ -90,14 +91,14 @@ pub enum Term<'arena, Range> {
     /// Dependent function types.
     FunType(
         Range,
-        Pattern<Range>,
+        Pattern<'arena, Range>,
         Option<&'arena Term<'arena, Range>>,
         &'arena Term<'arena, Range>,
     ),
     /// Function literals.
     FunLiteral(
         Range,
-        Pattern<Range>,
+        Pattern<'arena, Range>,
         Option<&'arena Term<'arena, Range>>,
         &'arena Term<'arena, Range>,
     ),
@@ -107,6 +108,15 @@ pub enum Term<'arena, Range> {
         &'arena Term<'arena, Range>,
         &'arena Term<'arena, Range>,
     ),
+    /// Unary operator applications.
+    UnOp(Range, (Range, UnOp), &'arena Term<'arena, Range>),
+    /// Binary operator applications.
+    BinOp(
+        Range,
+        &'arena Term<'arena, Range>,
+        (Range, BinOp),
+        &'arena Term<'arena, Range>,
+    ),
     /// Dependent record types.
     RecordType(Range, &'arena [((Range, StringId), Term<'arena, Range>)]),
     /// Record literals.
@@ -119,14 +129,14 @@ pub enum Term<'arena, Range> {
     ArrayLiteral(Range, &'arena [Term<'arena, Range>]),
     /// String literal.
     ///
-    /// These are stored as strings, and will be parsed during [elaboration]
-    /// once the target type is known.
-    StringLiteral(Range, StringId),
+    /// These are stored as strings, including their quotes and any suffix,
+    /// and will be parsed during [elaboration] once the target type is known.
+    StringLiteral(Range, &'arena str),
     /// Number literals.
     ///
     /// These are stored as strings, and will be parsed during [elaboration]
     /// once the target type is known.
-    NumberLiteral(Range, StringId),
+    NumberLiteral(Range, &'arena str),
     /// Boolean literals.
     BooleanLiteral(Range, bool),
     /// Record format.
@@ -147,11 +157,13 @@ impl<'arena, Range: Clone> Term<'arena, Range> {
             | Term::Ann(range, _, _)
             | Term::Let(range, _, _, _, _)
             | Term::Match(range, _, _)
-            | Term::Universe(range)
+            | Term::Universe(range, _)
             | Term::Arrow(range, _, _)
             | Term::FunType(range, _, _, _)
             | Term::FunLiteral(range, _, _, _)
             | Term::App(range, _, _)
+            | Term::UnOp(range, _, _)
+            | Term::BinOp(range, _, _, _)
             | Term::RecordType(range, _)
             | Term::RecordLiteral(range, _)
             | Term::UnitLiteral(range)
@@ -167,8 +179,243 @@ impl<'arena, Range: Clone> Term<'arena, Range> {
     }
 }
 
+/// Unary operators.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UnOp {
+    /// Logical or bitwise negation, eg. `!x`
+    Not,
+    /// Arithmetic negation, eg. `-x`
+    Neg,
+}
+
+impl UnOp {
+    /// The primitive operations that unary operators are elaborated to, along
+    /// with the type of their operands.
+    const PRIMS: &'static [(UnOp, Prim, Prim)] = &[
+        (UnOp::Not, Prim::BoolType, Prim::BoolNot),
+        (UnOp::Not, Prim::U8Type, Prim::U8Not),
+        (UnOp::Not, Prim::U16Type, Prim::U16Not),
+        (UnOp::Not, Prim::U32Type, Prim::U32Not),
+        (UnOp::Not, Prim::U64Type, Prim::U64Not),
+        (UnOp::Neg, Prim::S8Type, Prim::S8Neg),
+        (UnOp::Neg, Prim::S16Type, Prim::S16Neg),
+        (UnOp::Neg, Prim::S32Type, Prim::S32Neg),
+        (UnOp::Neg, Prim::S64Type, Prim::S64Neg),
+        (UnOp::Neg, Prim::F32Type, Prim::F32Neg),
+        (UnOp::Neg, Prim::F64Type, Prim::F64Neg),
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            UnOp::Not => "!",
+            UnOp::Neg => "-",
+        }
+    }
+
+    /// Lookup the primitive operation that implements this operator for
+    /// operands of the given type.
+    pub fn prim(self, operand_type: Prim) -> Option<Prim> {
+        (UnOp::PRIMS.iter())
+            .find(|(op, r#type, _)| *op == self && *r#type == operand_type)
+            .map(|(_, _, prim)| *prim)
+    }
+
+    /// Lookup the operator that a primitive operation can be displayed with.
+    pub fn from_prim(prim: Prim) -> Option<UnOp> {
+        (UnOp::PRIMS.iter())
+            .find(|(_, _, op_prim)| *op_prim == prim)
+            .map(|(op, _, _)| *op)
+    }
+}
+
+/// Binary operators.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BinOp {
+    /// Logical or, eg. `x || y`
+    Or,
+    /// Logical and, eg. `x && y`
+    And,
+    /// Equality, eg. `x == y`
+    Eq,
+    /// Inequality, eg. `x != y`
+    Neq,
+    /// Less than, eg. `x < y`
+    Lt,
+    /// Less than or equal, eg. `x <= y`
+    Lte,
+    /// Greater than, eg. `x > y`
+    Gt,
+    /// Greater than or equal, eg. `x >= y`
+    Gte,
+    /// Bitwise or, eg. `x | y`
+    BitOr,
+    /// Bitwise (or logical) exclusive or, eg. `x ^ y`
+    BitXor,
+    /// Bitwise and, eg. `x & y`
+    BitAnd,
+    /// Shift left, eg. `x << y`
+    Shl,
+    /// Shift right, eg. `x >> y`
+    Shr,
+    /// Addition, eg. `x + y`
+    Add,
+    /// Subtraction, eg. `x - y`
+    Sub,
+    /// Multiplication, eg. `x * y`
+    Mul,
+    /// Division, eg. `x / y`
+    Div,
+}
+
+macro_rules! bin_op_prims {
+    ($(
+        $Type:ident {
+            $($Op:ident => $Prim:ident),* $(,)?
+        }
+    ),* $(,)?) => {
+        &[$($((BinOp::$Op, Prim::$Type, Prim::$Prim),)*)*]
+    };
+}
+
+impl BinOp {
+    /// The primitive operations that binary operators are elaborated to, along
+    /// with the type of their left-hand operands. The type of the right-hand
+    /// operand is the same as the left, except for shifts (which take a `U8`)
+    /// and positions (which can be offset by any unsigned integer).
+    const PRIMS: &'static [(BinOp, Prim, Prim)] = bin_op_prims![
+        BoolType {
+            Or => BoolOr, And => BoolAnd, Eq => BoolEq, Neq => BoolNeq, BitXor => BoolXor,
+        },
+        U8Type {
+            Eq => U8Eq, Neq => U8Neq, Lt => U8Lt, Lte => U8Lte, Gt => U8Gt, Gte => U8Gte,
+            BitOr => U8Or, BitXor => U8Xor, BitAnd => U8And, Shl => U8Shl, Shr => U8Shr,
+            Add => U8Add, Sub => U8Sub, Mul => U8Mul, Div => U8Div,
+        },
+        U16Type {
+            Eq => U16Eq, Neq => U16Neq, Lt => U16Lt, Lte => U16Lte, Gt => U16Gt, Gte => U16Gte,
+            BitOr => U16Or, BitXor => U16Xor, BitAnd => U16And, Shl => U16Shl, Shr => U16Shr,
+            Add => U16Add, Sub => U16Sub, Mul => U16Mul, Div => U16Div,
+        },
+        U32Type {
+            Eq => U32Eq, Neq => U32Neq, Lt => U32Lt, Lte => U32Lte, Gt => U32Gt, Gte => U32Gte,
+            BitOr => U32Or, BitXor => U32Xor, BitAnd => U32And, Shl => U32Shl, Shr => U32Shr,
+            Add => U32Add, Sub => U32Sub, Mul => U32Mul, Div => U32Div,
+        },
+        U64Type {
+            Eq => U64Eq, Neq => U64Neq, Lt => U64Lt, Lte => U64Lte, Gt => U64Gt, Gte => U64Gte,
+            BitOr => U64Or, BitXor => U64Xor, BitAnd => U64And, Shl => U64Shl, Shr => U64Shr,
+            Add => U64Add, Sub => U64Sub, Mul => U64Mul, Div => U64Div,
+        },
+        S8Type {
+            Eq => S8Eq, Neq => S8Neq, Lt => S8Lt, Lte => S8Lte, Gt => S8Gt, Gte => S8Gte,
+            Add => S8Add, Sub => S8Sub, Mul => S8Mul, Div => S8Div,
+        },
+        S16Type {
+            Eq => S16Eq, Neq => S16Neq, Lt => S16Lt, Lte => S16Lte, Gt => S16Gt, Gte => S16Gte,
+            Add => S16Add, Sub => S16Sub, Mul => S16Mul, Div => S16Div,
+        },
+        S32Type {
+            Eq => S32Eq, Neq => S32Neq, Lt => S32Lt, Lte => S32Lte, Gt => S32Gt, Gte => S32Gte,
+            Add => S32Add, Sub => S32Sub, Mul => S32Mul, Div => S32Div,
+        },
+        S64Type {
+            Eq => S64Eq, Neq => S64Neq, Lt => S64Lt, Lte => S64Lte, Gt => S64Gt, Gte => S64Gte,
+            Add => S64Add, Sub => S64Sub, Mul => S64Mul, Div => S64Div,
+        },
+        F32Type {
+            Eq => F32Eq, Neq => F32Neq, Lt => F32Lt, Lte => F32Lte, Gt => F32Gt, Gte => F32Gte,
+            Add => F32Add, Sub => F32Sub, Mul => F32Mul, Div => F32Div,
+        },
+        F64Type {
+            Eq => F64Eq, Neq => F64Neq, Lt => F64Lt, Lte => F64Lte, Gt => F64Gt, Gte => F64Gte,
+            Add => F64Add, Sub => F64Sub, Mul => F64Mul, Div => F64Div,
+        },
+    ];
+
+    /// The position offsetting operations, along with the type of the offset.
+    const POS_PRIMS: &'static [(Prim, Prim)] = &[
+        (Prim::U8Type, Prim::PosAddU8),
+        (Prim::U16Type, Prim::PosAddU16),
+        (Prim::U32Type, Prim::PosAddU32),
+        (Prim::U64Type, Prim::PosAddU64),
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Or => "||",
+            BinOp::And => "&&",
+            BinOp::Eq => "==",
+            BinOp::Neq => "!=",
+            BinOp::Lt => "<",
+            BinOp::Lte => "<=",
+            BinOp::Gt => ">",
+            BinOp::Gte => ">=",
+            BinOp::BitOr => "|",
+            BinOp::BitXor => "^",
+            BinOp::BitAnd => "&",
+            BinOp::Shl => "<<",
+            BinOp::Shr => ">>",
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+        }
+    }
+
+    /// Returns `true` if the operator always produces a `Bool`, regardless
+    /// of the types of its operands.
+    pub fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            BinOp::Eq | BinOp::Neq | BinOp::Lt | BinOp::Lte | BinOp::Gt | BinOp::Gte,
+        )
+    }
+
+    /// Returns `true` if the right operand is always a `U8`.
+    pub fn is_shift(self) -> bool {
+        matches!(self, BinOp::Shl | BinOp::Shr)
+    }
+
+    /// Lookup the primitive operation that implements this operator for
+    /// operands of the given types.
+    pub fn prim(self, lhs_type: Prim, rhs_type: Prim) -> Option<Prim> {
+        if self == BinOp::Add && lhs_type == Prim::PosType {
+            return (BinOp::POS_PRIMS.iter())
+                .find(|(r#type, _)| *r#type == rhs_type)
+                .map(|(_, prim)| *prim);
+        }
+
+        let expected_rhs_type = if self.is_shift() {
+            Prim::U8Type
+        } else {
+            lhs_type
+        };
+        if rhs_type != expected_rhs_type {
+            return None;
+        }
+
+        (BinOp::PRIMS.iter())
+            .find(|(op, r#type, _)| *op == self && *r#type == lhs_type)
+            .map(|(_, _, prim)| *prim)
+    }
+
+    /// Lookup the operator that a primitive operation can be displayed with.
+    pub fn from_prim(prim: Prim) -> Option<BinOp> {
+        if BinOp::POS_PRIMS
+            .iter()
+            .any(|(_, pos_prim)| *pos_prim == prim)
+        {
+            return Some(BinOp::Add);
+        }
+
+        (BinOp::PRIMS.iter())
+            .find(|(_, _, op_prim)| *op_prim == prim)
+            .map(|(op, _, _)| *op)
+    }
+}
+
 impl<'arena> Term<'arena, ByteRange> {
-    /// Parse a term from the `source` string, interning strings to the
+    /// Parse a term from the `source` string, interning names to the
     /// supplied `interner` and allocating nodes to the `arena`.
     pub fn parse<'source>(
         interner: &RefCell<StringInterner>,
@@ -314,6 +561,6 @@ mod tests {
     fn no_drop() {
         assert!(!std::mem::needs_drop::<Term<'_, ()>>());
         assert!(!std::mem::needs_drop::<Term<'_, StringId>>());
-        assert!(!std::mem::needs_drop::<Pattern<StringId>>());
+        assert!(!std::mem::needs_drop::<Pattern<'_, StringId>>());
     }
 }
@@ -2,11 +2,14 @@
 
 use scoped_arena::Scope;
 use std::cell::RefCell;
+use std::sync::Arc;
 
+use crate::alloc;
+use crate::core::semantics::{ArcValue, ElimContext, QuoteContext, Value};
 use crate::core::UIntStyle;
-use crate::env::{self, EnvLen, GlobalVar, LocalVar, UniqueEnv};
+use crate::env::{self, EnvLen, GlobalVar, LocalVar, SliceEnv, UniqueEnv};
 use crate::surface::elaboration::FlexSource;
-use crate::surface::{Pattern, Term};
+use crate::surface::{BinOp, Pattern, Term, UnOp};
 use crate::{core, StringId, StringInterner};
 
 /// Distillation context.
@@ -18,6 +21,11 @@ pub struct Context<'interner, 'arena, 'env> {
     rigid_names: &'env mut UniqueEnv<Option<StringId>>,
     /// Flexible sources.
     flexible_sources: &'env UniqueEnv<FlexSource>,
+    /// Solutions to flexible variables, which are substituted into the
+    /// distilled terms in place of the variables.
+    flexible_exprs: &'env SliceEnv<Option<ArcValue<'env>>>,
+    /// Names chosen for flexible variables, based on their sources.
+    flexible_names: UniqueEnv<Option<StringId>>,
 }
 
 impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
@@ -27,16 +35,22 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         scope: &'arena Scope<'arena>,
         rigid_names: &'env mut UniqueEnv<Option<StringId>>,
         flexible_sources: &'env UniqueEnv<FlexSource>,
+        flexible_exprs: &'env SliceEnv<Option<ArcValue<'env>>>,
     ) -> Context<'interner, 'arena, 'env> {
+        let mut flexible_names = UniqueEnv::new();
+        flexible_names.resize(flexible_sources.len(), None);
+
         Context {
             interner,
             scope,
             rigid_names,
             flexible_sources,
+            flexible_exprs,
+            flexible_names,
         }
     }
 
-    fn rigid_len(&mut self) -> EnvLen {
+    fn rigid_len(&self) -> EnvLen {
         self.rigid_names.len()
     }
 
@@ -44,17 +58,79 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         self.rigid_names.get_local(var).copied().flatten()
     }
 
-    fn push_rigid(&mut self, name: Option<StringId>) -> StringId {
-        let name = name.unwrap_or_else(|| {
-            self.interner.borrow_mut().get_or_intern_static("_") // TODO: choose a better name?
-        });
+    fn get_rigid_global_name(&self, var: GlobalVar) -> Option<StringId> {
+        self.rigid_names.get_global(var).copied().flatten()
+    }
+
+    /// Push a binder onto the rigid environment, choosing a name for it that
+    /// will not capture any of the variables or primitives referenced in
+    /// `body`, which should be distilled with the binder in scope.
+    ///
+    /// Unnamed binders are only given names if they are referenced in `body`,
+    /// returning `None` otherwise.
+    fn push_binder(&mut self, name: Option<StringId>, body: &core::Term<'_>) -> Option<StringId> {
+        let binder_var = self.rigid_len().next_global();
+        let free_vars = {
+            let mut body_len = self.rigid_len();
+            body_len.push();
+            body.free_vars(body_len)
+        };
+
+        let name = match name {
+            None if !free_vars.rigid_vars.contains(&binder_var) => None,
+            None => Some(self.fresh_name("x", &free_vars)),
+            Some(name) if !self.is_captured(name, &free_vars) => Some(name),
+            Some(name) => {
+                let base = self.interner.borrow().resolve(name).unwrap().to_owned();
+                Some(self.fresh_name(&base, &free_vars))
+            }
+        };
 
-        // TODO: avoid globals
-        // TODO: ensure we chose a correctly bound name
-        self.rigid_names.push(Some(name));
+        self.rigid_names.push(name);
         name
     }
 
+    /// Push an entry of a telescope onto the rigid environment. These are
+    /// referred to by their labels, so they can't be renamed.
+    fn push_label(&mut self, label: StringId) {
+        self.rigid_names.push(Some(label));
+    }
+
+    /// Choose a name based on `base` that will not capture any of the
+    /// `free_vars` of the body of a new binder.
+    fn fresh_name(&mut self, base: &str, free_vars: &core::FreeVars) -> StringId {
+        let candidates =
+            std::iter::once(base.to_owned()).chain((1..).map(|index| format!("{}{}", base, index)));
+
+        for candidate in candidates {
+            let name = self.interner.borrow_mut().get_or_intern(candidate);
+            if !self.is_captured(name, free_vars) {
+                return name;
+            }
+        }
+
+        unreachable!("ran out of names")
+    }
+
+    /// Returns `true` if binding a new variable named `name` over a body with
+    /// the given `free_vars` would capture a variable or primitive that is
+    /// referenced in the body with the same name, or if the new binder would
+    /// itself be captured by a field label in the body.
+    fn is_captured(&self, name: StringId, free_vars: &core::FreeVars) -> bool {
+        let binder_var = self.rigid_len().next_global();
+
+        let captures_rigid = (free_vars.rigid_vars.iter())
+            .any(|var| *var != binder_var && self.get_rigid_global_name(*var) == Some(name));
+        // Labels can't be renamed, so the binder must avoid them instead
+        let is_label_captured = free_vars.labelled_vars.contains(&(name, binder_var));
+
+        captures_rigid || is_label_captured || {
+            let interner = self.interner.borrow();
+            let name = interner.resolve(name).unwrap();
+            free_vars.prims.iter().any(|prim| prim.name() == name)
+        }
+    }
+
     fn pop_rigid(&mut self) {
         self.rigid_names.pop();
     }
@@ -63,11 +139,83 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         self.rigid_names.truncate(len);
     }
 
-    fn get_flexible_name(&self, var: GlobalVar) -> Option<StringId> {
-        match self.flexible_sources.get_global(var)? {
-            FlexSource::HoleExpr(_, name) => Some(*name),
-            _ => None,
+    /// Quote the solution of a flexible variable back into a term in the
+    /// current rigid environment, applying it to the parameters in
+    /// `rigid_infos`. Returns `None` if the variable is unsolved.
+    fn quote_flexible_solution(
+        &self,
+        var: GlobalVar,
+        rigid_infos: &[core::EntryInfo],
+    ) -> Option<core::Term<'arena>> {
+        let mut expr = self.flexible_exprs.get_global(var)?.clone()?;
+
+        let elim_context = ElimContext::new(self.flexible_exprs);
+        for (var, info) in Iterator::zip(env::global_vars(), rigid_infos.iter()) {
+            match info {
+                core::EntryInfo::Definition => {}
+                core::EntryInfo::Parameter => {
+                    let input_expr = Arc::new(Value::rigid_var(var));
+                    expr = elim_context.fun_app(expr, input_expr);
+                }
+            }
         }
+
+        let mut quote_context =
+            QuoteContext::new(self.scope, self.rigid_len(), self.flexible_exprs);
+        Some(quote_context.quote(&expr))
+    }
+
+    /// Get the name of an unsolved flexible variable, choosing a name based on
+    /// its source if it has not been named yet.
+    fn get_flexible_name(&mut self, var: GlobalVar) -> Option<StringId> {
+        if let Some(name) = self.flexible_names.get_global(var)? {
+            return Some(*name);
+        }
+
+        let name = {
+            let mut interner = self.interner.borrow_mut();
+            let mut typed_name = |name: StringId| {
+                let name = format!("{}_type", interner.resolve(name).unwrap());
+                interner.get_or_intern(name)
+            };
+
+            let base = match self.flexible_sources.get_global(var)? {
+                FlexSource::HoleExpr(_, name) => {
+                    // Holes keep the names they were given in the source code
+                    self.flexible_names.set_global(var, Some(*name));
+                    return Some(*name);
+                }
+                FlexSource::HoleType(_, name) => typed_name(*name),
+                FlexSource::NamedPatternType(_, name) => typed_name(*name),
+                // Placeholders are distilled back to placeholders, so that they
+                // are inferred again if the distilled term is elaborated
+                FlexSource::PlaceholderExpr(_) => return None,
+                FlexSource::PlaceholderType(_) => interner.get_or_intern_static("placeholder_type"),
+                FlexSource::PlaceholderPatternType(_) => {
+                    interner.get_or_intern_static("pattern_type")
+                }
+                FlexSource::MatchOutputType(_) => interner.get_or_intern_static("match_type"),
+                FlexSource::FunInputType(_) => interner.get_or_intern_static("input_type"),
+                FlexSource::FunOutputType(_) => interner.get_or_intern_static("output_type"),
+                FlexSource::ReportedErrorType(_) => interner.get_or_intern_static("error_type"),
+                FlexSource::Universe(_) => interner.get_or_intern_static("universe"),
+            };
+
+            // Avoid reusing the names chosen for other flexible variables
+            let is_used = |name| self.flexible_names.iter().any(|n| *n == Some(name));
+            let base_str = interner.resolve(base).unwrap().to_owned();
+            let mut name = base;
+            for index in 1.. {
+                if !is_used(name) {
+                    break;
+                }
+                name = interner.get_or_intern(format!("{}{}", base_str, index));
+            }
+            name
+        };
+
+        self.flexible_names.set_global(var, Some(name));
+        Some(name)
     }
 
     fn check_number_literal_styled<T: core::UIntStyled<N>, const N: usize>(
@@ -75,17 +223,26 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         number: T,
         style: UIntStyle,
     ) -> Term<'arena, ()> {
-        let string = style.format(number);
-        let number = self.interner.borrow_mut().get_or_intern(string);
-        Term::NumberLiteral((), number)
+        let number = style.format(number);
+        Term::NumberLiteral((), alloc::str_to_scope(self.scope, &number))
     }
 
     fn check_number_literal<T: std::fmt::Display>(&mut self, number: T) -> Term<'arena, ()> {
-        let number = self.interner.borrow_mut().get_or_intern(number.to_string());
-        Term::NumberLiteral((), number)
+        let number = number.to_string();
+        Term::NumberLiteral((), alloc::str_to_scope(self.scope, &number))
+    }
+
+    fn check_string_literal(&mut self, string: &str) -> Term<'arena, ()> {
+        Term::StringLiteral((), self.escape_string(string))
     }
 
-    fn check_boolean_pattern(&mut self, boolean: bool) -> Pattern<()> {
+    /// Escape a string so that it can be printed as a string literal.
+    fn escape_string(&mut self, string: &str) -> &'arena str {
+        let escaped = format!("\"{}\"", string.escape_debug());
+        alloc::str_to_scope(self.scope, &escaped)
+    }
+
+    fn check_boolean_pattern(&mut self, boolean: bool) -> Pattern<'arena, ()> {
         let name = match boolean {
             true => self.interner.borrow_mut().get_or_intern("true"),
             false => self.interner.borrow_mut().get_or_intern("false"),
@@ -93,23 +250,22 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         Pattern::Name((), name)
     }
 
-    fn check_number_pattern<T: std::fmt::Display>(&mut self, number: T) -> Pattern<()> {
-        let number = self.interner.borrow_mut().get_or_intern(number.to_string());
-        Pattern::NumberLiteral((), number)
+    fn check_number_pattern<T: std::fmt::Display>(&mut self, number: T) -> Pattern<'arena, ()> {
+        let number = number.to_string();
+        Pattern::NumberLiteral((), alloc::str_to_scope(self.scope, &number))
     }
 
     fn check_number_pattern_styled<T: core::UIntStyled<N>, const N: usize>(
         &mut self,
         number: T,
         style: UIntStyle,
-    ) -> Pattern<()> {
+    ) -> Pattern<'arena, ()> {
         // TODO: Share with check_number_literal_styled
-        let string = style.format(number);
-        let number = self.interner.borrow_mut().get_or_intern(string);
-        Pattern::NumberLiteral((), number)
+        let number = style.format(number);
+        Pattern::NumberLiteral((), alloc::str_to_scope(self.scope, &number))
     }
 
-    fn check_constant_pattern(&mut self, r#const: &core::Const) -> Pattern<()> {
+    fn check_constant_pattern(&mut self, r#const: &core::Const) -> Pattern<'arena, ()> {
         match r#const {
             core::Const::Bool(boolean) => self.check_boolean_pattern(*boolean),
             core::Const::U8(number, style) => self.check_number_pattern_styled(number, *style),
@@ -124,11 +280,12 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
             core::Const::F64(number) => self.check_number_pattern(number),
             core::Const::Pos(number) => self.check_number_pattern(number),
             core::Const::Ref(number) => self.check_number_pattern(number),
+            core::Const::String(string) => Pattern::StringLiteral((), self.escape_string(string)),
         }
     }
 
     fn synth_prim(&mut self, prim: core::Prim) -> Term<'arena, ()> {
-        // FIXME: Check if shadowed
+        // Binders are renamed in `push_binder` to avoid shadowing primitives
         let name = self.interner.borrow_mut().get_or_intern_static(prim.name());
         Term::Name((), name)
     }
@@ -144,6 +301,39 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         Term::Ann((), self.scope.to_scope(expr), self.scope.to_scope(r#type))
     }
 
+    /// Infinities and `NaN`s can't be written as number literals, so we
+    /// distill them to divisions by zero instead, for example `f64_div 1 0`.
+    fn synth_non_finite_literal(
+        &mut self,
+        number: f64,
+        (div_prim, neg_prim): (core::Prim, core::Prim),
+    ) -> Term<'arena, ()> {
+        let numerator = if number.is_nan() { "0" } else { "1" };
+        let denominator = "0";
+
+        let div_expr = self.synth_prim(div_prim);
+        let div_expr = Term::App(
+            (),
+            self.scope.to_scope(Term::App(
+                (),
+                self.scope.to_scope(div_expr),
+                self.scope.to_scope(Term::NumberLiteral((), numerator)),
+            )),
+            self.scope.to_scope(Term::NumberLiteral((), denominator)),
+        );
+
+        if number.is_sign_negative() && number.is_infinite() {
+            let neg_expr = self.synth_prim(neg_prim);
+            Term::App(
+                (),
+                self.scope.to_scope(neg_expr),
+                self.scope.to_scope(div_expr),
+            )
+        } else {
+            div_expr
+        }
+    }
+
     fn synth_number_literal_styled<T: core::UIntStyled<N>, const N: usize>(
         &mut self,
         number: T,
@@ -167,26 +357,26 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
                 let def_type = self.synth(def_type);
                 let def_expr = self.check(def_expr);
 
-                let def_name = self.push_rigid(*def_name);
+                let def_name = self.push_binder(*def_name, output_expr);
                 let output_expr = self.check(output_expr);
                 self.pop_rigid();
 
                 Term::Let(
                     (),
-                    Pattern::Name((), def_name),
+                    binder_pattern(def_name),
                     Some(self.scope.to_scope(def_type)),
                     self.scope.to_scope(def_expr),
                     self.scope.to_scope(output_expr),
                 )
             }
             core::Term::FunLit(input_name, output_expr) => {
-                let input_name = self.push_rigid(*input_name);
+                let input_name = self.push_binder(*input_name, output_expr);
                 let output_expr = self.check(output_expr);
                 self.pop_rigid();
 
                 Term::FunLiteral(
                     (),
-                    Pattern::Name((), input_name),
+                    binder_pattern(input_name),
                     None,
                     self.scope.to_scope(output_expr),
                 )
@@ -217,21 +407,36 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
                 core::Const::S16(number) => self.check_number_literal(number),
                 core::Const::S32(number) => self.check_number_literal(number),
                 core::Const::S64(number) => self.check_number_literal(number),
+                core::Const::F32(number) if !number.is_finite() => {
+                    let prims = (core::Prim::F32Div, core::Prim::F32Neg);
+                    self.synth_non_finite_literal(f64::from(*number), prims)
+                }
+                core::Const::F64(number) if !number.is_finite() => {
+                    let prims = (core::Prim::F64Div, core::Prim::F64Neg);
+                    self.synth_non_finite_literal(*number, prims)
+                }
                 core::Const::F32(number) => self.check_number_literal(number),
                 core::Const::F64(number) => self.check_number_literal(number),
                 core::Const::Pos(number) => self.check_number_literal(number),
                 core::Const::Ref(number) => self.check_number_literal(number),
+                core::Const::String(string) => self.check_string_literal(string),
             },
+            core::Term::FunApp(head_expr, input_expr) => {
+                match self.check_op_app(head_expr, input_expr) {
+                    Some(term) => term,
+                    None => self.synth(core_term),
+                }
+            }
             core::Term::ConstMatch(head_expr, branches, default_expr) => {
                 let head_expr = self.synth(head_expr);
                 match default_expr {
                     Some(default_expr) => {
                         let default_branch = {
-                            let name = self.push_rigid(None);
+                            let name = self.push_binder(None, default_expr);
                             let default_expr = self.check(default_expr);
                             self.pop_rigid();
 
-                            (Pattern::Name((), name), default_expr)
+                            (binder_pattern(name), default_expr)
                         };
 
                         Term::Match(
@@ -272,13 +477,23 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         match core_term {
             core::Term::RigidVar(var) => match self.get_rigid_name(*var) {
                 Some(name) => Term::Name((), name),
-                None => todo!("misbound variable"), // TODO: error?
-            },
-            core::Term::FlexibleVar(var) => match self.get_flexible_name(*var) {
-                Some(name) => Term::Hole((), name),
+                // Binders pushed by `push_binder` are always named if they are
+                // referenced, so this variable must have been bound without a
+                // name outside of the term being distilled.
                 None => Term::Placeholder(()),
             },
+            core::Term::FlexibleVar(var) => match self.quote_flexible_solution(*var, &[]) {
+                Some(expr) => self.synth(&expr),
+                None => match self.get_flexible_name(*var) {
+                    Some(name) => Term::Hole((), name),
+                    None => Term::Placeholder(()),
+                },
+            },
             core::Term::FlexibleInsertion(var, rigid_infos) => {
+                if let Some(expr) = self.quote_flexible_solution(*var, rigid_infos) {
+                    return self.synth(&expr);
+                }
+
                 let mut head_expr = self.synth(&core::Term::FlexibleVar(*var));
 
                 for (var, info) in Iterator::zip(env::global_vars(), rigid_infos.iter()) {
@@ -308,47 +523,62 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
                 let def_type = self.synth(def_type);
                 let def_expr = self.check(def_expr);
 
-                let def_name = self.push_rigid(*def_name);
+                let def_name = self.push_binder(*def_name, output_expr);
                 let output_expr = self.synth(output_expr);
                 self.pop_rigid();
 
                 Term::Let(
                     (),
-                    Pattern::Name((), def_name),
+                    binder_pattern(def_name),
                     Some(self.scope.to_scope(def_type)),
                     self.scope.to_scope(def_expr),
                     self.scope.to_scope(output_expr),
                 )
             }
-            core::Term::Universe => Term::Universe(()),
+            core::Term::Universe(level) => Term::Universe((), *level),
             core::Term::FunType(input_name, input_type, output_type) => {
                 let input_type = self.check(input_type);
 
-                let input_name = self.push_rigid(*input_name);
+                if !output_type.references_local(LocalVar::last()) {
+                    self.rigid_names.push(None);
+                    let output_type = self.check(output_type);
+                    self.pop_rigid();
+
+                    return Term::Arrow(
+                        (),
+                        self.scope.to_scope(input_type),
+                        self.scope.to_scope(output_type),
+                    );
+                }
+
+                let input_name = self.push_binder(*input_name, output_type);
                 let output_type = self.check(output_type);
                 self.pop_rigid();
 
-                // TODO: distill to arrow if `input_name` is not bound in `output_type`
                 Term::FunType(
                     (),
-                    Pattern::Name((), input_name),
+                    binder_pattern(input_name),
                     Some(self.scope.to_scope(input_type)),
                     self.scope.to_scope(output_type),
                 )
             }
             core::Term::FunLit(input_name, output_expr) => {
-                let input_name = self.push_rigid(*input_name);
+                let input_name = self.push_binder(*input_name, output_expr);
                 let output_expr = self.synth(output_expr);
                 self.pop_rigid();
 
                 Term::FunLiteral(
                     (),
-                    Pattern::Name((), input_name),
+                    binder_pattern(input_name),
                     None,
                     self.scope.to_scope(output_expr),
                 )
             }
             core::Term::FunApp(head_expr, input_expr) => {
+                if let Some(term) = self.synth_op_app(head_expr, input_expr) {
+                    return term;
+                }
+
                 let head_expr = self.synth(head_expr);
                 let input_expr = self.check(input_expr);
 
@@ -359,14 +589,14 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
                 )
             }
             core::Term::RecordType(labels, _) if labels.is_empty() => {
-                Term::Ann((), &Term::UnitLiteral(()), &Term::Universe(()))
+                Term::Ann((), &Term::UnitLiteral(()), &Term::Universe((), 0))
             }
             core::Term::RecordType(labels, types) => {
                 let initial_rigid_len = self.rigid_len();
                 let type_fields = (self.scope).to_scope_from_iter(
                     Iterator::zip(labels.iter(), types.iter()).map(|(label, r#type)| {
                         let r#type = self.check(r#type);
-                        self.push_rigid(Some(*label));
+                        self.push_label(*label);
                         (((), *label), r#type)
                     }),
                 );
@@ -424,21 +654,34 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
                 core::Const::S16(number) => self.synth_number_literal(number, core::Prim::S16Type),
                 core::Const::S32(number) => self.synth_number_literal(number, core::Prim::S32Type),
                 core::Const::S64(number) => self.synth_number_literal(number, core::Prim::S64Type),
+                core::Const::F32(number) if !number.is_finite() => {
+                    let prims = (core::Prim::F32Div, core::Prim::F32Neg);
+                    self.synth_non_finite_literal(f64::from(*number), prims)
+                }
+                core::Const::F64(number) if !number.is_finite() => {
+                    let prims = (core::Prim::F64Div, core::Prim::F64Neg);
+                    self.synth_non_finite_literal(*number, prims)
+                }
                 core::Const::F32(number) => self.synth_number_literal(number, core::Prim::F32Type),
                 core::Const::F64(number) => self.synth_number_literal(number, core::Prim::F64Type),
                 core::Const::Pos(number) => self.synth_number_literal(number, core::Prim::PosType),
                 core::Const::Ref(number) => self.synth_number_literal(number, core::Prim::RefType),
+                core::Const::String(string) => {
+                    let expr = self.check_string_literal(string);
+                    let r#type = self.synth_prim(core::Prim::StringType);
+                    Term::Ann((), self.scope.to_scope(expr), self.scope.to_scope(r#type))
+                }
             },
             core::Term::ConstMatch(head_expr, branches, default_expr) => {
                 let head_expr = self.synth(head_expr);
                 match default_expr {
                     Some(default_expr) => {
                         let default_branch = {
-                            let name = self.push_rigid(None);
+                            let name = self.push_binder(None, default_expr);
                             let default_expr = self.synth(default_expr);
                             self.pop_rigid();
 
-                            (Pattern::Name((), name), default_expr)
+                            (binder_pattern(name), default_expr)
                         };
 
                         Term::Match(
@@ -472,6 +715,79 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         }
     }
 
+    /// Distill the application of a primitive operation to an operator, if
+    /// the operands can be checked against the type of the operator.
+    fn check_op_app(
+        &mut self,
+        head_expr: &core::Term<'_>,
+        input_expr: &core::Term<'_>,
+    ) -> Option<Term<'arena, ()>> {
+        match head_expr {
+            core::Term::Prim(prim) => {
+                let op = UnOp::from_prim(*prim)?;
+                let expr = self.check(input_expr);
+
+                Some(Term::UnOp((), ((), op), self.scope.to_scope(expr)))
+            }
+            core::Term::FunApp(core::Term::Prim(prim), lhs_expr) => {
+                let op = BinOp::from_prim(*prim)?;
+                if op.is_comparison() || is_pos_prim(*prim) {
+                    return None;
+                }
+
+                let lhs_expr = self.check(lhs_expr);
+                let rhs_expr = self.check(input_expr);
+
+                Some(Term::BinOp(
+                    (),
+                    self.scope.to_scope(lhs_expr),
+                    ((), op),
+                    self.scope.to_scope(rhs_expr),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Distill the application of a primitive operation to an operator.
+    fn synth_op_app(
+        &mut self,
+        head_expr: &core::Term<'_>,
+        input_expr: &core::Term<'_>,
+    ) -> Option<Term<'arena, ()>> {
+        match head_expr {
+            core::Term::Prim(prim) => {
+                let op = UnOp::from_prim(*prim)?;
+                let expr = self.synth(input_expr);
+
+                Some(Term::UnOp((), ((), op), self.scope.to_scope(expr)))
+            }
+            core::Term::FunApp(core::Term::Prim(prim), lhs_expr) => {
+                let op = BinOp::from_prim(*prim)?;
+
+                // Mirror the order that the operands are elaborated in
+                let (lhs_expr, rhs_expr) = match (lhs_expr, input_expr) {
+                    (core::Term::ConstLit(_), core::Term::ConstLit(_)) => {
+                        (self.synth(lhs_expr), self.check(input_expr))
+                    }
+                    (core::Term::ConstLit(_), _) if !op.is_shift() && !is_pos_prim(*prim) => {
+                        (self.check(lhs_expr), self.synth(input_expr))
+                    }
+                    (_, _) if is_pos_prim(*prim) => (self.synth(lhs_expr), self.synth(input_expr)),
+                    (_, _) => (self.synth(lhs_expr), self.check(input_expr)),
+                };
+
+                Some(Term::BinOp(
+                    (),
+                    self.scope.to_scope(lhs_expr),
+                    ((), op),
+                    self.scope.to_scope(rhs_expr),
+                ))
+            }
+            _ => None,
+        }
+    }
+
     fn synth_format_fields(
         &mut self,
         labels: &[StringId],
@@ -481,7 +797,7 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         let format_fields = (self.scope).to_scope_from_iter(
             Iterator::zip(labels.iter(), core_formats.iter()).map(|(label, format)| {
                 let format = self.check(format);
-                self.push_rigid(Some(*label));
+                self.push_label(*label);
                 (((), *label), format)
             }),
         );
@@ -490,3 +806,18 @@ impl<'interner, 'arena, 'env> Context<'interner, 'arena, 'env> {
         format_fields
     }
 }
+
+/// The pattern used to bind a variable that was named by
+/// [`Context::push_binder`].
+fn binder_pattern<'arena>(name: Option<StringId>) -> Pattern<'arena, ()> {
+    match name {
+        Some(name) => Pattern::Name((), name),
+        None => Pattern::Placeholder(()),
+    }
+}
+
+fn is_pos_prim(prim: core::Prim) -> bool {
+    use core::Prim::*;
+
+    matches!(prim, PosAddU8 | PosAddU16 | PosAddU32 | PosAddU64)
+}
@@ -25,13 +25,13 @@ use std::cell::RefCell;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use crate::alloc::SliceVec;
-use crate::core::semantics::{self, ArcValue, Closure, Head, Telescope, Value};
-use crate::core::{self, binary, Const, Prim, UIntStyle};
+use crate::alloc::{self, SliceVec};
+use crate::core::semantics::{self, ArcValue, Closure, Elim, Head, Telescope, Value};
+use crate::core::{self, binary, Const, Prim, UIntStyle, UniverseLevel};
 use crate::env::{self, EnvLen, GlobalVar, SharedEnv, UniqueEnv};
 use crate::source::ByteRange;
 use crate::surface::elaboration::reporting::Message;
-use crate::surface::{distillation, pretty, Pattern, Term};
+use crate::surface::{distillation, pretty, BinOp, Pattern, Term};
 use crate::{StringId, StringInterner};
 
 mod reporting;
@@ -85,7 +85,9 @@ impl<'arena> RigidEnv<'arena> {
         const VAR2: core::Term<'_> = core::Term::RigidVar(env::LocalVar::last().prev().prev());
         const VAR3: core::Term<'_> =
             core::Term::RigidVar(env::LocalVar::last().prev().prev().prev());
-        const UNIVERSE: core::Term<'_> = core::Term::Universe;
+        const VAR4: core::Term<'_> =
+            core::Term::RigidVar(env::LocalVar::last().prev().prev().prev().prev());
+        const UNIVERSE: core::Term<'_> = core::Term::Universe(0);
         const FORMAT_TYPE: core::Term<'_> = core::Term::Prim(Prim::FormatType);
         const BOOL_TYPE: core::Term<'_> = core::Term::Prim(Prim::BoolType);
         const U8_TYPE: core::Term<'_> = core::Term::Prim(Prim::U8Type);
@@ -96,6 +98,18 @@ impl<'arena> RigidEnv<'arena> {
         const S16_TYPE: core::Term<'_> = core::Term::Prim(Prim::S16Type);
         const S32_TYPE: core::Term<'_> = core::Term::Prim(Prim::S32Type);
         const S64_TYPE: core::Term<'_> = core::Term::Prim(Prim::S64Type);
+        const F32_TYPE: core::Term<'_> = core::Term::Prim(Prim::F32Type);
+        const F64_TYPE: core::Term<'_> = core::Term::Prim(Prim::F64Type);
+        const OPTION_TYPE: core::Term<'_> = core::Term::Prim(Prim::OptionType);
+        const OPTION_U8_TYPE: core::Term<'_> = core::Term::FunApp(&OPTION_TYPE, &U8_TYPE);
+        const OPTION_U16_TYPE: core::Term<'_> = core::Term::FunApp(&OPTION_TYPE, &U16_TYPE);
+        const OPTION_U32_TYPE: core::Term<'_> = core::Term::FunApp(&OPTION_TYPE, &U32_TYPE);
+        const OPTION_U64_TYPE: core::Term<'_> = core::Term::FunApp(&OPTION_TYPE, &U64_TYPE);
+        const OPTION_S8_TYPE: core::Term<'_> = core::Term::FunApp(&OPTION_TYPE, &S8_TYPE);
+        const OPTION_S16_TYPE: core::Term<'_> = core::Term::FunApp(&OPTION_TYPE, &S16_TYPE);
+        const OPTION_S32_TYPE: core::Term<'_> = core::Term::FunApp(&OPTION_TYPE, &S32_TYPE);
+        const OPTION_S64_TYPE: core::Term<'_> = core::Term::FunApp(&OPTION_TYPE, &S64_TYPE);
+        const ARRAY_TYPE: core::Term<'_> = core::Term::Prim(ArrayType);
         const ARRAY8_TYPE: core::Term<'_> = core::Term::Prim(Array8Type);
         const ARRAY16_TYPE: core::Term<'_> = core::Term::Prim(Array16Type);
         const ARRAY32_TYPE: core::Term<'_> = core::Term::Prim(Array32Type);
@@ -116,6 +130,7 @@ impl<'arena> RigidEnv<'arena> {
         env.define_prim(S64Type, &UNIVERSE);
         env.define_prim(F32Type, &UNIVERSE);
         env.define_prim(F64Type, &UNIVERSE);
+        env.define_prim(StringType, &UNIVERSE);
         env.define_prim_fun(OptionType, [&UNIVERSE], &UNIVERSE);
         env.define_prim_fun(ArrayType, [&UNIVERSE], &UNIVERSE);
         env.define_prim_fun(Array8Type, [&U8_TYPE, &UNIVERSE], &UNIVERSE);
@@ -144,6 +159,22 @@ impl<'arena> RigidEnv<'arena> {
         env.define_prim(FormatF32Le, &FORMAT_TYPE);
         env.define_prim(FormatF64Be, &FORMAT_TYPE);
         env.define_prim(FormatF64Le, &FORMAT_TYPE);
+        env.define_prim(FormatCString, &FORMAT_TYPE);
+        env.define_prim_fun(FormatUtf8Len8, [&U8_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatUtf8Len16, [&U16_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatUtf8Len32, [&U32_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatUtf8Len64, [&U64_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatUtf16BeLen8, [&U8_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatUtf16BeLen16, [&U16_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatUtf16BeLen32, [&U32_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatUtf16BeLen64, [&U64_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatAsciiLen8, [&U8_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatAsciiLen16, [&U16_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatAsciiLen32, [&U32_TYPE], &FORMAT_TYPE);
+        env.define_prim_fun(FormatAsciiLen64, [&U64_TYPE], &FORMAT_TYPE);
+        env.define_prim(FormatULeb128, &FORMAT_TYPE);
+        env.define_prim(FormatSLeb128, &FORMAT_TYPE);
+        env.define_prim(FormatBase128Be, &FORMAT_TYPE);
         env.define_prim_fun(FormatArray8, [&U8_TYPE, &FORMAT_TYPE], &FORMAT_TYPE);
         env.define_prim_fun(FormatArray16, [&U16_TYPE, &FORMAT_TYPE], &FORMAT_TYPE);
         env.define_prim_fun(FormatArray32, [&U32_TYPE, &FORMAT_TYPE], &FORMAT_TYPE);
@@ -162,7 +193,27 @@ impl<'arena> RigidEnv<'arena> {
                 ),
             ),
         );
+        env.define_prim(
+            FormatMap,
+            // fun (f : Format) -> fun (B : Type) -> (Repr f   -> B)   -> Format
+            // fun (f : Format) -> fun (B : Type) -> (Repr f@1 -> B@1) -> Format
+            scope.to_scope(core::Term::FunType(
+                env.name("f"),
+                &FORMAT_TYPE,
+                scope.to_scope(core::Term::FunType(
+                    env.name("B"),
+                    &UNIVERSE,
+                    &Term::FunType(
+                        None,
+                        &Term::FunType(None, &Term::FunApp(&Term::Prim(FormatRepr), &VAR1), &VAR1),
+                        &FORMAT_TYPE,
+                    ),
+                )),
+            )),
+        );
         env.define_prim(FormatStreamPos, &FORMAT_TYPE);
+        env.define_prim(FormatEndOfInput, &FORMAT_TYPE);
+        env.define_prim(FormatRemainingBytes, &FORMAT_TYPE);
         env.define_prim(
             FormatSucceed,
             &core::Term::FunType(
@@ -319,6 +370,86 @@ impl<'arena> RigidEnv<'arena> {
         env.define_prim_fun(S64Abs, [&S64_TYPE], &S64_TYPE);
         env.define_prim_fun(S64UAbs, [&S64_TYPE], &U64_TYPE);
 
+        env.define_prim_fun(F32Eq, [&F32_TYPE, &F32_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F32Neq, [&F32_TYPE, &F32_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F32Gt, [&F32_TYPE, &F32_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F32Lt, [&F32_TYPE, &F32_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F32Gte, [&F32_TYPE, &F32_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F32Lte, [&F32_TYPE, &F32_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F32Neg, [&F32_TYPE], &F32_TYPE);
+        env.define_prim_fun(F32Add, [&F32_TYPE, &F32_TYPE], &F32_TYPE);
+        env.define_prim_fun(F32Sub, [&F32_TYPE, &F32_TYPE], &F32_TYPE);
+        env.define_prim_fun(F32Mul, [&F32_TYPE, &F32_TYPE], &F32_TYPE);
+        env.define_prim_fun(F32Div, [&F32_TYPE, &F32_TYPE], &F32_TYPE);
+        env.define_prim_fun(F32Abs, [&F32_TYPE], &F32_TYPE);
+        env.define_prim_fun(F32IsNan, [&F32_TYPE], &BOOL_TYPE);
+
+        env.define_prim_fun(F64Eq, [&F64_TYPE, &F64_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F64Neq, [&F64_TYPE, &F64_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F64Gt, [&F64_TYPE, &F64_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F64Lt, [&F64_TYPE, &F64_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F64Gte, [&F64_TYPE, &F64_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F64Lte, [&F64_TYPE, &F64_TYPE], &BOOL_TYPE);
+        env.define_prim_fun(F64Neg, [&F64_TYPE], &F64_TYPE);
+        env.define_prim_fun(F64Add, [&F64_TYPE, &F64_TYPE], &F64_TYPE);
+        env.define_prim_fun(F64Sub, [&F64_TYPE, &F64_TYPE], &F64_TYPE);
+        env.define_prim_fun(F64Mul, [&F64_TYPE, &F64_TYPE], &F64_TYPE);
+        env.define_prim_fun(F64Div, [&F64_TYPE, &F64_TYPE], &F64_TYPE);
+        env.define_prim_fun(F64Abs, [&F64_TYPE], &F64_TYPE);
+        env.define_prim_fun(F64IsNan, [&F64_TYPE], &BOOL_TYPE);
+
+        env.define_prim_fun(U8ToU16, [&U8_TYPE], &U16_TYPE);
+        env.define_prim_fun(U8ToU32, [&U8_TYPE], &U32_TYPE);
+        env.define_prim_fun(U8ToU64, [&U8_TYPE], &U64_TYPE);
+        env.define_prim_fun(U16ToU32, [&U16_TYPE], &U32_TYPE);
+        env.define_prim_fun(U16ToU64, [&U16_TYPE], &U64_TYPE);
+        env.define_prim_fun(U32ToU64, [&U32_TYPE], &U64_TYPE);
+        env.define_prim_fun(S8ToS16, [&S8_TYPE], &S16_TYPE);
+        env.define_prim_fun(S8ToS32, [&S8_TYPE], &S32_TYPE);
+        env.define_prim_fun(S8ToS64, [&S8_TYPE], &S64_TYPE);
+        env.define_prim_fun(S16ToS32, [&S16_TYPE], &S32_TYPE);
+        env.define_prim_fun(S16ToS64, [&S16_TYPE], &S64_TYPE);
+        env.define_prim_fun(S32ToS64, [&S32_TYPE], &S64_TYPE);
+        env.define_prim_fun(U8ToS16, [&U8_TYPE], &S16_TYPE);
+        env.define_prim_fun(U8ToS32, [&U8_TYPE], &S32_TYPE);
+        env.define_prim_fun(U8ToS64, [&U8_TYPE], &S64_TYPE);
+        env.define_prim_fun(U16ToS32, [&U16_TYPE], &S32_TYPE);
+        env.define_prim_fun(U16ToS64, [&U16_TYPE], &S64_TYPE);
+        env.define_prim_fun(U32ToS64, [&U32_TYPE], &S64_TYPE);
+
+        env.define_prim_fun(U16ToU8, [&U16_TYPE], &OPTION_U8_TYPE);
+        env.define_prim_fun(U32ToU8, [&U32_TYPE], &OPTION_U8_TYPE);
+        env.define_prim_fun(U32ToU16, [&U32_TYPE], &OPTION_U16_TYPE);
+        env.define_prim_fun(U64ToU8, [&U64_TYPE], &OPTION_U8_TYPE);
+        env.define_prim_fun(U64ToU16, [&U64_TYPE], &OPTION_U16_TYPE);
+        env.define_prim_fun(U64ToU32, [&U64_TYPE], &OPTION_U32_TYPE);
+        env.define_prim_fun(S16ToS8, [&S16_TYPE], &OPTION_S8_TYPE);
+        env.define_prim_fun(S32ToS8, [&S32_TYPE], &OPTION_S8_TYPE);
+        env.define_prim_fun(S32ToS16, [&S32_TYPE], &OPTION_S16_TYPE);
+        env.define_prim_fun(S64ToS8, [&S64_TYPE], &OPTION_S8_TYPE);
+        env.define_prim_fun(S64ToS16, [&S64_TYPE], &OPTION_S16_TYPE);
+        env.define_prim_fun(S64ToS32, [&S64_TYPE], &OPTION_S32_TYPE);
+        env.define_prim_fun(U8ToS8, [&U8_TYPE], &OPTION_S8_TYPE);
+        env.define_prim_fun(U16ToS16, [&U16_TYPE], &OPTION_S16_TYPE);
+        env.define_prim_fun(U32ToS32, [&U32_TYPE], &OPTION_S32_TYPE);
+        env.define_prim_fun(U64ToS64, [&U64_TYPE], &OPTION_S64_TYPE);
+        env.define_prim_fun(S8ToU8, [&S8_TYPE], &OPTION_U8_TYPE);
+        env.define_prim_fun(S16ToU16, [&S16_TYPE], &OPTION_U16_TYPE);
+        env.define_prim_fun(S32ToU32, [&S32_TYPE], &OPTION_U32_TYPE);
+        env.define_prim_fun(S64ToU64, [&S64_TYPE], &OPTION_U64_TYPE);
+
+        env.define_prim_fun(U8ToF32, [&U8_TYPE], &F32_TYPE);
+        env.define_prim_fun(U16ToF32, [&U16_TYPE], &F32_TYPE);
+        env.define_prim_fun(S8ToF32, [&S8_TYPE], &F32_TYPE);
+        env.define_prim_fun(S16ToF32, [&S16_TYPE], &F32_TYPE);
+        env.define_prim_fun(U8ToF64, [&U8_TYPE], &F64_TYPE);
+        env.define_prim_fun(U16ToF64, [&U16_TYPE], &F64_TYPE);
+        env.define_prim_fun(U32ToF64, [&U32_TYPE], &F64_TYPE);
+        env.define_prim_fun(S8ToF64, [&S8_TYPE], &F64_TYPE);
+        env.define_prim_fun(S16ToF64, [&S16_TYPE], &F64_TYPE);
+        env.define_prim_fun(S32ToF64, [&S32_TYPE], &F64_TYPE);
+        env.define_prim_fun(F32ToF64, [&F32_TYPE], &F64_TYPE);
+
         env.define_prim(
             OptionSome,
             // fun (A : Type) -> A   -> Option A
@@ -366,39 +497,131 @@ impl<'arena> RigidEnv<'arena> {
             )),
         );
 
-        // fun (len : UN) (A : Type) -> (A   -> Bool) -> ArrayN len   A   -> Option A
-        // fun (len : UN) (A : Type) -> (A@0 -> Bool) -> ArrayN len@2 A@1 -> Option A@2
-        let find_type = |index_type, array_type| {
-            scope.to_scope(core::Term::FunType(
-                env.name("len"),
-                index_type,
-                scope.to_scope(core::Term::FunType(
-                    env.name("A"),
+        // Array operations are defined for each of the fixed-length array
+        // types, `ArrayN len A` (where `len : UN`), and for the dynamically
+        // sized `Array A` (where lengths and indices are `U64`). The `len`
+        // parameter is always the outermost binder, so omitting it for `Array`
+        // leaves the indices of the remaining variables unchanged.
+        let len_name = env.name("len");
+        let a_name = env.name("A");
+        let b_name = env.name("B");
+        let fun_type = |name, input_type, output_type| {
+            &*scope.to_scope(Term::FunType(name, input_type, output_type))
+        };
+        let array = |array_type: &'arena Term<'arena>, len, elem_type| match array_type {
+            Term::Prim(ArrayType) => &*scope.to_scope(Term::FunApp(array_type, elem_type)),
+            _ => scope.to_scope(Term::FunApp(
+                scope.to_scope(Term::FunApp(array_type, len)),
+                elem_type,
+            )),
+        };
+        #[rustfmt::skip]
+        let array_prims = [
+            (Some(&U8_TYPE), &ARRAY8_TYPE, [Array8Len, Array8Index, Array8Fold, Array8Map, Array8All, Array8Any, Array8Find, Array8BinarySearch]),
+            (Some(&U16_TYPE), &ARRAY16_TYPE, [Array16Len, Array16Index, Array16Fold, Array16Map, Array16All, Array16Any, Array16Find, Array16BinarySearch]),
+            (Some(&U32_TYPE), &ARRAY32_TYPE, [Array32Len, Array32Index, Array32Fold, Array32Map, Array32All, Array32Any, Array32Find, Array32BinarySearch]),
+            (Some(&U64_TYPE), &ARRAY64_TYPE, [Array64Len, Array64Index, Array64Fold, Array64Map, Array64All, Array64Any, Array64Find, Array64BinarySearch]),
+            (None, &ARRAY_TYPE, [ArrayLen, ArrayIndex, ArrayFold, ArrayMap, ArrayAll, ArrayAny, ArrayFind, ArrayBinarySearch]),
+        ];
+        for (len_type, array_type, prims) in array_prims {
+            let [len, index, fold, map, all, any, find, binary_search] = prims;
+            let index_type = len_type.unwrap_or(&U64_TYPE);
+            let with_len = |r#type| match len_type {
+                Some(len_type) => fun_type(len_name, len_type, r#type),
+                None => r#type,
+            };
+
+            // fun (len : UN) (A : Type) -> ArrayN len@1 A@0 -> UN
+            env.define_prim(
+                len,
+                with_len(fun_type(
+                    a_name,
                     &UNIVERSE,
-                    scope.to_scope(core::Term::FunType(
+                    fun_type(None, array(array_type, &VAR1, &VAR0), index_type),
+                )),
+            );
+            // fun (len : UN) (A : Type) -> UN -> ArrayN len@2 A@1 -> Option A@2
+            env.define_prim(
+                index,
+                with_len(fun_type(
+                    a_name,
+                    &UNIVERSE,
+                    fun_type(
                         None,
-                        &Term::FunType(None, &VAR0, &BOOL_TYPE), // (A@0 -> Bool)
-                        scope.to_scope(core::Term::FunType(
+                        index_type,
+                        fun_type(
                             None,
-                            // ArrayN len@2 A@1
-                            scope.to_scope(Term::FunApp(
-                                scope.to_scope(Term::FunApp(array_type, &VAR2)),
-                                &VAR1,
-                            )),
-                            &Term::FunApp(&Term::Prim(OptionType), &VAR2), // Option A@2
-                        )),
-                    )),
+                            array(array_type, &VAR2, &VAR1),
+                            &Term::FunApp(&OPTION_TYPE, &VAR2),
+                        ),
+                    ),
                 )),
-            ))
-        };
-        let array8_find_type = find_type(&U8_TYPE, &ARRAY8_TYPE);
-        let array16_find_type = find_type(&U16_TYPE, &ARRAY16_TYPE);
-        let array32_find_type = find_type(&U32_TYPE, &ARRAY32_TYPE);
-        let array64_find_type = find_type(&U64_TYPE, &ARRAY64_TYPE);
-        env.define_prim(Array8Find, array8_find_type);
-        env.define_prim(Array16Find, array16_find_type);
-        env.define_prim(Array32Find, array32_find_type);
-        env.define_prim(Array64Find, array64_find_type);
+            );
+            // fun (len : UN) (A : Type) (B : Type) -> B   -> (B   -> A   -> B  ) -> ArrayN len   A   -> B
+            // fun (len : UN) (A : Type) (B : Type) -> B@0 -> (B@1 -> A@3 -> B@3) -> ArrayN len@4 A@3 -> B@3
+            env.define_prim(
+                fold,
+                with_len(fun_type(
+                    a_name,
+                    &UNIVERSE,
+                    fun_type(
+                        b_name,
+                        &UNIVERSE,
+                        fun_type(
+                            None,
+                            &VAR0,
+                            fun_type(
+                                None,
+                                &Term::FunType(None, &VAR1, &Term::FunType(None, &VAR3, &VAR3)),
+                                fun_type(None, array(array_type, &VAR4, &VAR3), &VAR3),
+                            ),
+                        ),
+                    ),
+                )),
+            );
+            // fun (len : UN) (A : Type) (B : Type) -> (A   -> B  ) -> ArrayN len   A   -> ArrayN len   B
+            // fun (len : UN) (A : Type) (B : Type) -> (A@1 -> B@1) -> ArrayN len@3 A@2 -> ArrayN len@4 B@2
+            env.define_prim(
+                map,
+                with_len(fun_type(
+                    a_name,
+                    &UNIVERSE,
+                    fun_type(
+                        b_name,
+                        &UNIVERSE,
+                        fun_type(
+                            None,
+                            &Term::FunType(None, &VAR1, &VAR1),
+                            fun_type(
+                                None,
+                                array(array_type, &VAR3, &VAR2),
+                                array(array_type, &VAR4, &VAR2),
+                            ),
+                        ),
+                    ),
+                )),
+            );
+
+            // fun (len : UN) (A : Type) -> (A   -> Output) -> ArrayN len   A   -> Output
+            // fun (len : UN) (A : Type) -> (A@0 -> Output) -> ArrayN len@2 A@1 -> Output
+            let search_type = |pred_output_type, output_type| {
+                with_len(fun_type(
+                    a_name,
+                    &UNIVERSE,
+                    fun_type(
+                        None,
+                        scope.to_scope(Term::FunType(None, &VAR0, pred_output_type)),
+                        fun_type(None, array(array_type, &VAR2, &VAR1), output_type),
+                    ),
+                ))
+            };
+            // Option A@2
+            let option_elem_type = &Term::FunApp(&OPTION_TYPE, &VAR2);
+            env.define_prim(all, search_type(&BOOL_TYPE, &BOOL_TYPE));
+            env.define_prim(any, search_type(&BOOL_TYPE, &BOOL_TYPE));
+            env.define_prim(find, search_type(&BOOL_TYPE, option_elem_type));
+            env.define_prim(binary_search, search_type(&S8_TYPE, option_elem_type));
+        }
 
         env.define_prim_fun(PosAddU8, [&POS_TYPE, &U8_TYPE], &POS_TYPE);
         env.define_prim_fun(PosAddU16, [&POS_TYPE, &U16_TYPE], &POS_TYPE);
@@ -485,8 +708,7 @@ impl<'i, 'arena> RigidEnvBuilder<'i, 'arena> {
         let flexible_exprs = UniqueEnv::new();
         let r#type =
             semantics::EvalContext::new(&mut SharedEnv::new(), &flexible_exprs).eval(r#type);
-        self.env
-            .push_def(name, Arc::new(Value::prim(prim, [])), r#type);
+        self.env.push_def(name, semantics::prim_value(prim), r#type);
     }
 
     fn define_prim_fun<const ARITY: usize>(
@@ -532,6 +754,8 @@ pub enum FlexSource {
     FunOutputType(ByteRange),
     /// The type of a reported error.
     ReportedErrorType(ByteRange),
+    /// The universe of a type.
+    Universe(ByteRange),
 }
 
 /// Flexible environment.
@@ -544,8 +768,10 @@ pub struct FlexibleEnv<'arena> {
     /// The source of inserted flexible variables, used when reporting [unsolved
     /// flexible variables][Message::UnsolvedFlexibleVar].
     sources: UniqueEnv<FlexSource>,
-    /// Types of flexible variables.
-    types: UniqueEnv</* TODO: lazy value */ ArcValue<'arena>>,
+    /// Types of flexible variables. These are set to [`None`] for flexible
+    /// universes, which inhabit the universe above the one they are solved
+    /// with.
+    types: UniqueEnv</* TODO: lazy value */ Option<ArcValue<'arena>>>,
     /// Expressions that will be substituted for flexible variables during
     /// [evaluation][semantics::EvalContext::eval].
     ///
@@ -566,7 +792,7 @@ impl<'arena> FlexibleEnv<'arena> {
     }
 
     /// Push an unsolved flexible binder onto the context.
-    fn push(&mut self, source: FlexSource, r#type: ArcValue<'arena>) -> GlobalVar {
+    fn push(&mut self, source: FlexSource, r#type: Option<ArcValue<'arena>>) -> GlobalVar {
         // TODO: check that hole name is not already in use
         let var = self.exprs.len().next_global();
 
@@ -577,50 +803,82 @@ impl<'arena> FlexibleEnv<'arena> {
         var
     }
 
-    fn report<'this, 'interner: 'this, 'error: 'this>(
-        &'this self,
-        interner: &'interner RefCell<StringInterner>,
-        scope: &'error Scope<'error>,
-        mut rigid_names: UniqueEnv<Option<StringId>>,
-    ) -> impl 'this + Iterator<Item = Message> {
+    fn report(&self) -> impl '_ + Iterator<Item = Message> {
         let entries = Iterator::zip(self.sources.iter(), self.exprs.iter());
 
-        entries.filter_map(move |(&source, expr)| match (expr, source) {
+        entries.filter_map(|(&source, expr)| match (expr, source) {
             // Avoid producing messages for some unsolved flexible sources:
             (None, FlexSource::HoleType(_, _)) => None, // should have an unsolved hole expression
+            (None, FlexSource::HoleExpr(_, _)) => None, // should be reported with the hole goal
             (None, FlexSource::PlaceholderType(_)) => None, // should have an unsolved placeholder expression
             (None, FlexSource::ReportedErrorType(_)) => None, // should already have an error reported
+            (None, FlexSource::Universe(_)) => None, // should default to the lowest universe
 
             // For other sources, report an unsolved problem message
             (None, source) => Some(Message::UnsolvedFlexibleVar { source }),
-            // Yield messages of solved named holes
-            (Some(expr), FlexSource::HoleExpr(range, name)) => {
-                let term = semantics::QuoteContext::new(scope, rigid_names.len(), &self.exprs)
-                    .quote(&expr);
-                let surface_term =
-                    distillation::Context::new(interner, scope, &mut rigid_names, &self.sources)
-                        .check(&term);
-
-                let pretty_context = pretty::Context::new(interner, scope);
-                let doc = pretty_context.term(&surface_term).into_doc();
-                let expr = doc.pretty(usize::MAX).to_string();
-
-                Some(Message::HoleSolution { range, name, expr })
-            }
-            // Ignore solutions of anything else
+            // Ignore solutions, named holes are reported with their goals
             (Some(_), _) => None,
         })
     }
 }
 
+/// A constraint that a type inhabits a flexible universe, which is checked
+/// once elaboration is complete. This is used when the universe of a type
+/// depends on flexible variables that might not have been solved yet.
+struct UniverseConstraint<'arena> {
+    range: ByteRange,
+    /// The type, in the rigid environment where the constraint was recorded.
+    r#type: ArcValue<'arena>,
+    /// The types of the rigid variables in scope of the type.
+    rigid_types: UniqueEnv<ArcValue<'arena>>,
+    /// The flexible universe that the type is expected to inhabit.
+    universe: ArcValue<'arena>,
+}
+
+/// The state of the elaborator when a named hole was encountered. This is
+/// used to report the goal of the hole once elaboration is complete.
+struct HoleGoal<'arena> {
+    range: ByteRange,
+    name: StringId,
+    /// The flexible variable that was inserted for the hole expression.
+    var: GlobalVar,
+    /// The hole expression, in the rigid environment of the hole.
+    expr: ArcValue<'arena>,
+    /// The expected type of the hole.
+    r#type: ArcValue<'arena>,
+    /// Names of the rigid variables in scope of the hole.
+    rigid_names: UniqueEnv<Option<StringId>>,
+    /// Names and types of the rigid variables in scope of the hole, not
+    /// including the primitives.
+    locals: Vec<(Option<StringId>, ArcValue<'arena>)>,
+}
+
 #[derive(Debug)]
-enum CheckedPattern {
+enum CheckedPattern<'arena> {
     Name(ByteRange, StringId),
     Placeholder(ByteRange),
-    Const(ByteRange, Const),
+    Const(ByteRange, Const<'arena>),
     ReportedError(ByteRange),
 }
 
+/// A character in a string literal, after escape codes have been processed.
+#[derive(Debug, Copy, Clone)]
+enum StringChar {
+    Char(char),
+    /// A byte escape code, eg. `\x89`
+    Byte(u8),
+}
+
+/// The byte order of a string literal, as specified by its suffix.
+#[derive(Debug, Copy, Clone)]
+enum ByteOrder {
+    Big,
+    Little,
+}
+
+/// The characters of a string literal, along with its byte order suffix.
+type StringLiteral = (Vec<(ByteRange, StringChar)>, Option<(ByteRange, ByteOrder)>);
+
 /// Elaboration context.
 pub struct Context<'interner, 'arena, 'error> {
     /// Global string interner.
@@ -635,10 +893,17 @@ pub struct Context<'interner, 'arena, 'error> {
     error_scope: &'error Scope<'error>,
     /// Rigid environment.
     rigid_env: RigidEnv<'arena>,
+    /// The length of the rigid environment before any binders were pushed
+    /// after the primitives.
+    prim_env_len: EnvLen,
     /// Flexible environment.
     flexible_env: FlexibleEnv<'arena>,
     /// A partial renaming to be used during [`unification`].
     renaming: unification::PartialRenaming,
+    /// Constraints on flexible universes, checked once elaboration is complete.
+    universe_constraints: Vec<UniverseConstraint<'arena>>,
+    /// The goals of the named holes encountered during elaboration.
+    hole_goals: Vec<HoleGoal<'arena>>,
     /// Diagnostic messages encountered during elaboration.
     messages: Vec<Message>,
 }
@@ -650,13 +915,19 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         scope: &'arena Scope<'arena>,
         error_scope: &'error Scope<'error>,
     ) -> Context<'interner, 'arena, 'error> {
+        let rigid_env = RigidEnv::default(interner, scope);
+        let prim_env_len = rigid_env.len();
+
         Context {
             interner,
             scope,
             error_scope,
-            rigid_env: RigidEnv::default(interner, scope),
+            rigid_env,
+            prim_env_len,
             flexible_env: FlexibleEnv::new(),
             renaming: unification::PartialRenaming::new(),
+            universe_constraints: Vec::new(),
+            hole_goals: Vec::new(),
             messages: Vec::new(),
         }
     }
@@ -669,6 +940,39 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
             .find_map(|(n, (var, r#type))| (Some(name) == n).then(|| (var, r#type)))
     }
 
+    /// Find the `candidates` that are similar to `name`, ordered from most to
+    /// least similar. This is used to suggest corrections for typos.
+    fn suggest_names(
+        &self,
+        name: StringId,
+        candidates: impl Iterator<Item = StringId>,
+    ) -> Vec<StringId> {
+        const MAX_SUGGESTIONS: usize = 3;
+
+        let interner = self.interner.borrow();
+        let name = interner.resolve(name).unwrap();
+        // Allow roughly one edit for every three characters in the name
+        let max_distance = std::cmp::max(1, name.chars().count() / 3);
+
+        let mut suggestions = candidates
+            .filter_map(|candidate| {
+                let distance =
+                    strsim::damerau_levenshtein(name, interner.resolve(candidate).unwrap());
+                (0 < distance && distance <= max_distance).then(|| (distance, candidate))
+            })
+            .collect::<Vec<_>>();
+
+        suggestions.sort_by_key(|(distance, candidate)| {
+            (*distance, interner.resolve(*candidate).unwrap())
+        });
+        suggestions.dedup_by_key(|(_, candidate)| *candidate);
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+            .into_iter()
+            .map(|(_, candidate)| candidate)
+            .collect()
+    }
+
     /// Push an unsolved flexible binder onto the context.
     fn push_flexible_term(
         &mut self,
@@ -676,7 +980,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         r#type: ArcValue<'arena>,
     ) -> core::Term<'arena> {
         let rigid_infos = (self.scope).to_scope_from_iter(self.rigid_env.infos.iter().copied());
-        core::Term::FlexibleInsertion(self.flexible_env.push(source, r#type), rigid_infos)
+        core::Term::FlexibleInsertion(self.flexible_env.push(source, Some(r#type)), rigid_infos)
     }
 
     /// Push an unsolved flexible binder onto the context.
@@ -689,18 +993,312 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         self.eval_context().eval(&term)
     }
 
+    /// Push an unsolved flexible type onto the context, along with a flexible
+    /// universe for it to inhabit.
+    fn push_flexible_type_term(
+        &mut self,
+        source: FlexSource,
+        range: ByteRange,
+    ) -> core::Term<'arena> {
+        let universe = self.push_flexible_universe(range);
+        let term = self.push_flexible_term(source, universe.clone());
+        let r#type = self.eval_context().eval(&term);
+        self.push_universe_constraint(range, r#type, universe);
+        term
+    }
+
+    /// Push an unsolved flexible type onto the context, along with a flexible
+    /// universe for it to inhabit.
+    fn push_flexible_type(&mut self, source: FlexSource, range: ByteRange) -> ArcValue<'arena> {
+        let universe = self.push_flexible_universe(range);
+        let r#type = self.push_flexible_value(source, universe.clone());
+        self.push_universe_constraint(range, r#type.clone(), universe);
+        r#type
+    }
+
+    /// Push an unsolved flexible universe onto the context. Universes never
+    /// depend on rigid variables, so unlike other flexible variables these are
+    /// not applied to the rigid environment.
+    fn push_flexible_universe(&mut self, range: ByteRange) -> ArcValue<'arena> {
+        let var = self.flexible_env.push(FlexSource::Universe(range), None);
+        Arc::new(Value::flexible_var(var))
+    }
+
+    /// Returns `true` if `value` is an unsolved flexible universe.
+    fn is_flexible_universe(&self, value: &ArcValue<'arena>) -> bool {
+        match self.elim_context().force(value).as_ref() {
+            Value::Stuck(Head::FlexibleVar(var), spine) => {
+                spine.is_empty()
+                    && matches!(
+                        self.flexible_env.sources.get_global(*var),
+                        Some(FlexSource::Universe(_)),
+                    )
+            }
+            _ => false,
+        }
+    }
+
+    /// Record that `type` must inhabit `universe`, to be checked once
+    /// elaboration is complete. Constraints are ignored if `universe` is not
+    /// solved to a universe.
+    fn push_universe_constraint(
+        &mut self,
+        range: ByteRange,
+        r#type: ArcValue<'arena>,
+        universe: ArcValue<'arena>,
+    ) {
+        self.universe_constraints.push(UniverseConstraint {
+            range,
+            r#type,
+            rigid_types: self.rigid_env.types.clone(),
+            universe,
+        });
+    }
+
+    /// Record that a flexible expression must inhabit its flexible type, in
+    /// case the expression is solved to a type and its type to a universe.
+    fn push_flexible_expr_constraint(
+        &mut self,
+        range: ByteRange,
+        expr: &core::Term<'arena>,
+        r#type: &ArcValue<'arena>,
+    ) {
+        let expr = self.eval_context().eval(expr);
+        self.push_universe_constraint(range, expr, r#type.clone());
+    }
+
+    /// Solve the flexible universes with the lowest levels that satisfy the
+    /// recorded universe constraints, reporting any constraints that could not
+    /// be satisfied.
+    fn solve_universe_constraints(&mut self) {
+        // Start with every unsolved universe at the lowest level. These will
+        // be raised until the constraints are satisfied.
+        let mut levels = Iterator::zip(env::global_vars(), self.flexible_env.sources.iter())
+            .filter(|(var, source)| {
+                matches!(source, FlexSource::Universe(_))
+                    && matches!(self.flexible_env.exprs.get_global(*var), Some(None))
+            })
+            .map(|(var, _)| (var, 0))
+            .collect::<Vec<_>>();
+
+        let mut constraints = std::mem::take(&mut self.universe_constraints);
+
+        // Raising the level of a universe can raise the levels of the types
+        // that depend on it, so we continue until no more changes are made.
+        // Each pass raises at least one universe, so the number of passes is
+        // bounded unless the constraints are cyclic.
+        for _ in 0..=constraints.len() {
+            let mut is_changed = false;
+            for constraint in &mut constraints {
+                let found_level =
+                    self.type_level(&levels, &mut constraint.rigid_types, &constraint.r#type);
+                let universe = self.elim_context().force(&constraint.universe);
+                if let Value::Stuck(Head::FlexibleVar(var), spine) = universe.as_ref() {
+                    if let (Ok(index), []) =
+                        (levels.binary_search_by_key(var, |(v, _)| *v), &spine[..])
+                    {
+                        if levels[index].1 < found_level {
+                            levels[index].1 = found_level;
+                            is_changed = true;
+                        }
+                    }
+                }
+            }
+            if !is_changed {
+                break;
+            }
+        }
+
+        for (var, level) in &levels {
+            let universe = semantics::universe_value(*level);
+            self.flexible_env.exprs.set_global(*var, Some(universe));
+        }
+
+        for constraint in &mut constraints {
+            let found_level = self.type_level(&[], &mut constraint.rigid_types, &constraint.r#type);
+            let universe = self.elim_context().force(&constraint.universe);
+            if let Value::Universe(expected_level) = universe.as_ref() {
+                if *expected_level < found_level {
+                    self.push_message(Message::MismatchedUniverseLevel {
+                        range: constraint.range,
+                        expected_level: *expected_level,
+                        found_level,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Returns the level of a universe, using the current `levels` of any
+    /// flexible universes that are still being solved.
+    fn universe_level(
+        &self,
+        levels: &[(GlobalVar, UniverseLevel)],
+        universe: &ArcValue<'arena>,
+    ) -> Option<UniverseLevel> {
+        match self.elim_context().force(universe).as_ref() {
+            Value::Universe(level) => Some(*level),
+            Value::Stuck(Head::FlexibleVar(var), spine) if spine.is_empty() => {
+                let index = levels.binary_search_by_key(var, |(v, _)| *v).ok()?;
+                Some(levels[index].1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Compute the level of the smallest universe that a type inhabits.
+    fn type_level(
+        &self,
+        levels: &[(GlobalVar, UniverseLevel)],
+        rigid_types: &mut UniqueEnv<ArcValue<'arena>>,
+        r#type: &ArcValue<'arena>,
+    ) -> UniverseLevel {
+        if let Some(level) = self.universe_level(levels, r#type) {
+            return level.saturating_add(1);
+        }
+
+        match self.elim_context().force(r#type).as_ref() {
+            // Type variables inhabit the universe of their types
+            Value::Stuck(Head::RigidVar(var), spine) => match rigid_types.get_global(*var) {
+                Some(var_type) => {
+                    let mut r#type = var_type.clone();
+                    for elim in spine {
+                        r#type = match (elim, self.elim_context().force(&r#type).as_ref()) {
+                            (Elim::FunApp(input_expr), Value::FunType(_, _, output_type)) => {
+                                (self.elim_context()).apply_closure(output_type, input_expr.clone())
+                            }
+                            (_, _) => return 0,
+                        };
+                    }
+                    self.universe_level(levels, &r#type).unwrap_or(0)
+                }
+                None => 0,
+            },
+            Value::FunType(_, input_type, output_type) => {
+                let input_level = self.type_level(levels, rigid_types, input_type);
+                let var = Arc::new(Value::rigid_var(rigid_types.len().next_global()));
+                let output_type = self.elim_context().apply_closure(output_type, var);
+
+                rigid_types.push(input_type.clone());
+                let output_level = self.type_level(levels, rigid_types, &output_type);
+                rigid_types.pop();
+
+                Ord::max(input_level, output_level)
+            }
+            Value::RecordType(_, types) => {
+                let initial_len = rigid_types.len();
+                let mut types = types.clone();
+                let mut level = 0;
+
+                while let Some((r#type, next_types)) = self.elim_context().split_telescope(types) {
+                    level = Ord::max(level, self.type_level(levels, rigid_types, &r#type));
+                    let var = Arc::new(Value::rigid_var(rigid_types.len().next_global()));
+                    rigid_types.push(r#type);
+                    types = next_types(var);
+                }
+
+                rigid_types.truncate(initial_len);
+                level
+            }
+            // Primitive types inhabit the lowest universe. Unsolved flexible
+            // variables and errors will have already been reported.
+            _ => 0,
+        }
+    }
+
+    /// Compute the universe of a type former from the `universes` of its
+    /// component types, recording a constraint if these are not yet known.
+    fn former_universe(
+        &mut self,
+        range: ByteRange,
+        r#type: &core::Term<'arena>,
+        universes: &[ArcValue<'arena>],
+    ) -> ArcValue<'arena> {
+        let mut level = 0;
+        for universe in universes {
+            match self.elim_context().force(universe).as_ref() {
+                Value::Universe(universe_level) => level = Ord::max(level, *universe_level),
+                _ => {
+                    let universe = self.push_flexible_universe(range);
+                    let r#type = self.eval_context().eval(r#type);
+                    self.push_universe_constraint(range, r#type, universe.clone());
+                    return universe;
+                }
+            }
+        }
+        semantics::universe_value(level)
+    }
+
+    /// Record the goal of a named hole, to be reported once elaboration is
+    /// complete.
+    fn push_hole_goal(
+        &mut self,
+        range: ByteRange,
+        name: StringId,
+        var: GlobalVar,
+        expr: &core::Term<'arena>,
+        r#type: &ArcValue<'arena>,
+    ) {
+        let expr = self.eval_context().eval(expr);
+        let binders = Iterator::zip(self.rigid_env.names.iter(), self.rigid_env.types.iter());
+        let locals = Iterator::zip(env::global_vars(), binders)
+            // Skip the primitives, which are in scope everywhere
+            .filter(|(var, _)| self.prim_env_len.global_to_local(*var).is_none())
+            .map(|(_, (name, r#type))| (*name, r#type.clone()))
+            .collect();
+
+        self.hole_goals.push(HoleGoal {
+            range,
+            name,
+            var,
+            expr,
+            r#type: r#type.clone(),
+            rigid_names: self.rigid_env.names.clone(),
+            locals,
+        });
+    }
+
     fn push_message(&mut self, message: Message) {
         self.messages.push(message);
     }
 
+    /// Report the expected type, local bindings, and solution of a named hole.
+    fn report_hole_goal(&mut self, goal: HoleGoal<'arena>) -> Message {
+        // Print values using the names of the rigid variables in scope of the hole
+        let rigid_names = std::mem::replace(&mut self.rigid_env.names, goal.rigid_names);
+
+        let r#type = self.pretty_print_value(&goal.r#type);
+        let solution = match self.flexible_env.exprs.get_global(goal.var) {
+            Some(Some(_)) => Some(self.pretty_print_value(&goal.expr)),
+            Some(None) | None => None,
+        };
+        let context = (goal.locals.iter())
+            .filter_map(|(name, r#type)| Some(((*name)?, self.pretty_print_value(r#type))))
+            .collect();
+
+        self.rigid_env.names = rigid_names;
+
+        Message::HoleGoal {
+            range: goal.range,
+            name: goal.name,
+            r#type,
+            context,
+            solution,
+        }
+    }
+
     pub fn drain_messages<'this>(&'this mut self) -> impl 'this + Iterator<Item = Message> {
-        let report_messages = self.flexible_env.report(
-            self.interner,
-            self.error_scope,
-            self.rigid_env.names.clone(),
-        );
+        self.solve_universe_constraints();
+
+        let hole_goals = std::mem::take(&mut self.hole_goals);
+        let hole_messages = (hole_goals.into_iter())
+            .map(|goal| self.report_hole_goal(goal))
+            .collect::<Vec<_>>();
+        let report_messages = self.flexible_env.report();
 
-        self.messages.drain(..).chain(report_messages)
+        (self.messages.drain(..))
+            .chain(hole_messages)
+            .chain(report_messages)
     }
 
     pub fn eval_context(&mut self) -> semantics::EvalContext<'arena, '_> {
@@ -711,6 +1309,10 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         semantics::ElimContext::new(&self.flexible_env.exprs)
     }
 
+    pub fn conversion_context(&self) -> semantics::ConversionContext<'arena, '_> {
+        semantics::ConversionContext::new(self.rigid_env.len(), &self.flexible_env.exprs)
+    }
+
     pub fn quote_context<'out_arena>(
         &self,
         scope: &'out_arena Scope<'out_arena>,
@@ -736,11 +1338,18 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
             scope,
             &mut self.rigid_env.names,
             &self.flexible_env.sources,
+            &self.flexible_env.exprs,
         )
     }
 
-    pub fn binary_context(&self) -> binary::Context<'arena, '_> {
-        binary::Context::new(&self.flexible_env.exprs)
+    pub fn binary_context<'out_arena>(
+        &self,
+        scope: &'out_arena Scope<'out_arena>,
+    ) -> binary::Context<'out_arena, '_>
+    where
+        'arena: 'out_arena,
+    {
+        binary::Context::new(scope, &self.flexible_env.exprs)
     }
 
     fn pretty_print_value(&mut self, value: &ArcValue<'_>) -> String {
@@ -751,6 +1360,47 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         doc.pretty(usize::MAX).to_string()
     }
 
+    /// Pretty print a value that was found underneath some binders that are
+    /// not in the current rigid environment.
+    fn pretty_print_value_under_binders(
+        &mut self,
+        binders: &[Option<StringId>],
+        value: &ArcValue<'_>,
+    ) -> String {
+        let initial_len = self.rigid_env.names.len();
+        for name in binders {
+            self.rigid_env.names.push(*name);
+        }
+        let output = self.pretty_print_value(value);
+        self.rigid_env.names.truncate(initial_len);
+        output
+    }
+
+    /// Report a failure to unify `type0` with `type1`.
+    fn push_unification_error(
+        &mut self,
+        range: ByteRange,
+        type0: &ArcValue<'arena>,
+        type1: &ArcValue<'arena>,
+        error: unification::Error<ArcValue<'arena>>,
+    ) {
+        let lhs = self.pretty_print_value(type0);
+        let rhs = self.pretty_print_value(type1);
+        let error = error.map_mismatch(|mismatch| unification::Mismatch {
+            lhs: self.pretty_print_value_under_binders(&mismatch.binders, &mismatch.lhs),
+            rhs: self.pretty_print_value_under_binders(&mismatch.binders, &mismatch.rhs),
+            path: mismatch.path,
+            binders: mismatch.binders,
+        });
+
+        self.push_message(Message::FailedToUnify {
+            range,
+            lhs,
+            rhs,
+            error,
+        });
+    }
+
     /// Reports an error if there are duplicate fields found, returning a slice
     /// of the labels unique labels and an iterator over the unique fields.
     fn report_duplicate_labels<'fields, 'a>(
@@ -788,58 +1438,194 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         (labels.into(), filtered_fields)
     }
 
+    /// Parse the contents of a string literal, processing escape codes and
+    /// returning the characters along with the byte order suffix.
+    fn parse_string_literal(&mut self, range: ByteRange, source: &str) -> Option<StringLiteral> {
+        // The lexer ensures that string literals are delimited by quotes
+        let contents_end = source.rfind('"').unwrap();
+        let (contents, suffix) = (&source[1..contents_end], &source[(contents_end + 1)..]);
+        let contents_start = range.start() + 1;
+
+        let suffix_range = ByteRange::new(contents_start + contents.len() + 1, range.end());
+        let byte_order = match suffix {
+            "" => None,
+            "be" => Some((suffix_range, ByteOrder::Big)),
+            "le" => Some((suffix_range, ByteOrder::Little)),
+            _ => {
+                self.push_message(Message::InvalidStringLiteralSuffix {
+                    invalid_range: suffix_range,
+                    message: "expected `be` or `le`".to_owned(),
+                });
+                return None;
+            }
+        };
+
+        let mut chars = Vec::with_capacity(contents.len());
+        let mut is_valid = true;
+        let mut char_indices = contents.char_indices().peekable();
+
+        while let Some((start, ch)) = char_indices.next() {
+            let ch = match ch {
+                '\\' => {
+                    let escape = match char_indices.next() {
+                        Some((_, 'n')) => Ok(StringChar::Char('\n')),
+                        Some((_, 'r')) => Ok(StringChar::Char('\r')),
+                        Some((_, 't')) => Ok(StringChar::Char('\t')),
+                        Some((_, '0')) => Ok(StringChar::Char('\0')),
+                        Some((_, '\\')) => Ok(StringChar::Char('\\')),
+                        Some((_, '"')) => Ok(StringChar::Char('"')),
+                        Some((_, '\'')) => Ok(StringChar::Char('\'')),
+                        Some((_, 'x')) => {
+                            let digits = (0..2).filter_map(|_| {
+                                char_indices.next_if(|(_, ch)| ch.is_ascii_hexdigit())
+                            });
+                            let digits = digits.map(|(_, ch)| ch).collect::<String>();
+                            match u8::from_str_radix(&digits, 16) {
+                                Ok(byte) if digits.len() == 2 => Ok(StringChar::Byte(byte)),
+                                _ => Err("expected two hexadecimal digits"),
+                            }
+                        }
+                        Some((_, 'u')) => {
+                            let mut digits = String::new();
+                            let is_closed = match char_indices.next_if(|(_, ch)| *ch == '{') {
+                                Some(_) => loop {
+                                    match char_indices.next() {
+                                        Some((_, '}')) => break true,
+                                        Some((_, ch)) => digits.push(ch),
+                                        None => break false,
+                                    }
+                                },
+                                None => false,
+                            };
+                            match u32::from_str_radix(&digits, 16).ok().filter(|_| is_closed) {
+                                Some(code) if digits.len() <= 6 => match char::from_u32(code) {
+                                    Some(ch) => Ok(StringChar::Char(ch)),
+                                    None => Err("invalid unicode character"),
+                                },
+                                _ => Err(
+                                    "expected one to six hexadecimal digits, surrounded by braces",
+                                ),
+                            }
+                        }
+                        _ => Err("unknown escape code"),
+                    };
+
+                    let end = char_indices.peek().map_or(contents.len(), |(end, _)| *end);
+                    let escape_range = ByteRange::new(contents_start + start, contents_start + end);
+                    match escape {
+                        Ok(ch) => (escape_range, ch),
+                        Err(message) => {
+                            self.push_message(Message::InvalidStringEscape {
+                                invalid_range: escape_range,
+                                message: message.to_owned(),
+                            });
+                            is_valid = false;
+                            continue;
+                        }
+                    }
+                }
+                ch => {
+                    let ch_start = contents_start + start;
+                    let ch_range = ByteRange::new(ch_start, ch_start + ch.len_utf8());
+                    (ch_range, StringChar::Char(ch))
+                }
+            };
+            chars.push(ch);
+        }
+
+        is_valid.then(|| (chars, byte_order))
+    }
+
     /// Parse a source string into number, assuming an ASCII encoding.
-    fn parse_ascii<T>(&mut self, range: ByteRange, string_id: StringId) -> Option<T>
+    fn parse_ascii<T>(&mut self, range: ByteRange, source: &str) -> Option<(T, UIntStyle)>
     where
-        T: From<u8> + std::ops::Shl<Output = T> + std::ops::BitOr<Output = T>,
+        T: std::convert::TryFrom<u64>,
     {
-        // TODO: Parse escape codes
-        // TODO: Alternate byte orders
         // TODO: Non-ASCII encodings
 
-        let interner = self.interner.borrow();
-        let mut data = Some(T::from(0));
-        let mut count: u8 = 0;
+        let (chars, byte_order) = self.parse_string_literal(range, source)?;
+        let mut bytes = Some(Vec::with_capacity(chars.len()));
 
-        for (offset, ch) in interner.resolve(string_id).unwrap().char_indices() {
-            if !ch.is_ascii() {
-                let ch_start = range.start() + 1 + offset;
-                let ch_end = ch_start + ch.len_utf8();
-
-                self.push_message(Message::NonAsciiStringLiteral {
-                    invalid_range: ByteRange::new(ch_start, ch_end),
-                });
-                data = None;
+        for (ch_range, ch) in chars {
+            match ch {
+                StringChar::Byte(byte) => bytes.iter_mut().for_each(|bytes| bytes.push(byte)),
+                StringChar::Char(ch) if ch.is_ascii() => {
+                    bytes.iter_mut().for_each(|bytes| bytes.push(ch as u8))
+                }
+                StringChar::Char(_) => {
+                    self.push_message(Message::NonAsciiStringLiteral {
+                        invalid_range: ch_range,
+                    });
+                    bytes = None;
+                }
             }
-
-            data = data.filter(|_| usize::from(count) < std::mem::size_of::<T>());
-            data = data.map(|data| {
-                // Yikes this is a tad ugly. Setting the bytes in reverse order...
-                let offset = 8 * (std::mem::size_of::<T>() as u8 - (count + 1));
-                data | (T::from(ch as u8) << T::from(offset))
-            });
-            count += 1;
         }
 
-        if count as usize != std::mem::size_of::<T>() {
+        let mut bytes = bytes?;
+        if bytes.len() != std::mem::size_of::<T>() {
             self.push_message(Message::MismatchedStringLiteralByteLength {
                 range,
                 expected_len: std::mem::size_of::<T>(),
-                found_len: count as usize,
+                found_len: bytes.len(),
+            });
+            return None;
+        }
+
+        let style = match byte_order {
+            None | Some((_, ByteOrder::Big)) => UIntStyle::Ascii,
+            Some((_, ByteOrder::Little)) => {
+                bytes.reverse();
+                UIntStyle::AsciiLe
+            }
+        };
+        // Accumulate in a 64-bit integer, as shifting by a full byte would
+        // overflow single byte integers
+        let data = bytes
+            .into_iter()
+            .fold(0, |data, byte| (data << 8) | u64::from(byte));
+
+        Some((T::try_from(data).ok()?, style))
+    }
+
+    /// Parse a source string into a string, assuming a UTF-8 encoding.
+    fn parse_string(&mut self, range: ByteRange, source: &str) -> Option<&'arena str> {
+        let (chars, byte_order) = self.parse_string_literal(range, source)?;
+
+        if let Some((suffix_range, _)) = byte_order {
+            self.push_message(Message::InvalidStringLiteralSuffix {
+                invalid_range: suffix_range,
+                message: "byte orders are not supported for strings".to_owned(),
             });
-            data = None;
+            return None;
         }
 
-        data
+        let mut string = Some(String::with_capacity(chars.len()));
+        for (ch_range, ch) in chars {
+            match ch {
+                StringChar::Char(ch) => string.iter_mut().for_each(|string| string.push(ch)),
+                StringChar::Byte(byte) if byte.is_ascii() => string
+                    .iter_mut()
+                    .for_each(|string| string.push(char::from(byte))),
+                StringChar::Byte(_) => {
+                    self.push_message(Message::InvalidStringEscape {
+                        invalid_range: ch_range,
+                        message: "byte escapes in strings must be ASCII".to_owned(),
+                    });
+                    string = None;
+                }
+            }
+        }
+
+        Some(alloc::str_to_scope(self.scope, &string?))
     }
 
     /// Parse a source string into a number.
-    fn parse_number<T: FromStr>(&mut self, range: ByteRange, string_id: StringId) -> Option<T>
+    fn parse_number<T: FromStr>(&mut self, range: ByteRange, source: &str) -> Option<T>
     where
         T::Err: std::fmt::Display,
     {
         // TODO: Custom parsing and improved errors
-        match self.interner.borrow().resolve(string_id).unwrap().parse() {
+        match source.parse() {
             Ok(data) => Some(data),
             Err(error) => {
                 let message = error.to_string();
@@ -853,17 +1639,15 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
     fn parse_number_radix<T: FromStrRadix>(
         &mut self,
         range: ByteRange,
-        string_id: StringId,
+        source: &str,
     ) -> Option<(T, UIntStyle)> {
         // TODO: Custom parsing and improved errors
-        let s = self.interner.borrow();
-        let s = s.resolve(string_id).unwrap();
-        let (s, radix, style) = if s.starts_with("0x") {
-            (&s[2..], 16, UIntStyle::Hexadecimal)
-        } else if s.starts_with("0b") {
-            (&s[2..], 2, UIntStyle::Binary)
+        let (s, radix, style) = if let Some(s) = source.strip_prefix("0x") {
+            (s, 16, UIntStyle::Hexadecimal)
+        } else if let Some(s) = source.strip_prefix("0b") {
+            (s, 2, UIntStyle::Binary)
         } else {
-            (s, 10, UIntStyle::Decimal)
+            (source, 10, UIntStyle::Decimal)
         };
         match T::from_str_radix(s, radix) {
             Ok(data) => Some((data, style)),
@@ -875,8 +1659,14 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         }
     }
 
-    /// Conversion checking for `expr` under the types `type0` and `type1`.
-    /// This will trigger unification, recording a unification error on failure.
+    /// Conversion checking for `expr` under the types `type0` and `type1`,
+    /// where `type0` is allowed to be a [subtype][unification::Context::subtype]
+    /// of `type1`. This will trigger unification, recording a unification
+    /// error on failure.
+    ///
+    /// Universes are compared with flexible universes by recording universe
+    /// constraints, rather than by solving the flexible universes, so that
+    /// their levels can be raised by later uses.
     //
     // NOTE: We could eventually call this method `coerce` if we end up adding
     //       coercions to the core language.
@@ -887,17 +1677,33 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         type0: &ArcValue<'arena>,
         type1: &ArcValue<'arena>,
     ) -> core::Term<'arena> {
-        match self.unification_context().unify(type0, type1) {
+        if self.is_flexible_universe(type1)
+            && (self.is_flexible_universe(type0)
+                || matches!(
+                    self.elim_context().force(type0).as_ref(),
+                    Value::Universe(_)
+                ))
+        {
+            let expr_value = self.eval_context().eval(&expr);
+            self.push_universe_constraint(range, expr_value, type1.clone());
+            return expr;
+        }
+
+        match self.unification_context().subtype(type0, type1) {
             Ok(()) => expr,
             Err(error) => {
-                let lhs = self.pretty_print_value(type0);
-                let rhs = self.pretty_print_value(type1);
-                self.push_message(Message::FailedToUnify {
-                    range,
-                    lhs,
-                    rhs,
-                    error,
-                });
+                let forced_type0 = self.elim_context().force(type0);
+                let forced_type1 = self.elim_context().force(type1);
+                match (forced_type0.as_ref(), forced_type1.as_ref()) {
+                    (Value::Universe(found_level), Value::Universe(expected_level)) => {
+                        self.push_message(Message::MismatchedUniverseLevel {
+                            range,
+                            expected_level: *expected_level,
+                            found_level: *found_level,
+                        });
+                    }
+                    (_, _) => self.push_unification_error(range, type0, type1, error),
+                }
                 core::Term::Prim(Prim::ReportedError)
             }
         }
@@ -908,7 +1714,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         &mut self,
         pattern: &Pattern<ByteRange>,
         expected_type: &ArcValue<'arena>,
-    ) -> (CheckedPattern, ArcValue<'arena>) {
+    ) -> (CheckedPattern<'arena>, ArcValue<'arena>) {
         match pattern {
             Pattern::Name(range, name) => {
                 (CheckedPattern::Name(*range, *name), expected_type.clone())
@@ -919,17 +1725,20 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
             Pattern::StringLiteral(range, string) => {
                 let constant = match expected_type.match_prim_spine() {
                     Some((Prim::U8Type, [])) => self
-                        .parse_ascii(*range, *string)
-                        .map(|num| Const::U8(num, UIntStyle::Ascii)),
+                        .parse_ascii(*range, string)
+                        .map(|(num, style)| Const::U8(num, style)),
                     Some((Prim::U16Type, [])) => self
-                        .parse_ascii(*range, *string)
-                        .map(|num| Const::U16(num, UIntStyle::Ascii)),
+                        .parse_ascii(*range, string)
+                        .map(|(num, style)| Const::U16(num, style)),
                     Some((Prim::U32Type, [])) => self
-                        .parse_ascii(*range, *string)
-                        .map(|num| Const::U32(num, UIntStyle::Ascii)),
+                        .parse_ascii(*range, string)
+                        .map(|(num, style)| Const::U32(num, style)),
                     Some((Prim::U64Type, [])) => self
-                        .parse_ascii(*range, *string)
-                        .map(|num| Const::U64(num, UIntStyle::Ascii)),
+                        .parse_ascii(*range, string)
+                        .map(|(num, style)| Const::U64(num, style)),
+                    Some((Prim::StringType, [])) => {
+                        self.parse_string(*range, string).map(Const::String)
+                    }
                     // Some((Prim::Array8Type, [len, _])) => todo!(),
                     // Some((Prim::Array16Type, [len, _])) => todo!(),
                     // Some((Prim::Array32Type, [len, _])) => todo!(),
@@ -952,7 +1761,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                     ),
                     None => {
                         let source = FlexSource::ReportedErrorType(*range);
-                        let r#type = self.push_flexible_value(source, Arc::new(Value::Universe));
+                        let r#type = self.push_flexible_type(source, *range);
 
                         (CheckedPattern::ReportedError(*range), r#type)
                     }
@@ -961,23 +1770,23 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
             Pattern::NumberLiteral(range, number) => {
                 let constant = match expected_type.match_prim_spine() {
                     Some((Prim::U8Type, [])) => self
-                        .parse_number_radix(*range, *number)
+                        .parse_number_radix(*range, number)
                         .map(|(num, style)| Const::U8(num, style)),
                     Some((Prim::U16Type, [])) => self
-                        .parse_number_radix(*range, *number)
+                        .parse_number_radix(*range, number)
                         .map(|(num, style)| Const::U16(num, style)),
                     Some((Prim::U32Type, [])) => self
-                        .parse_number_radix(*range, *number)
+                        .parse_number_radix(*range, number)
                         .map(|(num, style)| Const::U32(num, style)),
                     Some((Prim::U64Type, [])) => self
-                        .parse_number_radix(*range, *number)
+                        .parse_number_radix(*range, number)
                         .map(|(num, style)| Const::U64(num, style)),
-                    Some((Prim::S8Type, [])) => self.parse_number(*range, *number).map(Const::S8),
-                    Some((Prim::S16Type, [])) => self.parse_number(*range, *number).map(Const::S16),
-                    Some((Prim::S32Type, [])) => self.parse_number(*range, *number).map(Const::S32),
-                    Some((Prim::S64Type, [])) => self.parse_number(*range, *number).map(Const::S64),
-                    Some((Prim::F32Type, [])) => self.parse_number(*range, *number).map(Const::F32),
-                    Some((Prim::F64Type, [])) => self.parse_number(*range, *number).map(Const::F64),
+                    Some((Prim::S8Type, [])) => self.parse_number(*range, number).map(Const::S8),
+                    Some((Prim::S16Type, [])) => self.parse_number(*range, number).map(Const::S16),
+                    Some((Prim::S32Type, [])) => self.parse_number(*range, number).map(Const::S32),
+                    Some((Prim::S64Type, [])) => self.parse_number(*range, number).map(Const::S64),
+                    Some((Prim::F32Type, [])) => self.parse_number(*range, number).map(Const::F32),
+                    Some((Prim::F64Type, [])) => self.parse_number(*range, number).map(Const::F64),
                     Some((Prim::ReportedError, _)) => None,
                     _ => {
                         let expected_type = self.pretty_print_value(expected_type);
@@ -996,7 +1805,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                     ),
                     None => {
                         let source = FlexSource::ReportedErrorType(*range);
-                        let r#type = self.push_flexible_value(source, Arc::new(Value::Universe));
+                        let r#type = self.push_flexible_type(source, *range);
 
                         (CheckedPattern::ReportedError(*range), r#type)
                     }
@@ -1021,7 +1830,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                     ),
                     None => {
                         let source = FlexSource::ReportedErrorType(*range);
-                        let r#type = self.push_flexible_value(source, Arc::new(Value::Universe));
+                        let r#type = self.push_flexible_type(source, *range);
 
                         (CheckedPattern::ReportedError(*range), r#type)
                     }
@@ -1034,33 +1843,33 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
     fn synth_pattern(
         &mut self,
         pattern: &Pattern<ByteRange>,
-    ) -> (CheckedPattern, ArcValue<'arena>) {
+    ) -> (CheckedPattern<'arena>, ArcValue<'arena>) {
         match pattern {
             Pattern::Name(range, name) => {
                 let source = FlexSource::NamedPatternType(*range, *name);
-                let r#type = self.push_flexible_value(source, Arc::new(Value::Universe));
+                let r#type = self.push_flexible_type(source, *range);
                 (CheckedPattern::Name(*range, *name), r#type)
             }
             Pattern::Placeholder(range) => {
                 let source = FlexSource::PlaceholderPatternType(*range);
-                let r#type = self.push_flexible_value(source, Arc::new(Value::Universe));
+                let r#type = self.push_flexible_type(source, *range);
                 (CheckedPattern::Placeholder(*range), r#type)
             }
             Pattern::StringLiteral(range, _) => {
                 self.push_message(Message::AmbiguousStringLiteral { range: *range });
                 let source = FlexSource::ReportedErrorType(*range);
-                let r#type = self.push_flexible_value(source, Arc::new(Value::Universe));
+                let r#type = self.push_flexible_type(source, *range);
                 (CheckedPattern::ReportedError(*range), r#type)
             }
             Pattern::NumberLiteral(range, _) => {
                 self.push_message(Message::AmbiguousNumericLiteral { range: *range });
                 let source = FlexSource::ReportedErrorType(*range);
-                let r#type = self.push_flexible_value(source, Arc::new(Value::Universe));
+                let r#type = self.push_flexible_type(source, *range);
                 (CheckedPattern::ReportedError(*range), r#type)
             }
             Pattern::BooleanLiteral(range, val) => {
                 let r#const = Const::Bool(*val);
-                let r#type = Arc::new(Value::prim(Prim::BoolType, []));
+                let r#type = semantics::prim_value(Prim::BoolType);
                 (CheckedPattern::Const(*range, r#const), r#type)
             }
         }
@@ -1072,29 +1881,21 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         pattern: &Pattern<ByteRange>,
         r#type: Option<&Term<'_, ByteRange>>,
         expected_type: &ArcValue<'arena>,
-    ) -> (CheckedPattern, ArcValue<'arena>) {
+    ) -> (CheckedPattern<'arena>, ArcValue<'arena>) {
         match r#type {
             None => self.check_pattern(pattern, &expected_type),
             Some(r#type) => {
-                let universe = Arc::new(Value::Universe);
                 let range = r#type.range();
-                let r#type = self.check(r#type, &universe);
+                let (r#type, _) = self.check_type(r#type);
                 let r#type = self.eval_context().eval(&r#type);
 
                 match self.unification_context().unify(&r#type, expected_type) {
                     Ok(()) => self.check_pattern(pattern, &r#type),
                     Err(error) => {
-                        let lhs = self.pretty_print_value(&r#type);
-                        let rhs = self.pretty_print_value(expected_type);
-                        self.push_message(Message::FailedToUnify {
-                            range,
-                            lhs,
-                            rhs,
-                            error,
-                        });
+                        self.push_unification_error(range, &r#type, expected_type, error);
 
                         let source = FlexSource::ReportedErrorType(range);
-                        let input_type = self.push_flexible_value(source, universe);
+                        let input_type = self.push_flexible_type(source, range);
 
                         (CheckedPattern::ReportedError(range), input_type)
                     }
@@ -1103,27 +1904,86 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         }
     }
 
-    /// Synthesize the type of an annotated pattern.
+    /// Synthesize the type of an annotated pattern, along with the universe
+    /// that the type inhabits.
     fn synth_ann_pattern(
         &mut self,
         pattern: &Pattern<ByteRange>,
         r#type: Option<&Term<'_, ByteRange>>,
-    ) -> (CheckedPattern, ArcValue<'arena>) {
+    ) -> (CheckedPattern<'arena>, ArcValue<'arena>, ArcValue<'arena>) {
         match r#type {
-            None => self.synth_pattern(pattern),
+            None => {
+                let range = pattern.range();
+                let (pattern, r#type) = self.synth_pattern(pattern);
+                let universe = self.type_universe(range, &r#type);
+                (pattern, r#type, universe)
+            }
             Some(r#type) => {
-                let r#type = self.check(r#type, &Arc::new(Value::Universe));
+                let (r#type, universe) = self.check_type(r#type);
                 let type_value = self.eval_context().eval(&r#type);
-                self.check_pattern(pattern, &type_value)
+                let (pattern, r#type) = self.check_pattern(pattern, &type_value);
+                (pattern, r#type, universe)
             }
         }
     }
 
+    /// Check that a surface term is a type, returning the elaborated term
+    /// along with the universe that the type inhabits.
+    fn check_type(
+        &mut self,
+        surface_term: &Term<'_, ByteRange>,
+    ) -> (core::Term<'arena>, ArcValue<'arena>) {
+        if let Term::UnitLiteral(_) = surface_term {
+            return (
+                core::Term::RecordType(&[], &[]),
+                semantics::universe_value(0),
+            );
+        }
+
+        let range = surface_term.range();
+        let (core_term, r#type) = self.synth(surface_term);
+        let r#type = self.elim_context().force(&r#type);
+        match r#type.as_ref() {
+            Value::Universe(_) => (core_term, r#type),
+            _ if self.is_flexible_universe(&r#type) => (core_term, r#type),
+            Value::Stuck(Head::Prim(Prim::ReportedError), _) => {
+                (core_term, semantics::universe_value(0))
+            }
+            // The universe is not known yet, so we constrain the type to
+            // inhabit a flexible universe
+            Value::Stuck(Head::FlexibleVar(_), _) => {
+                let universe = self.push_flexible_universe(range);
+                let core_term = self.convert(range, core_term, &r#type, &universe);
+                let type_value = self.eval_context().eval(&core_term);
+                self.push_universe_constraint(range, type_value, universe.clone());
+                (core_term, universe)
+            }
+            _ => {
+                let universe = semantics::universe_value(0);
+                let core_term = self.convert(range, core_term, &r#type, &universe);
+                (core_term, universe)
+            }
+        }
+    }
+
+    /// Return the universe inhabited by the type of a synthesized pattern.
+    /// Flexible pattern types are constrained to inhabit a flexible universe.
+    fn type_universe(&mut self, range: ByteRange, r#type: &ArcValue<'arena>) -> ArcValue<'arena> {
+        match self.elim_context().force(r#type).as_ref() {
+            Value::Stuck(Head::FlexibleVar(_), _) => {
+                let universe = self.push_flexible_universe(range);
+                self.push_universe_constraint(range, r#type.clone(), universe.clone());
+                universe
+            }
+            _ => semantics::universe_value(0),
+        }
+    }
+
     /// Push a rigid definition onto the context.
     /// The supplied `pattern` is expected to be irrefutable.
     fn push_rigid_def(
         &mut self,
-        pattern: CheckedPattern,
+        pattern: CheckedPattern<'arena>,
         expr: ArcValue<'arena>,
         r#type: ArcValue<'arena>,
     ) -> Option<StringId> {
@@ -1149,7 +2009,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
     /// The supplied `pattern` is expected to be irrefutable.
     fn push_rigid_param(
         &mut self,
-        pattern: CheckedPattern,
+        pattern: CheckedPattern<'arena>,
         r#type: ArcValue<'arena>,
     ) -> (Option<StringId>, ArcValue<'arena>) {
         let name = match pattern {
@@ -1182,7 +2042,8 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
 
         match (surface_term, expected_type.as_ref()) {
             (Term::Let(_, def_pattern, def_type, def_expr, output_expr), _) => {
-                let (def_pattern, def_type_value) = self.synth_ann_pattern(def_pattern, *def_type);
+                let (def_pattern, def_type_value, _) =
+                    self.synth_ann_pattern(def_pattern, *def_type);
                 let def_type = self.quote_context(self.scope).quote(&def_type_value); // FIXME: avoid requote if possible?
                 let def_expr = self.check(def_expr, &def_type_value);
                 let def_expr_value = self.eval_context().eval(&def_expr);
@@ -1226,6 +2087,22 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
 
                 core::Term::FunLit(input_name, self.scope.to_scope(output_expr))
             }
+            // Check function literals against errors in place of their input
+            // and output types, avoiding cascading errors for types that can
+            // no longer be inferred.
+            (
+                Term::FunLiteral(_, input_pattern, input_type, output_expr),
+                Value::Stuck(Head::Prim(Prim::ReportedError), _),
+            ) => {
+                let (input_name, input_type) =
+                    self.check_ann_pattern(input_pattern, *input_type, &expected_type);
+                let (input_name, _) = self.push_rigid_param(input_name, input_type);
+                let output_expr = self.check(output_expr, &expected_type);
+
+                self.rigid_env.pop();
+
+                core::Term::FunLit(input_name, self.scope.to_scope(output_expr))
+            }
             (Term::RecordLiteral(range, expr_fields), Value::RecordType(labels, types)) => {
                 // TODO: improve handling of duplicate labels
                 if expr_fields.len() != labels.len()
@@ -1257,7 +2134,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
 
                 core::Term::RecordLit(labels, exprs.into())
             }
-            (Term::UnitLiteral(_), Value::Universe) => core::Term::RecordType(&[], &[]),
+            (Term::UnitLiteral(_), Value::Universe(_)) => core::Term::RecordType(&[], &[]),
             (Term::UnitLiteral(_), _)
                 if matches!(
                     expected_type.match_prim_spine(),
@@ -1325,17 +2202,20 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
             (Term::StringLiteral(range, string), _) => {
                 let constant = match expected_type.match_prim_spine() {
                     Some((Prim::U8Type, [])) => self
-                        .parse_ascii(*range, *string)
-                        .map(|num| Const::U8(num, UIntStyle::Ascii)),
+                        .parse_ascii(*range, string)
+                        .map(|(num, style)| Const::U8(num, style)),
                     Some((Prim::U16Type, [])) => self
-                        .parse_ascii(*range, *string)
-                        .map(|num| Const::U16(num, UIntStyle::Ascii)),
+                        .parse_ascii(*range, string)
+                        .map(|(num, style)| Const::U16(num, style)),
                     Some((Prim::U32Type, [])) => self
-                        .parse_ascii(*range, *string)
-                        .map(|num| Const::U32(num, UIntStyle::Ascii)),
+                        .parse_ascii(*range, string)
+                        .map(|(num, style)| Const::U32(num, style)),
                     Some((Prim::U64Type, [])) => self
-                        .parse_ascii(*range, *string)
-                        .map(|num| Const::U64(num, UIntStyle::Ascii)),
+                        .parse_ascii(*range, string)
+                        .map(|(num, style)| Const::U64(num, style)),
+                    Some((Prim::StringType, [])) => {
+                        self.parse_string(*range, string).map(Const::String)
+                    }
                     // Some((Prim::Array8Type, [len, _])) => todo!(),
                     // Some((Prim::Array16Type, [len, _])) => todo!(),
                     // Some((Prim::Array32Type, [len, _])) => todo!(),
@@ -1359,23 +2239,23 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
             (Term::NumberLiteral(range, number), _) => {
                 let constant = match expected_type.match_prim_spine() {
                     Some((Prim::U8Type, [])) => self
-                        .parse_number_radix(*range, *number)
+                        .parse_number_radix(*range, number)
                         .map(|(num, style)| Const::U8(num, style)),
                     Some((Prim::U16Type, [])) => self
-                        .parse_number_radix(*range, *number)
+                        .parse_number_radix(*range, number)
                         .map(|(num, style)| Const::U16(num, style)),
                     Some((Prim::U32Type, [])) => self
-                        .parse_number_radix(*range, *number)
+                        .parse_number_radix(*range, number)
                         .map(|(num, style)| Const::U32(num, style)),
                     Some((Prim::U64Type, [])) => self
-                        .parse_number_radix(*range, *number)
+                        .parse_number_radix(*range, number)
                         .map(|(num, style)| Const::U64(num, style)),
-                    Some((Prim::S8Type, [])) => self.parse_number(*range, *number).map(Const::S8),
-                    Some((Prim::S16Type, [])) => self.parse_number(*range, *number).map(Const::S16),
-                    Some((Prim::S32Type, [])) => self.parse_number(*range, *number).map(Const::S32),
-                    Some((Prim::S64Type, [])) => self.parse_number(*range, *number).map(Const::S64),
-                    Some((Prim::F32Type, [])) => self.parse_number(*range, *number).map(Const::F32),
-                    Some((Prim::F64Type, [])) => self.parse_number(*range, *number).map(Const::F64),
+                    Some((Prim::S8Type, [])) => self.parse_number(*range, number).map(Const::S8),
+                    Some((Prim::S16Type, [])) => self.parse_number(*range, number).map(Const::S16),
+                    Some((Prim::S32Type, [])) => self.parse_number(*range, number).map(Const::S32),
+                    Some((Prim::S64Type, [])) => self.parse_number(*range, number).map(Const::S64),
+                    Some((Prim::F32Type, [])) => self.parse_number(*range, number).map(Const::F32),
+                    Some((Prim::F64Type, [])) => self.parse_number(*range, number).map(Const::F64),
                     Some((Prim::ReportedError, _)) => None,
                     _ => {
                         let expected_type = self.pretty_print_value(&expected_type);
@@ -1392,6 +2272,21 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                     None => core::Term::Prim(Prim::ReportedError),
                 }
             }
+            (Term::BinOp(range, lhs, op, rhs), _) => {
+                match Self::check_bin_op_prim(op.1, &expected_type) {
+                    Some((prim, rhs_type)) => {
+                        let lhs_expr = self.check(lhs, &expected_type);
+                        let rhs_type = semantics::prim_value(rhs_type);
+                        let rhs_expr = self.check(rhs, &rhs_type);
+
+                        self.bin_op_app(prim, lhs_expr, rhs_expr)
+                    }
+                    None => {
+                        let (core_term, synth_type) = self.synth_bin_op(*range, lhs, *op, rhs);
+                        self.convert(*range, core_term, &synth_type, &expected_type)
+                    }
+                }
+            }
             (Term::ReportedError(_), _) => core::Term::Prim(Prim::ReportedError),
             (_, _) => {
                 let (core_term, synth_type) = self.synth(surface_term);
@@ -1411,9 +2306,12 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
             Term::Name(range, name) => match self.get_name(*name) {
                 Some((term, r#type)) => (core::Term::RigidVar(term), r#type.clone()),
                 None => {
+                    let candidates = self.rigid_env.names.iter().flatten().copied();
+                    let suggestions = self.suggest_names(*name, candidates);
                     self.push_message(Message::UnboundName {
                         range: *range,
                         name: *name,
+                        suggestions,
                     });
                     self.synth_reported_error(*range)
                 }
@@ -1422,8 +2320,11 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                 let type_source = FlexSource::HoleType(*range, *name);
                 let expr_source = FlexSource::HoleExpr(*range, *name);
 
-                let r#type = self.push_flexible_value(type_source, Arc::new(Value::Universe));
+                let r#type = self.push_flexible_type(type_source, *range);
+                let var = self.flexible_env.exprs.len().next_global();
                 let expr = self.push_flexible_term(expr_source, r#type.clone());
+                self.push_hole_goal(*range, *name, var, &expr, &r#type);
+                self.push_flexible_expr_constraint(*range, &expr, &r#type);
 
                 (expr, r#type)
             }
@@ -1431,13 +2332,14 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                 let type_source = FlexSource::PlaceholderType(*range);
                 let expr_source = FlexSource::PlaceholderExpr(*range);
 
-                let r#type = self.push_flexible_value(type_source, Arc::new(Value::Universe));
+                let r#type = self.push_flexible_type(type_source, *range);
                 let expr = self.push_flexible_term(expr_source, r#type.clone());
+                self.push_flexible_expr_constraint(*range, &expr, &r#type);
 
                 (expr, r#type)
             }
             Term::Ann(_, expr, r#type) => {
-                let r#type = self.check(r#type, &Arc::new(Value::Universe)); // FIXME: avoid temporary Arc
+                let (r#type, _) = self.check_type(r#type);
                 let type_value = self.eval_context().eval(&r#type);
                 let expr = self.check(expr, &type_value);
 
@@ -1447,7 +2349,8 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                 (ann_expr, type_value)
             }
             Term::Let(_, def_pattern, def_type, def_expr, output_expr) => {
-                let (def_pattern, def_type_value) = self.synth_ann_pattern(def_pattern, *def_type);
+                let (def_pattern, def_type_value, _) =
+                    self.synth_ann_pattern(def_pattern, *def_type);
                 let def_type = self.quote_context(self.scope).quote(&def_type_value); // FIXME: avoid requote if possible?
                 let def_expr = self.check(def_expr, &def_type_value);
                 let def_expr_value = self.eval_context().eval(&def_expr);
@@ -1473,8 +2376,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                 // the match expression's output expressions, allowing us to
                 // unify them together.
                 let source = FlexSource::MatchOutputType(*range);
-                let universe = Arc::new(Value::Universe);
-                let output_type = self.push_flexible_value(source, universe);
+                let output_type = self.push_flexible_type(source, *range);
 
                 let match_expr = self.check_match(
                     true,
@@ -1488,14 +2390,22 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
 
                 (match_expr, output_type)
             }
-            Term::Universe(_) => (core::Term::Universe, Arc::new(Value::Universe)),
-            Term::Arrow(_, input_type, output_type) => {
-                let universe = Arc::new(Value::Universe); // FIXME: avoid temporary Arc
-                let input_type = self.check(input_type, &universe);
+            Term::Universe(range, level) => match level.checked_add(1) {
+                Some(next_level) => (
+                    core::Term::Universe(*level),
+                    semantics::universe_value(next_level),
+                ),
+                None => {
+                    self.push_message(Message::UniverseLevelOverflow { range: *range });
+                    self.synth_reported_error(*range)
+                }
+            },
+            Term::Arrow(range, input_type, output_type) => {
+                let (input_type, input_universe) = self.check_type(input_type);
                 let input_type_value = self.eval_context().eval(&input_type);
 
                 self.rigid_env.push_param(None, input_type_value);
-                let output_type = self.check(output_type, &universe);
+                let (output_type, output_universe) = self.check_type(output_type);
                 self.rigid_env.pop();
 
                 let fun_type = core::Term::FunType(
@@ -1503,17 +2413,18 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                     self.scope.to_scope(input_type),
                     self.scope.to_scope(output_type),
                 );
+                let universe =
+                    self.former_universe(*range, &fun_type, &[input_universe, output_universe]);
 
                 (fun_type, universe)
             }
-            Term::FunType(_, input_pattern, input_type, output_type) => {
-                let universe = Arc::new(Value::Universe); // FIXME: avoid temporary Arc
-                let (input_pattern, input_type_value) =
+            Term::FunType(range, input_pattern, input_type, output_type) => {
+                let (input_pattern, input_type_value, input_universe) =
                     self.synth_ann_pattern(input_pattern, *input_type);
                 let input_type = self.quote_context(self.scope).quote(&input_type_value); // FIXME: avoid requote if possible?
 
                 let (input_name, _) = self.push_rigid_param(input_pattern, input_type_value);
-                let output_type = self.check(output_type, &universe);
+                let (output_type, output_universe) = self.check_type(output_type);
                 self.rigid_env.pop();
 
                 let fun_type = core::Term::FunType(
@@ -1521,11 +2432,13 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                     self.scope.to_scope(input_type),
                     self.scope.to_scope(output_type),
                 );
+                let universe =
+                    self.former_universe(*range, &fun_type, &[input_universe, output_universe]);
 
                 (fun_type, universe)
             }
             Term::FunLiteral(_, input_pattern, input_type, output_expr) => {
-                let (input_pattern, input_type) =
+                let (input_pattern, input_type, _) =
                     self.synth_ann_pattern(input_pattern, *input_type);
 
                 let (input_name, _) = self.push_rigid_param(input_pattern, input_type.clone());
@@ -1559,21 +2472,23 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                     Value::Stuck(Head::Prim(Prim::ReportedError), _) => {
                         return self.synth_reported_error(*range);
                     }
+                    _ if matches!(head_expr, core::Term::Prim(Prim::ReportedError)) => {
+                        return self.synth_reported_error(*range);
+                    }
                     // It's not immediately obvious that the head type is a
                     // function type, so instead we construct a function type
                     // with flexible variables standing-in for the input and
                     // output types, and then we attempt to unify the head type
                     // against it.
                     _ => {
-                        let universe = Arc::new(Value::Universe);
                         // Create a flexible input type
                         let input_source = FlexSource::FunInputType(head_range);
-                        let input_type = self.push_flexible_value(input_source, universe.clone());
+                        let input_type = self.push_flexible_type(input_source, head_range);
 
                         // Create a flexible output type, with the input bound
                         self.rigid_env.push_param(None, input_type.clone());
                         let output_source = FlexSource::FunOutputType(head_range);
-                        let output_type = self.push_flexible_term(output_source, universe);
+                        let output_type = self.push_flexible_type_term(output_source, head_range);
                         self.rigid_env.pop();
 
                         // Create a function type between the flexible variables.
@@ -1609,22 +2524,52 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
 
                 (fun_app, output_type)
             }
+            Term::UnOp(range, (op_range, op), expr) => {
+                let expr_range = expr.range();
+                let (expr, r#type) = self.synth(expr);
+
+                if let core::Term::Prim(Prim::ReportedError) = expr {
+                    return self.synth_reported_error(*range);
+                }
+
+                let r#type = self.elim_context().force(&r#type);
+                if let Some((operand_type, [])) = r#type.match_prim_spine() {
+                    if let Some(prim) = op.prim(operand_type) {
+                        let head_expr = self.scope.to_scope(core::Term::Prim(prim));
+                        let un_op_app = core::Term::FunApp(head_expr, self.scope.to_scope(expr));
+                        return (un_op_app, r#type);
+                    }
+                }
+
+                let operand_type = self.pretty_print_value(&r#type);
+                self.push_message(Message::UnsupportedUnOp {
+                    op_range: *op_range,
+                    op: *op,
+                    expr_range,
+                    operand_type,
+                });
+                self.synth_reported_error(*range)
+            }
+            Term::BinOp(range, lhs, op, rhs) => self.synth_bin_op(*range, lhs, *op, rhs),
             Term::RecordType(range, type_fields) => {
-                let universe = Arc::new(Value::Universe);
                 let initial_rigid_len = self.rigid_env.len();
                 let (labels, type_fields) = self.report_duplicate_labels(*range, type_fields);
                 let mut types = SliceVec::new(self.scope, labels.len());
+                let mut universes = Vec::with_capacity(labels.len());
 
                 for ((_, label), r#type) in type_fields {
-                    let r#type = self.check(r#type, &universe);
+                    let (r#type, universe) = self.check_type(r#type);
                     let type_value = self.eval_context().eval(&r#type);
                     self.rigid_env.push_param(Some(*label), type_value);
                     types.push(r#type);
+                    universes.push(universe);
                 }
 
                 self.rigid_env.truncate(initial_rigid_len);
 
-                (core::Term::RecordType(labels, types.into()), universe)
+                let record_type = core::Term::RecordType(labels, types.into());
+                let universe = self.former_universe(*range, &record_type, &universes);
+                (record_type, universe)
             }
             Term::RecordLiteral(range, expr_fields) => {
                 let (labels, expr_fields) = self.report_duplicate_labels(*range, expr_fields);
@@ -1657,8 +2602,11 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                 let head_expr_value = self.eval_context().eval(&head_expr);
 
                 let head_type = self.elim_context().force(&head_type);
+                let mut suggestions = Vec::new();
                 match head_type.as_ref() {
                     Value::RecordType(labels, types) => {
+                        suggestions = self.suggest_names(*label, labels.iter().copied());
+
                         let mut labels = labels.iter();
                         let mut types = types.clone();
 
@@ -1686,6 +2634,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                     head_range,
                     label_range: *label_range,
                     label: *label,
+                    suggestions,
                 });
                 self.synth_reported_error(*range)
             }
@@ -1704,17 +2653,17 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                 self.synth_reported_error(*range)
             }
             Term::BooleanLiteral(_range, val) => {
-                let bool_type = Arc::new(Value::prim(Prim::BoolType, []));
+                let bool_type = semantics::prim_value(Prim::BoolType);
                 (core::Term::ConstLit(Const::Bool(*val)), bool_type)
             }
             Term::FormatRecord(range, format_fields) => {
-                let format_type = Arc::new(Value::prim(Prim::FormatType, []));
+                let format_type = semantics::prim_value(Prim::FormatType);
                 let (labels, formats) = self.check_format_fields(*range, format_fields);
 
                 (core::Term::FormatRecord(labels, formats), format_type)
             }
             Term::FormatOverlap(range, format_fields) => {
-                let format_type = Arc::new(Value::prim(Prim::FormatType, []));
+                let format_type = semantics::prim_value(Prim::FormatType);
                 let (labels, formats) = self.check_format_fields(*range, format_fields);
 
                 (core::Term::FormatOverlap(labels, formats), format_type)
@@ -1723,9 +2672,118 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         }
     }
 
+    /// Lookup the primitive operation for a binary operator, if both of its
+    /// operands can be checked using the expected type of the operator.
+    ///
+    /// Returns the primitive along with the type of the right operand.
+    fn check_bin_op_prim(op: BinOp, expected_type: &ArcValue<'arena>) -> Option<(Prim, Prim)> {
+        match expected_type.match_prim_spine()? {
+            (lhs_type, []) if !op.is_comparison() => {
+                let rhs_type = if op.is_shift() {
+                    Prim::U8Type
+                } else {
+                    lhs_type
+                };
+                Some((op.prim(lhs_type, rhs_type)?, rhs_type))
+            }
+            _ => None,
+        }
+    }
+
+    /// Synthesize the type of a binary operator application, elaborating it
+    /// to the primitive operation that matches the types of its operands.
+    fn synth_bin_op(
+        &mut self,
+        range: ByteRange,
+        lhs: &Term<'_, ByteRange>,
+        (op_range, op): (ByteRange, BinOp),
+        rhs: &Term<'_, ByteRange>,
+    ) -> (core::Term<'arena>, ArcValue<'arena>) {
+        let is_literal = |term: &Term<'_, _>| {
+            matches!(term, Term::NumberLiteral(_, _) | Term::StringLiteral(_, _))
+        };
+
+        let (lhs_expr, lhs_type, rhs_expr, rhs_type) =
+            if is_literal(lhs) && !is_literal(rhs) && !op.is_shift() {
+                // Use the type of the right operand to elaborate literals on
+                // the left, for example in `1 + x`.
+                let (rhs_expr, rhs_type) = self.synth(rhs);
+                let lhs_expr = self.check(lhs, &rhs_type);
+                (lhs_expr, rhs_type.clone(), rhs_expr, rhs_type)
+            } else {
+                let (lhs_expr, lhs_type) = self.synth(lhs);
+                if let core::Term::Prim(Prim::ReportedError) = lhs_expr {
+                    // Avoid reporting cascading errors for ambiguous literals
+                    if !is_literal(rhs) {
+                        self.synth(rhs);
+                    }
+                    return self.synth_reported_error(range);
+                }
+
+                let lhs_type = self.elim_context().force(&lhs_type);
+                let (rhs_expr, rhs_type) = match lhs_type.match_prim_spine() {
+                    // Positions can be offset by any unsigned integer type
+                    Some((Prim::PosType, [])) if op == BinOp::Add => self.synth(rhs),
+                    _ if op.is_shift() => {
+                        let u8_type = semantics::prim_value(Prim::U8Type);
+                        (self.check(rhs, &u8_type), u8_type)
+                    }
+                    _ => (self.check(rhs, &lhs_type), lhs_type.clone()),
+                };
+                (lhs_expr, lhs_type, rhs_expr, rhs_type)
+            };
+
+        if let (core::Term::Prim(Prim::ReportedError), _)
+        | (_, core::Term::Prim(Prim::ReportedError)) = (&lhs_expr, &rhs_expr)
+        {
+            return self.synth_reported_error(range);
+        }
+
+        let lhs_type = self.elim_context().force(&lhs_type);
+        let rhs_type = self.elim_context().force(&rhs_type);
+        if let (Some((lhs_prim, [])), Some((rhs_prim, []))) =
+            (lhs_type.match_prim_spine(), rhs_type.match_prim_spine())
+        {
+            if let Some(prim) = op.prim(lhs_prim, rhs_prim) {
+                let output_type = match op.is_comparison() {
+                    true => semantics::prim_value(Prim::BoolType),
+                    false => lhs_type,
+                };
+                return (self.bin_op_app(prim, lhs_expr, rhs_expr), output_type);
+            }
+        }
+
+        let lhs_type = self.pretty_print_value(&lhs_type);
+        let rhs_type = self.pretty_print_value(&rhs_type);
+        self.push_message(Message::UnsupportedBinOp {
+            op_range,
+            op,
+            lhs_range: lhs.range(),
+            lhs_type,
+            rhs_range: rhs.range(),
+            rhs_type,
+        });
+        self.synth_reported_error(range)
+    }
+
+    /// Construct the application of a primitive binary operation.
+    fn bin_op_app(
+        &self,
+        prim: Prim,
+        lhs_expr: core::Term<'arena>,
+        rhs_expr: core::Term<'arena>,
+    ) -> core::Term<'arena> {
+        let head_expr = self.scope.to_scope(core::Term::Prim(prim));
+        let head_expr = core::Term::FunApp(head_expr, self.scope.to_scope(lhs_expr));
+        core::Term::FunApp(
+            self.scope.to_scope(head_expr),
+            self.scope.to_scope(rhs_expr),
+        )
+    }
+
     fn synth_reported_error(&mut self, range: ByteRange) -> (core::Term<'arena>, ArcValue<'arena>) {
         let type_source = FlexSource::ReportedErrorType(range);
-        let r#type = self.push_flexible_value(type_source, Arc::new(Value::Universe));
+        let r#type = self.push_flexible_type(type_source, range);
         (core::Term::Prim(Prim::ReportedError), r#type)
     }
 
@@ -1735,7 +2793,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
         range: ByteRange,
         format_fields: &[((ByteRange, StringId), Term<'_, ByteRange>)],
     ) -> (&'arena [StringId], &'arena [core::Term<'arena>]) {
-        let format_type = Arc::new(Value::prim(Prim::FormatType, []));
+        let format_type = semantics::prim_value(Prim::FormatType);
         let initial_rigid_len = self.rigid_env.len();
         let (labels, format_fields) = self.report_duplicate_labels(range, format_fields);
         let mut formats = SliceVec::new(self.scope, labels.len());
@@ -1843,9 +2901,7 @@ impl<'interner, 'arena, 'error> Context<'interner, 'arena, 'error> {
                                     // Find insertion index
                                     let res = branches.binary_search_by(
                                         |(probe_const, _term): &(Const, _)| {
-                                            probe_const
-                                                .partial_cmp(&r#const)
-                                                .expect("attempt to compare non-ordered value")
+                                            probe_const.cmp(&r#const)
                                         },
                                     );
                                     match res {
@@ -2,8 +2,10 @@ use codespan_reporting::diagnostic::{Diagnostic, Label};
 use itertools::Itertools;
 use std::cell::RefCell;
 
+use crate::core::UniverseLevel;
 use crate::source::{ByteRange, FileId};
 use crate::surface::elaboration::{unification, FlexSource};
+use crate::surface::{BinOp, UnOp};
 use crate::{StringId, StringInterner};
 
 /// Elaboration diagnostic messages.
@@ -13,6 +15,8 @@ pub enum Message {
     UnboundName {
         range: ByteRange,
         name: StringId,
+        /// Similar names that are in scope.
+        suggestions: Vec<StringId>,
     },
     RefutablePattern {
         pattern_range: ByteRange,
@@ -30,6 +34,8 @@ pub enum Message {
         // head_type: Doc<_>,
         label_range: ByteRange,
         label: StringId,
+        /// Similar labels that are in the head type.
+        suggestions: Vec<StringId>,
     },
     MismatchedFieldLabels {
         range: ByteRange,
@@ -65,6 +71,14 @@ pub enum Message {
     NonAsciiStringLiteral {
         invalid_range: ByteRange,
     },
+    InvalidStringEscape {
+        invalid_range: ByteRange,
+        message: String,
+    },
+    InvalidStringLiteralSuffix {
+        invalid_range: ByteRange,
+        message: String,
+    },
     StringLiteralNotSupported {
         range: ByteRange,
         expected_type: String,
@@ -83,12 +97,39 @@ pub enum Message {
     BooleanLiteralNotSupported {
         range: ByteRange,
     },
+    /// The unary operator is not defined for the type of its operand.
+    UnsupportedUnOp {
+        op_range: ByteRange,
+        op: UnOp,
+        expr_range: ByteRange,
+        operand_type: String,
+    },
+    /// The binary operator is not defined for the types of its operands.
+    UnsupportedBinOp {
+        op_range: ByteRange,
+        op: BinOp,
+        lhs_range: ByteRange,
+        lhs_type: String,
+        rhs_range: ByteRange,
+        rhs_type: String,
+    },
+    /// The level of a universe was too large to be represented.
+    UniverseLevelOverflow {
+        range: ByteRange,
+    },
+    /// A type was found in a higher universe than the one it was expected to
+    /// inhabit.
+    MismatchedUniverseLevel {
+        range: ByteRange,
+        expected_level: UniverseLevel,
+        found_level: UniverseLevel,
+    },
     /// Unification errors.
     FailedToUnify {
         range: ByteRange,
         lhs: String,
         rhs: String,
-        error: unification::Error,
+        error: unification::Error<String>,
     },
     /// A solution for a flexible variable could not be found.
     UnsolvedFlexibleVar {
@@ -96,12 +137,15 @@ pub enum Message {
         // TODO: add type
         // type: Doc<_>,
     },
-    HoleSolution {
+    /// The goal of a named hole, along with its solution if one was found.
+    HoleGoal {
         range: ByteRange,
         name: StringId,
-        // TODO: add type
-        // type: Doc<_>,
-        expr: String,
+        /// The expected type of the hole.
+        r#type: String,
+        /// The names and types of the local binders in scope of the hole.
+        context: Vec<(StringId, String)>,
+        solution: Option<String>,
     },
 }
 
@@ -112,7 +156,11 @@ impl Message {
         file_id: FileId,
     ) -> Diagnostic<FileId> {
         match self {
-            Message::UnboundName { range, name } => {
+            Message::UnboundName {
+                range,
+                name,
+                suggestions,
+            } => {
                 let interner = interner.borrow();
                 let name = interner.resolve(*name).unwrap();
 
@@ -121,7 +169,7 @@ impl Message {
                     .with_labels(vec![
                         Label::primary(file_id, *range).with_message("unbound name")
                     ])
-                // TODO: list suggestions
+                    .with_notes(suggestion_notes(&interner, suggestions))
             }
             Message::RefutablePattern { pattern_range } => Diagnostic::error()
                 .with_message("refutable patterns found in binding")
@@ -147,6 +195,7 @@ impl Message {
                 head_range,
                 label_range,
                 label,
+                suggestions,
             } => {
                 let interner = interner.borrow();
                 let label = interner.resolve(*label).unwrap();
@@ -158,7 +207,7 @@ impl Message {
                         Label::secondary(file_id, *head_range)
                             .with_message(format!("head expression")),
                     ])
-                // TODO: list suggestions
+                    .with_notes(suggestion_notes(&interner, suggestions))
             }
             Message::MismatchedFieldLabels {
                 range,
@@ -297,6 +346,22 @@ impl Message {
                 .with_labels(vec![
                     Label::primary(file_id, *invalid_range).with_message("non-ASCII character")
                 ]),
+            Message::InvalidStringEscape {
+                invalid_range,
+                message,
+            } => Diagnostic::error()
+                .with_message("invalid escape code in string literal")
+                .with_labels(vec![
+                    Label::primary(file_id, *invalid_range).with_message(message)
+                ]),
+            Message::InvalidStringLiteralSuffix {
+                invalid_range,
+                message,
+            } => Diagnostic::error()
+                .with_message("invalid suffix on string literal")
+                .with_labels(vec![
+                    Label::primary(file_id, *invalid_range).with_message(message)
+                ]),
             Message::StringLiteralNotSupported {
                 range,
                 expected_type,
@@ -329,27 +394,110 @@ impl Message {
             Message::BooleanLiteralNotSupported { range } => Diagnostic::error()
                 .with_message("boolean literal not supported for expected type")
                 .with_labels(vec![Label::primary(file_id, *range)]),
+            Message::UnsupportedUnOp {
+                op_range,
+                op,
+                expr_range,
+                operand_type,
+            } => Diagnostic::error()
+                .with_message(format!(
+                    "cannot apply `{}` to a value of type `{}`",
+                    op.symbol(),
+                    operand_type,
+                ))
+                .with_labels(vec![
+                    Label::primary(file_id, *op_range).with_message("unsupported operator"),
+                    Label::secondary(file_id, *expr_range)
+                        .with_message(format!("has type `{}`", operand_type)),
+                ]),
+            Message::UnsupportedBinOp {
+                op_range,
+                op,
+                lhs_range,
+                lhs_type,
+                rhs_range,
+                rhs_type,
+            } => Diagnostic::error()
+                .with_message(format!(
+                    "cannot apply `{}` to values of type `{}` and `{}`",
+                    op.symbol(),
+                    lhs_type,
+                    rhs_type,
+                ))
+                .with_labels(vec![
+                    Label::primary(file_id, *op_range).with_message("unsupported operator"),
+                    Label::secondary(file_id, *lhs_range)
+                        .with_message(format!("has type `{}`", lhs_type)),
+                    Label::secondary(file_id, *rhs_range)
+                        .with_message(format!("has type `{}`", rhs_type)),
+                ]),
+            Message::UniverseLevelOverflow { range } => Diagnostic::error()
+                .with_message("universe level is too large")
+                .with_labels(vec![Label::primary(file_id, *range)]),
+            Message::MismatchedUniverseLevel {
+                range,
+                expected_level,
+                found_level,
+            } => {
+                let universe = |level| match level {
+                    0 => "Type".to_owned(),
+                    level => format!("Type{}", level),
+                };
+                Diagnostic::error()
+                    .with_message("mismatched universe levels")
+                    .with_labels(vec![Label::primary(file_id, *range).with_message(format!(
+                        "type is in `{}`, but was expected to be in `{}`",
+                        universe(*found_level),
+                        universe(*expected_level),
+                    ))])
+            }
             Message::FailedToUnify {
                 range,
                 lhs,
                 rhs,
                 error,
             } => {
-                use unification::{Error, RenameError, SpineError};
+                use unification::{Error, PathSegment, RenameError, SpineError};
 
                 // TODO: Make these errors more user-friendly
                 match error {
-                    Error::Mismatch => Diagnostic::error()
-                        .with_message("mismatched types")
-                        .with_labels(vec![Label::primary(file_id, *range).with_message(format!(
-                            "type mismatch, expected `{}`, found `{}`",
-                            lhs, rhs
-                        ))])
-                        .with_notes(vec![[
+                    Error::Mismatch(mismatch) => {
+                        let mut notes = vec![[
                             format!("expected `{}`", lhs),
                             format!("   found `{}`", rhs),
                         ]
-                        .join("\n")]),
+                        .join("\n")];
+
+                        if !mismatch.path.is_empty() {
+                            let interner = interner.borrow();
+                            let path = mismatch.path.iter().map(|segment| match segment {
+                                PathSegment::FunInputType => "input type".to_owned(),
+                                PathSegment::FunOutputType => "output type".to_owned(),
+                                PathSegment::FunOutputExpr => "output expression".to_owned(),
+                                PathSegment::FunArg(index) => format!("argument {}", index),
+                                PathSegment::RecordField(label) => {
+                                    format!("field `{}`", interner.resolve(*label).unwrap())
+                                }
+                                PathSegment::ArrayElem(index) => format!("element {}", index),
+                            });
+
+                            notes.push(
+                                [
+                                    format!("mismatch in {}:", path.format(", ")),
+                                    format!("  expected `{}`", mismatch.lhs),
+                                    format!("     found `{}`", mismatch.rhs),
+                                ]
+                                .join("\n"),
+                            );
+                        }
+
+                        Diagnostic::error()
+                            .with_message("mismatched types")
+                            .with_labels(vec![Label::primary(file_id, *range).with_message(
+                                format!("type mismatch, expected `{}`, found `{}`", lhs, rhs),
+                            )])
+                            .with_notes(notes)
+                    }
                     // TODO: reduce confusion around ‘problem spines’
                     Error::Spine(error) => match error {
                         SpineError::NonLinearSpine(_var) => Diagnostic::error()
@@ -375,19 +523,41 @@ impl Message {
                     },
                 }
             }
-            Message::HoleSolution { range, name, expr } => {
+            Message::HoleGoal {
+                range,
+                name,
+                r#type,
+                context,
+                solution,
+            } => {
                 let interner = interner.borrow();
                 let name = interner.resolve(*name).unwrap();
 
-                Diagnostic::note()
-                    .with_message(format!("solution found for hole `?{}`", name))
-                    .with_labels(vec![
-                        Label::primary(file_id, *range).with_message("solution found")
-                    ])
-                    .with_notes(vec![format!(
-                        "hole `?{}` can be replaced with `{}`",
-                        name, expr,
-                    )])
+                let mut notes = vec![format!("hole `?{}` has type `{}`", name, r#type)];
+                if !context.is_empty() {
+                    let bindings = context.iter().map(|(name, r#type)| {
+                        format!("  {} : {}", interner.resolve(*name).unwrap(), r#type)
+                    });
+                    notes.push(format!("local bindings:\n{}", bindings.format("\n")));
+                }
+
+                match solution {
+                    Some(expr) => {
+                        notes.push(format!("hole `?{}` can be replaced with `{}`", name, expr));
+
+                        Diagnostic::note()
+                            .with_message(format!("solution found for hole `?{}`", name))
+                            .with_labels(vec![
+                                Label::primary(file_id, *range).with_message("solution found")
+                            ])
+                            .with_notes(notes)
+                    }
+                    None => Diagnostic::error()
+                        .with_message("failed to infer hole expression")
+                        .with_labels(vec![Label::primary(file_id, *range)
+                            .with_message("unsolved hole expression")])
+                        .with_notes(notes),
+                }
             }
             Message::UnsolvedFlexibleVar { source } => {
                 let (range, source_name) = match source {
@@ -403,6 +573,7 @@ impl Message {
                     FlexSource::FunInputType(range) => (*range, "function input type"),
                     FlexSource::FunOutputType(range) => (*range, "function output type"),
                     FlexSource::ReportedErrorType(range) => (*range, "error type"), // should never appear in user-facing output
+                    FlexSource::Universe(range) => (*range, "universe"), // should never appear in user-facing output
                 };
 
                 Diagnostic::error()
@@ -413,3 +584,16 @@ impl Message {
         }
     }
 }
+
+/// Help notes that suggest some similar names.
+fn suggestion_notes(interner: &StringInterner, suggestions: &[StringId]) -> Vec<String> {
+    let names = suggestions
+        .iter()
+        .map(|name| format!("`{}`", interner.resolve(*name).unwrap()));
+
+    match suggestions {
+        [] => Vec::new(),
+        [_] => vec![format!("help: did you mean {}?", names.format(""))],
+        [_, ..] => vec![format!("help: did you mean one of {}?", names.format(", "))],
+    }
+}
@@ -26,6 +26,14 @@ use crate::core::{Prim, Term};
 use crate::env::{EnvLen, GlobalVar, LocalVar, SharedEnv, SliceEnv, UniqueEnv};
 use crate::StringId;
 
+/// A comparison between two values, either [`Context::unify`] or
+/// [`Context::subtype`].
+type Compare<'arena, 'env> = fn(
+    &mut Context<'arena, 'env>,
+    &ArcValue<'arena>,
+    &ArcValue<'arena>,
+) -> Result<(), Error<ArcValue<'arena>>>;
+
 /// Errors encountered during unification.
 ///
 /// The documentation for the various pattern unification errors were adapted
@@ -34,31 +42,110 @@ use crate::StringId;
 ///
 /// [elaboration-zoo]: https://github.com/AndrasKovacs/elaboration-zoo/
 /// [comments about pattern unification]: https://github.com/AndrasKovacs/elaboration-zoo/blob/d38b695d5177352501463fab2ac6d0929ba4472b/03-holes/Main.hs#L118-L169
+///
+/// The parts of the values that failed to match are stored as `T`, allowing
+/// them to be pretty-printed before they are reported.
 #[derive(Debug, Clone)]
-pub enum Error {
+pub enum Error<T> {
     /// A known part of one value failed to match with a known part of the other
     /// value that we are comparing against.
-    //
-    // TODO: Return some sort of type-diff
-    Mismatch,
+    Mismatch(Mismatch<T>),
     /// An error that was found in the problem spine.
     Spine(SpineError),
     /// An error that occurred when renaming the solution.
     Rename(RenameError),
 }
 
-impl From<SpineError> for Error {
-    fn from(error: SpineError) -> Error {
+impl<T> Error<T> {
+    /// Apply a function to the mismatching parts of a [`Error::Mismatch`].
+    pub fn map_mismatch<U>(self, f: impl FnOnce(Mismatch<T>) -> Mismatch<U>) -> Error<U> {
+        match self {
+            Error::Mismatch(mismatch) => Error::Mismatch(f(mismatch)),
+            Error::Spine(error) => Error::Spine(error),
+            Error::Rename(error) => Error::Rename(error),
+        }
+    }
+
+    /// Record that the error was found in the `segment` of the values being
+    /// compared.
+    fn within(mut self, segment: PathSegment) -> Error<T> {
+        if let Error::Mismatch(mismatch) = &mut self {
+            mismatch.path.insert(0, segment);
+        }
+        self
+    }
+
+    /// Swap the mismatching parts, for when the values were compared in the
+    /// opposite order.
+    fn swap(mut self) -> Error<T> {
+        if let Error::Mismatch(mismatch) = &mut self {
+            std::mem::swap(&mut mismatch.lhs, &mut mismatch.rhs);
+        }
+        self
+    }
+
+    /// Record that the error was found underneath a binder.
+    fn under_binder(mut self, name: Option<StringId>) -> Error<T> {
+        if let Error::Mismatch(mismatch) = &mut self {
+            mismatch.binders.insert(0, name);
+        }
+        self
+    }
+}
+
+impl<T> From<SpineError> for Error<T> {
+    fn from(error: SpineError) -> Error<T> {
         Error::Spine(error)
     }
 }
 
-impl From<RenameError> for Error {
-    fn from(error: RenameError) -> Error {
+impl<T> From<RenameError> for Error<T> {
+    fn from(error: RenameError) -> Error<T> {
         Error::Rename(error)
     }
 }
 
+/// The first place where the values being compared failed to match.
+#[derive(Debug, Clone)]
+pub struct Mismatch<T> {
+    /// The path to the mismatching parts, starting from the outermost values.
+    pub path: Vec<PathSegment>,
+    /// The names of the binders that the mismatching parts were found under.
+    pub binders: Vec<Option<StringId>>,
+    /// The mismatching part of the first value.
+    pub lhs: T,
+    /// The mismatching part of the second value.
+    pub rhs: T,
+}
+
+impl<'arena> Mismatch<ArcValue<'arena>> {
+    fn new(lhs: &ArcValue<'arena>, rhs: &ArcValue<'arena>) -> Mismatch<ArcValue<'arena>> {
+        Mismatch {
+            path: Vec::new(),
+            binders: Vec::new(),
+            lhs: lhs.clone(),
+            rhs: rhs.clone(),
+        }
+    }
+}
+
+/// A step into the parts of a value.
+#[derive(Debug, Copy, Clone)]
+pub enum PathSegment {
+    /// The input type of a function type.
+    FunInputType,
+    /// The output type of a function type.
+    FunOutputType,
+    /// The output expression of a function literal.
+    FunOutputExpr,
+    /// An argument in a function application, starting from `1`.
+    FunArg(usize),
+    /// A field of a record type, record literal, or record format.
+    RecordField(StringId),
+    /// An element of an array literal, starting from `0`.
+    ArrayElem(usize),
+}
+
 /// An error that was found in the problem spine.
 #[derive(Debug, Clone)]
 pub enum SpineError {
@@ -189,7 +276,7 @@ impl<'arena, 'env> Context<'arena, 'env> {
         &mut self,
         value0: &ArcValue<'arena>,
         value1: &ArcValue<'arena>,
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error<ArcValue<'arena>>> {
         // Check for pointer equality before trying to force the values
         if Arc::ptr_eq(value0, value1) {
             return Ok(());
@@ -211,64 +298,76 @@ impl<'arena, 'env> Context<'arena, 'env> {
             (Value::Stuck(Head::Prim(prim0), spine0), Value::Stuck(Head::Prim(prim1), spine1))
                 if prim0 == prim1 =>
             {
-                self.unify_spines(spine0, spine1)
+                self.unify_spines(&value0, &value1, spine0, spine1)
             }
             (
                 Value::Stuck(Head::RigidVar(var0), spine0),
                 Value::Stuck(Head::RigidVar(var1), spine1),
-            ) if var0 == var1 => self.unify_spines(spine0, spine1),
+            ) if var0 == var1 => self.unify_spines(&value0, &value1, spine0, spine1),
             (
                 Value::Stuck(Head::FlexibleVar(var0), spine0),
                 Value::Stuck(Head::FlexibleVar(var1), spine1),
-            ) if var0 == var1 => self.unify_spines(spine0, spine1),
+            ) if var0 == var1 => self.unify_spines(&value0, &value1, spine0, spine1),
 
-            (Value::Universe, Value::Universe) => Ok(()),
+            (Value::Universe(level0), Value::Universe(level1)) if level0 == level1 => Ok(()),
 
             (
-                Value::FunType(_, input_type0, output_type0),
+                Value::FunType(name, input_type0, output_type0),
                 Value::FunType(_, input_type1, output_type1),
             ) => {
-                self.unify(input_type0, input_type1)?;
-                self.unify_closures(output_type0, output_type1)
+                (self.unify(input_type0, input_type1))
+                    .map_err(|error| error.within(PathSegment::FunInputType))?;
+                (self.unify_closures(Self::unify, *name, output_type0, output_type1))
+                    .map_err(|error| error.within(PathSegment::FunOutputType))
             }
-            (Value::FunLit(_, output_expr0), Value::FunLit(_, output_expr1)) => {
-                self.unify_closures(output_expr0, output_expr1)
+            (Value::FunLit(name, output_expr0), Value::FunLit(_, output_expr1)) => (self
+                .unify_closures(Self::unify, *name, output_expr0, output_expr1))
+            .map_err(|error| error.within(PathSegment::FunOutputExpr)),
+            (Value::FunLit(name, output_expr), _) => {
+                self.unify_fun_lit(*name, output_expr, &value1)
+            }
+            (_, Value::FunLit(name, output_expr)) => {
+                (self.unify_fun_lit(*name, output_expr, &value0)).map_err(Error::swap)
             }
-            (Value::FunLit(_, output_expr), _) => self.unify_fun_lit(output_expr, &value1),
-            (_, Value::FunLit(_, output_expr)) => self.unify_fun_lit(output_expr, &value0),
 
             (Value::RecordType(labels0, types0), Value::RecordType(labels1, types1)) => {
                 if labels0 != labels1 {
-                    return Err(Error::Mismatch);
+                    return Err(Error::Mismatch(Mismatch::new(&value0, &value1)));
                 }
-                self.unify_telescopes(types0, types1)
+                self.unify_telescopes(Self::unify, labels0, types0, types1)
             }
             (Value::RecordLit(labels0, exprs0), Value::RecordLit(labels1, exprs1)) => {
                 if labels0 != labels1 {
-                    return Err(Error::Mismatch);
+                    return Err(Error::Mismatch(Mismatch::new(&value0, &value1)));
                 }
-                for (expr0, expr1) in Iterator::zip(exprs0.iter(), exprs1.iter()) {
-                    self.unify(&expr0, &expr1)?;
+                for (label, (expr0, expr1)) in
+                    Iterator::zip(labels0.iter(), Iterator::zip(exprs0.iter(), exprs1.iter()))
+                {
+                    (self.unify(expr0, expr1))
+                        .map_err(|error| error.within(PathSegment::RecordField(*label)))?;
                 }
                 Ok(())
             }
             (Value::RecordLit(labels, exprs), _) => self.unify_record_lit(labels, exprs, &value1),
-            (_, Value::RecordLit(labels, exprs)) => self.unify_record_lit(labels, exprs, &value0),
+            (_, Value::RecordLit(labels, exprs)) => {
+                (self.unify_record_lit(labels, exprs, &value0)).map_err(Error::swap)
+            }
 
             (Value::ArrayLit(elem_exprs0), Value::ArrayLit(elem_exprs1)) => {
-                for (elem_expr0, elem_expr1) in
-                    Iterator::zip(elem_exprs0.iter(), elem_exprs1.iter())
+                for (index, (elem_expr0, elem_expr1)) in
+                    Iterator::zip(elem_exprs0.iter(), elem_exprs1.iter()).enumerate()
                 {
-                    self.unify(&elem_expr0, &elem_expr1)?;
+                    (self.unify(elem_expr0, elem_expr1))
+                        .map_err(|error| error.within(PathSegment::ArrayElem(index)))?;
                 }
                 Ok(())
             }
 
             (Value::FormatRecord(labels0, formats0), Value::FormatRecord(labels1, formats1)) => {
                 if labels0 != labels1 {
-                    return Err(Error::Mismatch);
+                    return Err(Error::Mismatch(Mismatch::new(&value0, &value1)));
                 }
-                self.unify_telescopes(formats0, formats1)
+                self.unify_telescopes(Self::unify, labels0, formats0, formats1)
             }
 
             (Value::ConstLit(const0), Value::ConstLit(const1)) if const0 == const1 => Ok(()),
@@ -284,77 +383,126 @@ impl<'arena, 'env> Context<'arena, 'env> {
                 self.solve(*var1, spine1, &value0)
             }
 
-            (_, _) => Err(Error::Mismatch),
+            (_, _) => Err(Error::Mismatch(Mismatch::new(&value0, &value1))),
         }
     }
 
-    /// Unify two elimination spines.
+    /// Check that `value0` is a subtype of `value1`, updating the solution
+    /// environment if necessary.
+    ///
+    /// Universes are cumulative, so types in lower universes are also types
+    /// in higher universes. This is lifted through the output types of
+    /// functions and the field types of records, for example making
+    /// `Type -> Type` a subtype of `Type -> Type1`. All other values must be
+    /// equal, as checked by [`Context::unify`].
+    pub fn subtype(
+        &mut self,
+        value0: &ArcValue<'arena>,
+        value1: &ArcValue<'arena>,
+    ) -> Result<(), Error<ArcValue<'arena>>> {
+        if Arc::ptr_eq(value0, value1) {
+            return Ok(());
+        }
+
+        let value0 = self.elim_context().force(value0);
+        let value1 = self.elim_context().force(value1);
+
+        match (value0.as_ref(), value1.as_ref()) {
+            (Value::Universe(level0), Value::Universe(level1)) if level0 <= level1 => Ok(()),
+            (
+                Value::FunType(name, input_type0, output_type0),
+                Value::FunType(_, input_type1, output_type1),
+            ) => {
+                (self.unify(input_type0, input_type1))
+                    .map_err(|error| error.within(PathSegment::FunInputType))?;
+                (self.unify_closures(Self::subtype, *name, output_type0, output_type1))
+                    .map_err(|error| error.within(PathSegment::FunOutputType))
+            }
+            (Value::RecordType(labels0, types0), Value::RecordType(labels1, types1))
+                if labels0 == labels1 =>
+            {
+                self.unify_telescopes(Self::subtype, labels0, types0, types1)
+            }
+            (_, _) => self.unify(&value0, &value1),
+        }
+    }
+
+    /// Unify the elimination spines of two stuck values.
     fn unify_spines(
         &mut self,
+        value0: &ArcValue<'arena>,
+        value1: &ArcValue<'arena>,
         spine0: &[Elim<'arena>],
         spine1: &[Elim<'arena>],
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error<ArcValue<'arena>>> {
         if spine0.len() != spine1.len() {
-            return Err(Error::Mismatch);
+            return Err(Error::Mismatch(Mismatch::new(value0, value1)));
         }
-        for (elim0, elim1) in Iterator::zip(spine0.iter(), spine1.iter()) {
+        for (index, (elim0, elim1)) in Iterator::zip(spine0.iter(), spine1.iter()).enumerate() {
             match (elim0, elim1) {
                 (Elim::FunApp(input_expr0), Elim::FunApp(input_expr1)) => {
-                    self.unify(input_expr0, input_expr1)?;
+                    (self.unify(input_expr0, input_expr1))
+                        .map_err(|error| error.within(PathSegment::FunArg(index + 1)))?;
                 }
                 (Elim::RecordProj(label0), Elim::RecordProj(label1)) if label0 == label1 => {}
                 (_, _) => {
-                    return Err(Error::Mismatch);
+                    return Err(Error::Mismatch(Mismatch::new(value0, value1)));
                 }
             }
         }
         Ok(())
     }
 
-    /// Unify two [closures][Closure].
+    /// Unify two [closures][Closure], comparing their bodies with `compare`.
     fn unify_closures(
         &mut self,
+        compare: Compare<'arena, 'env>,
+        name: Option<StringId>,
         closure0: &Closure<'arena>,
         closure1: &Closure<'arena>,
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error<ArcValue<'arena>>> {
         let var = Arc::new(Value::rigid_var(self.rigid_exprs.next_global()));
         let value0 = self.elim_context().apply_closure(closure0, var.clone());
         let value1 = self.elim_context().apply_closure(closure1, var);
 
         self.rigid_exprs.push();
-        let result = self.unify(&value0, &value1);
+        let result = compare(self, &value0, &value1);
         self.rigid_exprs.pop();
 
-        result
+        result.map_err(|error| error.under_binder(name))
     }
 
-    /// Unify two [telescopes][Telescope].
+    /// Unify two [telescopes][Telescope] with the same `labels`, comparing
+    /// their entries with `compare`.
     fn unify_telescopes(
         &mut self,
+        compare: Compare<'arena, 'env>,
+        labels: &[StringId],
         telescope0: &Telescope<'arena>,
         telescope1: &Telescope<'arena>,
-    ) -> Result<(), Error> {
-        if telescope0.len() != telescope1.len() {
-            return Err(Error::Mismatch);
-        }
-
+    ) -> Result<(), Error<ArcValue<'arena>>> {
         let initial_rigid_len = self.rigid_exprs;
         let mut telescope0 = telescope0.clone();
         let mut telescope1 = telescope1.clone();
+        let mut index = 0;
 
         while let Some(((value0, next_telescope0), (value1, next_telescope1))) = Option::zip(
             self.elim_context().split_telescope(telescope0),
             self.elim_context().split_telescope(telescope1),
         ) {
-            if let Err(error) = self.unify(&value0, &value1) {
+            if let Err(error) = compare(self, &value0, &value1) {
                 self.rigid_exprs.truncate(initial_rigid_len);
-                return Err(error);
+                // Earlier fields are bound in the types of later fields
+                let error = error.within(PathSegment::RecordField(labels[index]));
+                return Err((labels[..index].iter().rev())
+                    .fold(error, |error, label| error.under_binder(Some(*label))));
             }
 
             let var = Arc::new(Value::rigid_var(self.rigid_exprs.next_global()));
             telescope0 = next_telescope0(var.clone());
             telescope1 = next_telescope1(var);
             self.rigid_exprs.push();
+            index += 1;
         }
 
         self.rigid_exprs.truncate(initial_rigid_len);
@@ -368,9 +516,10 @@ impl<'arena, 'env> Context<'arena, 'env> {
     /// ```
     fn unify_fun_lit(
         &mut self,
+        name: Option<StringId>,
         output_expr: &Closure<'arena>,
         value: &ArcValue<'arena>,
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error<ArcValue<'arena>>> {
         let var = Arc::new(Value::rigid_var(self.rigid_exprs.next_global()));
         let value = self.elim_context().fun_app(value.clone(), var.clone());
         let output_expr = self.elim_context().apply_closure(output_expr, var);
@@ -379,7 +528,7 @@ impl<'arena, 'env> Context<'arena, 'env> {
         let result = self.unify(&output_expr, &value);
         self.rigid_exprs.pop();
 
-        result
+        result.map_err(|error| error.under_binder(name).within(PathSegment::FunOutputExpr))
     }
 
     /// Unify a record literal with a value, using eta-conversion.
@@ -392,10 +541,11 @@ impl<'arena, 'env> Context<'arena, 'env> {
         labels: &[StringId],
         exprs: &[ArcValue<'arena>],
         value: &ArcValue<'arena>,
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error<ArcValue<'arena>>> {
         for (label, expr) in Iterator::zip(labels.iter(), exprs.iter()) {
             let field_value = self.elim_context().record_proj(value.clone(), *label);
-            self.unify(expr, &field_value)?;
+            (self.unify(expr, &field_value))
+                .map_err(|error| error.within(PathSegment::RecordField(*label)))?;
         }
         Ok(())
     }
@@ -417,7 +567,7 @@ impl<'arena, 'env> Context<'arena, 'env> {
         flexible_var: GlobalVar,
         spine: &[Elim<'arena>],
         value: &ArcValue<'arena>,
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error<ArcValue<'arena>>> {
         self.init_renaming(spine)?;
         let term = self.rename(flexible_var, value)?;
         let fun_term = self.fun_intros(spine, term);
@@ -535,7 +685,7 @@ impl<'arena, 'env> Context<'arena, 'env> {
                 })
             }
 
-            Value::Universe => Ok(Term::Universe),
+            Value::Universe(level) => Ok(Term::Universe(*level)),
 
             Value::FunType(input_name, input_type, output_type) => {
                 let input_type = self.rename(flexible_var, input_type)?;
@@ -1,6 +1,7 @@
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use logos::Logos;
 
+use crate::core::UniverseLevel;
 use crate::source::{ByteRange, FileId};
 
 #[derive(Clone, Debug, Logos)]
@@ -9,9 +10,9 @@ pub enum Token<'source> {
     Name(&'source str),
     #[regex(r"\?[a-zA-Z_][a-zA-Z0-9_]*", |lex| &lex.slice()[1..])]
     Hole(&'source str),
-    #[regex(r#""([^"\\]|\\.)*""#, |lex| &lex.slice()[1..(lex.slice().len() - 1)])]
+    #[regex(r#""([^"\\]|\\.)*"[a-zA-Z0-9_]*"#)]
     StringLiteral(&'source str),
-    #[regex(r"[+-]?[0-9][a-zA-Z0-9_]*")]
+    #[regex(r"[0-9][a-zA-Z0-9_]*(\.[0-9][a-zA-Z0-9_]*)?")]
     NumberLiteral(&'source str),
 
     #[token("fun")]
@@ -24,27 +25,69 @@ pub enum Token<'source> {
     KeywordOverlap,
     #[token("Type")]
     KeywordType,
+    #[regex(r"Type[0-9]+", |lex| lex.slice()[4..].parse().ok())]
+    TypeLevel(UniverseLevel),
     #[token("true")]
     KeywordTrue,
     #[token("false")]
     KeywordFalse,
 
+    #[token("&")]
+    Ampersand,
+    #[token("&&")]
+    AmpersandAmpersand,
+    #[token("!")]
+    Bang,
+    #[token("!=")]
+    BangEquals,
+    #[token("^")]
+    Caret,
     #[token(":")]
     Colon,
     #[token(",")]
     Comma,
     #[token("=")]
     Equals,
+    #[token("==")]
+    EqualsEquals,
     #[token("=>")]
     EqualsGreater,
+    #[token("/")]
+    ForwardSlash,
     #[token(".")]
     FullStop,
+    #[token(">")]
+    Greater,
+    #[token(">=")]
+    GreaterEquals,
+    #[token(">>")]
+    GreaterGreater,
+    #[token("-")]
+    Hyphen,
+    /// A `-` that is the sign of a number literal, eg. `-1` in `f -1`
+    PrefixHyphen,
     #[token("->")]
     HyphenGreater,
+    #[token("<")]
+    Less,
+    #[token("<=")]
+    LessEquals,
     #[token("<-")]
     LessHyphen,
+    #[token("<<")]
+    LessLess,
+    #[token("|")]
+    Pipe,
+    #[token("||")]
+    PipePipe,
+    #[token("+")]
+    Plus,
+    /// A `+` that is the sign of a number literal, eg. `+1` in `f +1`
+    PrefixPlus,
     #[token(";")]
     Semicolon,
+    #[token("*")]
+    Star,
     #[token("_")]
     Underscore,
 
@@ -93,14 +136,52 @@ impl Error {
 pub fn tokens<'source>(
     source: &'source str,
 ) -> impl 'source + Iterator<Item = Result<Spanned<Token<'source>, usize>, Error>> {
-    Token::lexer(source)
-        .spanned()
-        .map(|(token, range)| match token {
+    let mut tokens = Token::lexer(source).spanned().peekable();
+    // The end of the previous token, if it was the end of an operand
+    let mut operand_end = None;
+
+    std::iter::from_fn(move || {
+        let (token, range) = tokens.next()?;
+
+        // Signs are lexed as prefixes of number literals if they are directly
+        // followed by a number literal, but not directly preceded by an
+        // operand. This means that `f -1` is an application, while `x-1` and
+        // `x - 1` are both subtractions.
+        let is_sign = match tokens.peek() {
+            Some((Token::NumberLiteral(_), next_range)) => {
+                next_range.start == range.end && operand_end != Some(range.start)
+            }
+            _ => false,
+        };
+        let token = match token {
+            Token::Hyphen if is_sign => Token::PrefixHyphen,
+            Token::Plus if is_sign => Token::PrefixPlus,
+            token => token,
+        };
+
+        operand_end = match token {
+            Token::Name(_)
+            | Token::Hole(_)
+            | Token::StringLiteral(_)
+            | Token::NumberLiteral(_)
+            | Token::KeywordType
+            | Token::TypeLevel(_)
+            | Token::KeywordTrue
+            | Token::KeywordFalse
+            | Token::Underscore
+            | Token::CloseBrace
+            | Token::CloseBracket
+            | Token::CloseParen => Some(range.end),
+            _ => None,
+        };
+
+        Some(match token {
             Token::Error => Err(Error::UnexpectedCharacter {
                 range: ByteRange::new(range.start, range.end),
             }),
             token => Ok((range.start, token, range.end)),
         })
+    })
 }
 
 impl<'source> Token<'source> {
@@ -117,14 +198,35 @@ impl<'source> Token<'source> {
             Token::KeywordMatch => "match",
             Token::KeywordOverlap => "overlap",
             Token::KeywordType => "Type",
+            Token::TypeLevel(_) => "Type",
+            Token::Ampersand => "&",
+            Token::AmpersandAmpersand => "&&",
+            Token::Bang => "!",
+            Token::BangEquals => "!=",
+            Token::Caret => "^",
             Token::Colon => ":",
             Token::Comma => ",",
             Token::Equals => "=>",
+            Token::EqualsEquals => "==",
             Token::EqualsGreater => "=>",
+            Token::ForwardSlash => "/",
             Token::FullStop => ".",
+            Token::Greater => ">",
+            Token::GreaterEquals => ">=",
+            Token::GreaterGreater => ">>",
+            Token::Hyphen => "-",
+            Token::PrefixHyphen => "-",
             Token::HyphenGreater => "->",
+            Token::Less => "<",
+            Token::LessEquals => "<=",
             Token::LessHyphen => "<-",
+            Token::LessLess => "<<",
+            Token::Pipe => "|",
+            Token::PipePipe => "||",
+            Token::Plus => "+",
+            Token::PrefixPlus => "+",
             Token::Semicolon => ";",
+            Token::Star => "*",
             Token::Underscore => "_",
             Token::OpenBrace => "{",
             Token::CloseBrace => "}",
@@ -2,7 +2,7 @@ use pretty::{Doc, DocAllocator, DocBuilder, DocPtr, RefDoc};
 use scoped_arena::Scope;
 use std::cell::RefCell;
 
-use crate::surface::{Pattern, Term};
+use crate::surface::{BinOp, Pattern, Term};
 use crate::{StringId, StringInterner};
 
 /// Term precedences
@@ -11,10 +11,40 @@ pub enum Prec {
     Top = 0,
     Let,
     Fun,
+    Or,
+    And,
+    Cmp,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
+    Add,
+    Mul,
+    Prefix,
     App,
     Atomic,
 }
 
+impl Prec {
+    /// The precedences of a binary operator, along with the precedences of its
+    /// left and right operands.
+    pub fn bin_op(op: BinOp) -> (Prec, Prec, Prec) {
+        match op {
+            BinOp::Or => (Prec::Or, Prec::Or, Prec::And),
+            BinOp::And => (Prec::And, Prec::And, Prec::Cmp),
+            BinOp::Eq | BinOp::Neq | BinOp::Lt | BinOp::Lte | BinOp::Gt | BinOp::Gte => {
+                (Prec::Cmp, Prec::BitOr, Prec::BitOr)
+            }
+            BinOp::BitOr => (Prec::BitOr, Prec::BitOr, Prec::BitXor),
+            BinOp::BitXor => (Prec::BitXor, Prec::BitXor, Prec::BitAnd),
+            BinOp::BitAnd => (Prec::BitAnd, Prec::BitAnd, Prec::Shift),
+            BinOp::Shl | BinOp::Shr => (Prec::Shift, Prec::Shift, Prec::Add),
+            BinOp::Add | BinOp::Sub => (Prec::Add, Prec::Add, Prec::Mul),
+            BinOp::Mul | BinOp::Div => (Prec::Mul, Prec::Mul, Prec::Prefix),
+        }
+    }
+}
+
 const INDENT: isize = 4;
 
 pub struct Context<'interner, 'arena> {
@@ -37,12 +67,12 @@ impl<'interner, 'arena> Context<'interner, 'arena> {
         }
     }
 
-    fn pattern<Range>(&'arena self, pattern: &Pattern<Range>) -> DocBuilder<'arena, Self> {
+    fn pattern<Range>(&'arena self, pattern: &Pattern<'_, Range>) -> DocBuilder<'arena, Self> {
         match pattern {
             Pattern::Placeholder(_) => self.text("_"),
             Pattern::Name(_, name) => self.string_id(*name),
-            Pattern::StringLiteral(_, number) => self.string_id(*number),
-            Pattern::NumberLiteral(_, number) => self.string_id(*number),
+            Pattern::StringLiteral(_, string) => self.text(string.to_string()),
+            Pattern::NumberLiteral(_, number) => self.text(number.to_string()),
             Pattern::BooleanLiteral(_, boolean) => match *boolean {
                 true => self.text("true"),
                 false => self.text("false"),
@@ -53,7 +83,7 @@ impl<'interner, 'arena> Context<'interner, 'arena> {
     fn ann_pattern<Range>(
         &'arena self,
         prec: Prec,
-        pattern: &Pattern<Range>,
+        pattern: &Pattern<'_, Range>,
         r#type: Option<&Term<'_, Range>>,
     ) -> DocBuilder<'arena, Self> {
         match r#type {
@@ -136,7 +166,8 @@ impl<'interner, 'arena> Context<'interner, 'arena> {
                 self.text(","),
                 self.text("}"),
             ),
-            Term::Universe(_) => self.text("Type"),
+            Term::Universe(_, 0) => self.text("Type"),
+            Term::Universe(_, level) => self.text(format!("Type{}", level)),
             Term::FunType(_, input_pattern, input_type, output_type) => self.paren(
                 prec > Prec::Fun,
                 self.concat([
@@ -155,7 +186,7 @@ impl<'interner, 'arena> Context<'interner, 'arena> {
             Term::Arrow(_, input_type, output_type) => self.paren(
                 prec > Prec::Fun,
                 self.concat([
-                    self.term_prec(Prec::App, input_type),
+                    self.term_prec(Prec::Or, input_type),
                     self.softline(),
                     self.text("->"),
                     self.softline(),
@@ -185,6 +216,23 @@ impl<'interner, 'arena> Context<'interner, 'arena> {
                     self.term_prec(Prec::Atomic, input_expr),
                 ]),
             ),
+            Term::UnOp(_, (_, op), expr) => self.paren(
+                prec > Prec::Prefix,
+                self.concat([self.text(op.symbol()), self.term_prec(Prec::Prefix, expr)]),
+            ),
+            Term::BinOp(_, lhs, (_, op), rhs) => {
+                let (op_prec, lhs_prec, rhs_prec) = Prec::bin_op(*op);
+                self.paren(
+                    prec > op_prec,
+                    self.concat([
+                        self.term_prec(lhs_prec, lhs),
+                        self.softline(),
+                        self.text(op.symbol()),
+                        self.space(),
+                        self.term_prec(rhs_prec, rhs),
+                    ]),
+                )
+            }
             Term::RecordType(_, type_fields) => self.sequence(
                 self.text("{"),
                 type_fields.iter().map(|((_, label), r#type)| {
@@ -225,10 +273,8 @@ impl<'interner, 'arena> Context<'interner, 'arena> {
                 self.text(","),
                 self.text("]"),
             ),
-            Term::StringLiteral(_, number) => {
-                self.concat([self.text("\""), self.string_id(*number), self.text("\"")])
-            }
-            Term::NumberLiteral(_, number) => self.string_id(*number),
+            Term::StringLiteral(_, string) => self.text(string.to_string()),
+            Term::NumberLiteral(_, number) => self.text(number.to_string()),
             Term::BooleanLiteral(_, boolean) => match *boolean {
                 true => self.text("true"),
                 false => self.text("false"),
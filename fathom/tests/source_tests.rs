@@ -36,12 +36,21 @@ struct Config {
     update_snapshots: bool,
     #[serde(default = "DEFAULT_TEST_NORMALISATION")]
     test_normalisation: bool,
+    #[serde(default = "DEFAULT_TEST_HEXDUMP")]
+    test_hexdump: bool,
+    #[serde(default = "DEFAULT_TEST_COVERAGE")]
+    test_coverage: bool,
+    #[serde(default = "DEFAULT_DIFF_DATA")]
+    diff_data: Vec<[PathBuf; 2]>,
 }
 
 const DEFAULT_IGNORE: fn() -> bool = || false;
 const DEFAULT_EXIT_CODE: fn() -> i32 = || 0;
 const DEFAULT_EXAMPLE_DATA: fn() -> Vec<String> = || Vec::new();
 const DEFAULT_TEST_NORMALISATION: fn() -> bool = || false;
+const DEFAULT_TEST_HEXDUMP: fn() -> bool = || false;
+const DEFAULT_TEST_COVERAGE: fn() -> bool = || false;
+const DEFAULT_DIFF_DATA: fn() -> Vec<[PathBuf; 2]> = || Vec::new();
 
 struct TestFailure {
     name: &'static str,
@@ -81,12 +90,18 @@ enum Command<'a> {
     Elaborate,
     Normalise,
     ParseData(&'a Path),
+    HexdumpData(&'a Path),
+    CoverageData(&'a Path),
+    DiffData(&'a Path, &'a Path),
 }
 
 impl<'a> Command<'a> {
     pub(crate) fn snap_name(&self) -> &'static str {
         match self {
             Command::Normalise => "norm",
+            Command::HexdumpData(_) => "hexdump",
+            Command::CoverageData(_) => "coverage",
+            Command::DiffData(_, _) => "diff",
             Command::Elaborate | Command::ParseData(_) => "",
         }
     }
@@ -189,6 +204,51 @@ fn run_test(test: &libtest_mimic::Test<TestData>) -> libtest_mimic::Outcome {
                 });
             }
         }
+
+        let data_commands = [
+            (
+                config.test_hexdump,
+                Command::HexdumpData(&test.data.input_file),
+            ),
+            (
+                config.test_coverage,
+                Command::CoverageData(&test.data.input_file),
+            ),
+        ];
+        for (enabled, command) in data_commands {
+            if !enabled {
+                continue;
+            }
+
+            let test_command = TestCommand::new(command, &config, example_file.path());
+            match test_command.run() {
+                Ok(mut test_failures) => failures.append(&mut test_failures),
+                Err(error) => {
+                    failures.push(TestFailure {
+                        name: "unexpected test command error",
+                        details: vec![("std::io::Error", error.to_string())],
+                    });
+                }
+            }
+        }
+    }
+
+    let diff_data = (config.diff_data.iter())
+        .map(|[old_file, new_file]| (base_dir.join(old_file), base_dir.join(new_file)))
+        .collect::<Vec<_>>();
+
+    for (old_file, new_file) in &diff_data {
+        let command = Command::DiffData(&test.data.input_file, new_file);
+        let test_command = TestCommand::new(command, &config, old_file);
+        match test_command.run() {
+            Ok(mut test_failures) => failures.append(&mut test_failures),
+            Err(error) => {
+                failures.push(TestFailure {
+                    name: "unexpected test command error",
+                    details: vec![("std::io::Error", error.to_string())],
+                });
+            }
+        }
     }
 
     failures_to_outcome(&failures)
@@ -234,6 +294,9 @@ impl<'a> TestCommand<'a> {
         let mut failures = Vec::new();
         let mut exe = process::Command::from(self.command);
         exe.arg(self.input_file);
+        if let Command::DiffData(_, new_file) = self.command {
+            exe.arg(new_file);
+        }
 
         match exe.output() {
             Ok(output) => {
@@ -324,6 +387,18 @@ impl<'a> From<Command<'a>> for process::Command {
                 exe.args(["data", "--format"]);
                 exe.arg(format);
             }
+            Command::HexdumpData(format) => {
+                exe.args(["data", "--hexdump", "--format"]);
+                exe.arg(format);
+            }
+            Command::CoverageData(format) => {
+                exe.args(["data", "--coverage", "--format"]);
+                exe.arg(format);
+            }
+            Command::DiffData(format, _) => {
+                exe.args(["diff", "--format"]);
+                exe.arg(format);
+            }
         }
         exe
     }